@@ -0,0 +1,299 @@
+//! Querying the internal `log` table (`rtimelogger log`).
+//!
+//! Rows can be filtered by operation, date, target and count, migration markers are hidden
+//! unless asked for, and operations recorded with an undo [`Snapshot`] are expanded into
+//! field-level changes (old → new) of the affected events.
+
+use crate::db::Event;
+use crate::error::{Error, Result};
+use crate::output::LogRecord;
+use crate::undo::Snapshot;
+use crate::utils::{self, Confirm};
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use rusqlite::{Connection, ToSql};
+use serde::Serialize;
+use std::path::Path;
+
+/// Operation written by the migration runner for every applied migration.
+pub const MIGRATION_OPERATION: &str = "migration_applied";
+
+/// Rows written by migrations: the runner's markers, the legacy `migration` rows and the
+/// notes migration functions log with a `migrate_*` target.
+const MIGRATION_ROWS: &str =
+    "(operation IN ('migration_applied', 'migration') OR COALESCE(target, '') LIKE 'migrate%')";
+
+/// Filters of `rtimelogger log`. The default selects every user action.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Keep only these operations (e.g. `add`, `edit`, `del`); empty = all
+    pub operations: Vec<String>,
+    /// First day included (`YYYY-MM-DD`)
+    pub since: Option<String>,
+    /// Last day included (`YYYY-MM-DD`)
+    pub until: Option<String>,
+    /// Substring searched in the target and the message (e.g. a date)
+    pub target: Option<String>,
+    /// Include migration markers
+    pub migrations: bool,
+    /// Keep the first N matching rows
+    pub limit: Option<usize>,
+    /// Keep the last N matching rows
+    pub tail: Option<usize>,
+}
+
+/// One changed field of one event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub event_id: i32,
+    /// `date kind`, e.g. `2025-10-03 in`
+    pub event: String,
+    /// `time`, `position`, `lunch_break`, ... or `event` for created/deleted events
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "#{} {} {}: {} → {}",
+            self.event_id,
+            self.event,
+            self.field,
+            self.old.as_deref().unwrap_or("∅"),
+            self.new.as_deref().unwrap_or("∅")
+        )
+    }
+}
+
+/// A `log` row with its decoded snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: i32,
+    pub date: String,
+    pub operation: String,
+    pub target: String,
+    pub message: String,
+    /// `done`, `undone` or `discarded` for undoable operations
+    pub state: Option<String>,
+    pub user: Option<String>,
+    pub changes: Vec<FieldChange>,
+}
+
+impl From<&AuditEntry> for LogRecord {
+    fn from(e: &AuditEntry) -> Self {
+        Self {
+            id: e.id,
+            date: e.date.clone(),
+            operation: e.operation.clone(),
+            target: e.target.clone(),
+            message: e.message.clone(),
+            user: e.user.clone(),
+            state: e.state.clone(),
+            changes: e
+                .changes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; "),
+        }
+    }
+}
+
+/// Rows of `log` matching `filter`, in ascending id order.
+pub fn query_log(conn: &Connection, filter: &LogFilter) -> Result<Vec<AuditEntry>> {
+    if filter.limit.is_some() && filter.tail.is_some() {
+        return Err(Error::invalid("--limit and --tail cannot be used together"));
+    }
+    let mut conditions: Vec<String> = Vec::new();
+    let mut params: Vec<String> = Vec::new();
+
+    if !filter.operations.is_empty() {
+        conditions.push(format!(
+            "operation IN ({})",
+            vec!["?"; filter.operations.len()].join(",")
+        ));
+        params.extend(filter.operations.iter().map(|o| o.trim().to_lowercase()));
+    } else if !filter.migrations {
+        conditions.push(format!("NOT {}", MIGRATION_ROWS));
+    }
+    // `log.date` is a UTC RFC 3339 timestamp while the days are local: compare UTC instants
+    if let Some(since) = &filter.since {
+        conditions.push("datetime(date) >= ?".to_string());
+        params.push(local_day_start_utc(checked_date(since, "--since")?));
+    }
+    if let Some(until) = &filter.until {
+        let next = checked_date(until, "--until")?.succ_opt();
+        if let Some(next) = next {
+            conditions.push("datetime(date) < ?".to_string());
+            params.push(local_day_start_utc(next));
+        }
+    }
+    if let Some(t) = &filter.target {
+        conditions.push("(target LIKE ? OR message LIKE ?)".to_string());
+        let pattern = format!("%{}%", t);
+        params.push(pattern.clone());
+        params.push(pattern);
+    }
+
+    let mut sql = String::from(
+        "SELECT id, date, operation, COALESCE(target, ''), message, snapshot, state FROM log",
+    );
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+    }
+    sql.push_str(" ORDER BY id ASC");
+
+    let mut stmt = conn.prepare(&sql)?;
+    let sql_params: Vec<&dyn ToSql> = params.iter().map(|p| p as &dyn ToSql).collect();
+    let mut entries = stmt
+        .query_map(sql_params.as_slice(), |row| {
+            Ok((
+                AuditEntry {
+                    id: row.get(0)?,
+                    date: row.get(1)?,
+                    operation: row.get(2)?,
+                    target: row.get(3)?,
+                    message: row.get(4)?,
+                    state: row.get(6)?,
+                    user: None,
+                    changes: Vec::new(),
+                },
+                row.get::<_, Option<String>>(5)?,
+            ))
+        })?
+        .map(|r| {
+            let (mut entry, snapshot) = r?;
+            if let Some(json) = snapshot {
+                let snap: Snapshot = serde_json::from_str(&json)?;
+                entry.user = snap.user.clone();
+                entry.changes = diff_snapshot(&snap);
+            }
+            Ok(entry)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if let Some(n) = filter.limit {
+        entries.truncate(n);
+    }
+    if let Some(n) = filter.tail {
+        let skip = entries.len().saturating_sub(n);
+        entries.drain(..skip);
+    }
+    Ok(entries)
+}
+
+/// Field-level changes between the `before` and `after` events of a snapshot.
+pub fn diff_snapshot(snap: &Snapshot) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    for old in &snap.before {
+        match snap.after.iter().find(|e| e.id == old.id) {
+            Some(new) => {
+                let fields: [(&str, String, String); 5] = [
+                    ("date", old.date.clone(), new.date.clone()),
+                    ("time", old.time.clone(), new.time.clone()),
+                    ("kind", old.kind.clone(), new.kind.clone()),
+                    ("position", old.position.clone(), new.position.clone()),
+                    (
+                        "lunch_break",
+                        old.lunch_break.to_string(),
+                        new.lunch_break.to_string(),
+                    ),
+                ];
+                for (field, o, n) in fields {
+                    if o != n {
+                        changes.push(FieldChange {
+                            event_id: old.id,
+                            event: describe(old),
+                            field: field.to_string(),
+                            old: Some(o),
+                            new: Some(n),
+                        });
+                    }
+                }
+            }
+            None => changes.push(FieldChange {
+                event_id: old.id,
+                event: describe(old),
+                field: "event".to_string(),
+                old: Some(summary(old)),
+                new: None,
+            }),
+        }
+    }
+    for new in snap
+        .after
+        .iter()
+        .filter(|e| !snap.before.iter().any(|o| o.id == e.id))
+    {
+        changes.push(FieldChange {
+            event_id: new.id,
+            event: describe(new),
+            field: "event".to_string(),
+            old: None,
+            new: Some(summary(new)),
+        });
+    }
+    changes
+}
+
+fn describe(e: &Event) -> String {
+    format!("{} {}", e.date, e.kind)
+}
+
+fn summary(e: &Event) -> String {
+    format!("{} {} lunch={}", e.time, e.position, e.lunch_break)
+}
+
+fn checked_date(s: &str, flag: &str) -> Result<NaiveDate> {
+    utils::iso2date(s).map_err(|_| {
+        Error::invalid(format!(
+            "Invalid {} date: {} (expected YYYY-MM-DD)",
+            flag, s
+        ))
+    })
+}
+
+/// UTC instant at which the local `day` starts, formatted like SQLite `datetime()`.
+fn local_day_start_utc(day: NaiveDate) -> String {
+    let midnight = day.and_time(NaiveTime::MIN);
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc).naive_utc())
+        .unwrap_or(midnight)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// Write the entries to `path` as CSV or JSON (`format`, or guessed from the extension).
+pub fn export_log(
+    entries: &[AuditEntry],
+    path: &Path,
+    format: Option<&str>,
+    overwrite: bool,
+    mode: Confirm,
+) -> Result<()> {
+    let fmt = match format {
+        Some(f) => f.to_lowercase(),
+        None => path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("csv")
+            .to_lowercase(),
+    };
+    crate::export::ensure_writable(path, overwrite, mode)?;
+    match fmt.as_str() {
+        "json" => crate::export::export_json(entries, path),
+        "csv" => {
+            let records: Vec<LogRecord> = entries.iter().map(LogRecord::from).collect();
+            crate::export::export_csv(&records, path)
+        }
+        other => Err(Error::invalid(format!(
+            "Unsupported log export format '{}'. Use csv or json",
+            other
+        ))),
+    }
+}
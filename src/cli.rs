@@ -1,3 +1,5 @@
+use crate::output::OutputFormat;
+use crate::style::ColorChoice;
 use clap::{Parser, Subcommand};
 
 /// Command-line interface definition for rTimelogger
@@ -14,6 +16,37 @@ pub struct Cli {
     #[arg(global = true, long = "db")]
     pub db: Option<String>,
 
+    /// Answer "yes" to every confirmation prompt (deletions); never read from stdin
+    #[arg(global = true, long = "yes", short = 'y')]
+    pub yes: bool,
+
+    /// Never read from stdin: fail instead of asking for a confirmation (combine with --yes to confirm)
+    #[arg(global = true, long = "no-input")]
+    pub no_input: bool,
+
+    /// Suppress progress lines (✅, 🗑️, ...); errors and warnings are still printed to stderr
+    #[arg(global = true, long = "quiet", short = 'q')]
+    pub quiet: bool,
+
+    /// Output format for list/status/log views: table (default), json (JSON lines) or csv.
+    /// Non-table formats also imply --quiet so stdout only carries records.
+    #[arg(global = true, long = "output", value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+
+    /// When to use colors: auto (default: only on a terminal and when NO_COLOR is unset),
+    /// always or never
+    #[arg(global = true, long = "color", value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Pretend the current local date-time is the given one (YYYY-MM-DDTHH:MM); used by tests
+    #[arg(
+        global = true,
+        long = "now-override",
+        value_name = "DATETIME",
+        hide = true
+    )]
+    pub now_override: Option<String>,
+
     /// Run in test mode (no config file update)
     #[arg(global = true, long = "test", hide = true)]
     pub test: bool,
@@ -54,11 +87,52 @@ pub enum Commands {
         /// Print rows from the internal `log` table
         #[arg(long = "print", help = "Print rows from the internal log table")]
         print: bool,
+
+        /// Only these operations (comma separated, e.g. add,edit,del)
+        #[arg(long = "operation", value_name = "OP", value_delimiter = ',')]
+        operation: Vec<String>,
+
+        /// Only rows logged on or after this day (YYYY-MM-DD or an expression like "last-week")
+        #[arg(long, value_name = "DATE", allow_hyphen_values = true)]
+        since: Option<String>,
+
+        /// Only rows logged on or before this day (YYYY-MM-DD or an expression like "yesterday")
+        #[arg(long, value_name = "DATE", allow_hyphen_values = true)]
+        until: Option<String>,
+
+        /// Only rows whose target or message contains TEXT (e.g. a date)
+        #[arg(long, value_name = "TEXT")]
+        target: Option<String>,
+
+        /// Show the first N matching rows
+        #[arg(long, value_name = "N", conflicts_with = "tail")]
+        limit: Option<usize>,
+
+        /// Show the last N matching rows
+        #[arg(long, value_name = "N")]
+        tail: Option<usize>,
+
+        /// Include migration markers (hidden by default)
+        #[arg(long)]
+        migrations: bool,
+
+        /// Write the matching rows to FILE instead of printing them (csv or json)
+        #[arg(long, value_name = "FILE")]
+        export: Option<String>,
+
+        /// Export format: csv, json (default: from the file extension)
+        #[arg(long, value_name = "FORMAT", requires = "export")]
+        format: Option<String>,
+
+        /// Overwrite the export file without confirmation
+        #[arg(long, requires = "export")]
+        overwrite: bool,
     },
 
     /// Add or update a work session
     Add {
-        /// Date (YYYY-MM-DD)
+        /// Date (YYYY-MM-DD, "today", "yesterday", "mon", "last fri", "-2d", ...)
+        #[arg(allow_hyphen_values = true)]
         date: String,
 
         /// (Positional) Position: O=office, R=remote, H=holiday, C=On-Site Client
@@ -88,6 +162,9 @@ pub enum Commands {
         /// Enable edit mode (together with --pair) to update an existing pair's events instead of creating new ones
         #[arg(long = "edit", help = "Edit existing pair (use with --pair)")]
         edit: bool,
+        /// Change the date even if it falls in a locked period (recorded in the log)
+        #[arg(long = "force")]
+        force: bool,
     },
     /// Delete a work session by ID
     Del {
@@ -95,12 +172,18 @@ pub enum Commands {
         #[arg(long = "pair", help = "Pair id to delete for the given date")]
         pair: Option<usize>,
 
-        /// Date (YYYY-MM-DD) to delete (all sessions/events for this date) or required with --pair
+        /// Date (YYYY-MM-DD or an expression like "yesterday") to delete (all sessions/events for this date) or required with --pair
+        #[arg(allow_hyphen_values = true)]
         date: String,
+
+        /// Delete even if the date falls in a locked period (recorded in the log)
+        #[arg(long = "force")]
+        force: bool,
     },
     /// List sessions
     List {
-        #[arg(long, short)]
+        /// Period to list (e.g. "2025", "2025-09", "2025-W41", "last-month", "2025-09-15..2025-10-03")
+        #[arg(long, short, allow_hyphen_values = true)]
         period: Option<String>,
 
         /// Filter by position (O=Office, R=Remote, H=Holiday)
@@ -150,28 +233,183 @@ pub enum Commands {
 
     /// Export work session data in various formats
     Export {
-        /// Export format: csv, json
+        /// Export format: csv, json, xlsx, ods, pdf, ics, html, timew
         #[arg(long, value_name = "FORMAT", default_value = "csv")]
         format: String,
 
-        /// Output file path (absolute path required)
+        /// Output file path (absolute path required), or `-` for stdout (csv, json, ics, html, timew)
         #[arg(long, value_name = "FILE")]
         file: String,
 
-        /// Date range for export (e.g., "2025-01" for January 2025, "2025-01:2025-03" for Jan-Mar 2025)
-        #[arg(long, value_name = "RANGE")]
+        /// Date range for export (e.g. "2025", "2025-01", "this-week", "last-month", "2025-01-15..2025-03-31")
+        #[arg(long, value_name = "RANGE", allow_hyphen_values = true)]
         range: Option<String>,
 
         /// Export EVENTS (from `events` table)
-        #[arg(long, conflicts_with = "sessions")]
+        #[arg(long, group = "dataset")]
         events: bool,
 
         /// Export SESSIONS (from `work_sessions` table)
-        #[arg(long, conflicts_with = "events")]
+        #[arg(long, group = "dataset")]
         sessions: bool,
 
+        /// Export one row per in/out pair with its duration and an unmatched flag
+        #[arg(long, group = "dataset")]
+        summary: bool,
+
+        /// Export one row per day with worked, expected and surplus minutes
+        #[arg(long, group = "dataset")]
+        daily: bool,
+
+        /// Export one row per month with days, worked, expected and surplus minutes
+        #[arg(long, group = "dataset")]
+        monthly: bool,
+
+        /// Columns to export, in this order (e.g. "date,time,kind,position")
+        #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+        columns: Option<Vec<String>>,
+
+        /// Filter by position (O=Office, R=Remote, H=Holiday, ...)
+        #[arg(long)]
+        pos: Option<String>,
+
+        /// Filter events by kind (only with --events)
+        #[arg(long, value_parser = ["in", "out"])]
+        kind: Option<String>,
+
+        /// Render the dataset through a template file, or a built-in one: markdown, html
+        #[arg(long, value_name = "TEMPLATE", conflicts_with = "format")]
+        template: Option<String>,
+
         /// Overwrite output file without confirmation
         #[arg(long, short = 'f')]
         force: bool,
     },
+
+    /// Import absences from an iCalendar file, or worked time from another time tracker
+    Import {
+        /// Input format: ics, timewarrior (`timew export`), toggl or clockify (detailed CSV report)
+        #[arg(long, value_name = "FORMAT", default_value = "ics")]
+        format: String,
+
+        /// Input file path
+        #[arg(long, value_name = "FILE")]
+        file: String,
+
+        /// Only import days in this range (e.g. "2025", "2025-08", "2025-08-{04..22}", "last-month", "2025-08-04..")
+        #[arg(long, value_name = "RANGE", allow_hyphen_values = true)]
+        range: Option<String>,
+
+        /// Only events with one of these categories (comma separated); also selects timed events
+        #[arg(long = "category", value_name = "NAME", value_delimiter = ',')]
+        categories: Vec<String>,
+
+        /// Only events whose summary matches this regular expression; also selects timed events
+        #[arg(long = "summary", value_name = "REGEX")]
+        summary: Option<String>,
+
+        /// Position recorded for imported days (default: H for ics, `default_position` otherwise)
+        #[arg(long = "pos")]
+        pos: Option<String>,
+
+        /// Also import Saturdays and Sundays
+        #[arg(long = "include-weekends")]
+        include_weekends: bool,
+
+        /// Show the days that would be imported without writing anything
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+
+        /// Import into locked periods too (recorded in the log)
+        #[arg(long = "force")]
+        force: bool,
+    },
+
+    /// Printable monthly timesheet (PDF, one page per month) with signature lines
+    Timesheet {
+        /// Month to print (YYYY-MM, "last-month"), or a year (YYYY, "this-year") for one page per month
+        #[arg(value_name = "PERIOD")]
+        period: String,
+
+        /// Output file path (absolute path required)
+        #[arg(long, value_name = "FILE")]
+        file: String,
+
+        /// Overwrite output file without confirmation
+        #[arg(long, short = 'f')]
+        force: bool,
+    },
+
+    /// Interactive full-screen interface: browse months, edit and delete pairs
+    Tui,
+
+    /// Revert the last N add/edit/del operations
+    Undo {
+        /// Number of operations to revert
+        #[arg(value_name = "N", default_value_t = 1)]
+        steps: usize,
+
+        /// Revert even if a touched date falls in a locked period (recorded in the log)
+        #[arg(long = "force")]
+        force: bool,
+    },
+
+    /// Re-apply the last N undone operations
+    Redo {
+        /// Number of operations to re-apply
+        #[arg(value_name = "N", default_value_t = 1)]
+        steps: usize,
+
+        /// Re-apply even if a touched date falls in a locked period (recorded in the log)
+        #[arg(long = "force")]
+        force: bool,
+    },
+
+    /// Lock closed months so their events can no longer be changed
+    Period {
+        #[command(subcommand)]
+        action: PeriodAction,
+    },
+}
+
+impl Commands {
+    /// True when `--force` was given to a command that may change a locked period.
+    pub fn forces_locked(&self) -> bool {
+        match self {
+            Commands::Add { force, .. }
+            | Commands::Del { force, .. }
+            | Commands::Import { force, .. }
+            | Commands::Undo { force, .. }
+            | Commands::Redo { force, .. } => *force,
+            _ => false,
+        }
+    }
+
+    /// True when the command writes its data to stdout (`export --file -`), so progress lines
+    /// must stay off it.
+    pub fn writes_data_to_stdout(&self) -> bool {
+        matches!(self, Commands::Export { file, .. } if file == "-")
+    }
+}
+
+/// Subcommands of `period`.
+#[derive(Subcommand)]
+pub enum PeriodAction {
+    /// Lock a month (YYYY-MM) and store the checksum of its events
+    Lock {
+        /// Month to lock (YYYY-MM or "last-month")
+        period: String,
+    },
+    /// Unlock a previously locked month
+    Unlock {
+        /// Month to unlock (YYYY-MM or "last-month")
+        period: String,
+    },
+    /// List locked months with their checksum
+    List,
+    /// Recompute the checksum of a locked month and compare it with the stored one
+    Verify {
+        /// Month to verify (YYYY-MM or "last-month")
+        period: String,
+    },
 }
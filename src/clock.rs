@@ -0,0 +1,80 @@
+//! Source of "now" for the library.
+//!
+//! Everything time-dependent (audit timestamps, `created_at`, "today" in `list --now`) asks a
+//! [`Clock`] instead of calling `Utc::now()` / `Local::now()` directly, so tests and the hidden
+//! `--now-override` CLI flag can pin the current date and time.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+pub trait Clock: Send + Sync {
+    /// Current local date and time (naive, as typed by the user).
+    fn now_local(&self) -> NaiveDateTime;
+
+    /// Current instant in UTC, used for stored timestamps.
+    fn now_utc(&self) -> DateTime<Utc>;
+
+    /// Current local date.
+    fn today(&self) -> NaiveDate {
+        self.now_local().date()
+    }
+}
+
+/// The real system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_local(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+
+    fn now_utc(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock frozen at a given local date and time (tests, `--now-override`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock {
+    now: NaiveDateTime,
+}
+
+impl FixedClock {
+    pub fn new(now: NaiveDateTime) -> Self {
+        Self { now }
+    }
+
+    /// Parse `YYYY-MM-DDTHH:MM` (seconds optional).
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M")
+            .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S"))
+            .map(Self::new)
+            .map_err(|_| {
+                Error::invalid(format!(
+                    "Invalid date-time: {} (expected YYYY-MM-DDTHH:MM)",
+                    s
+                ))
+            })
+    }
+
+    /// Move the clock forward (or backward with a negative duration).
+    pub fn advance(&mut self, by: Duration) {
+        self.now += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now_local(&self) -> NaiveDateTime {
+        self.now
+    }
+
+    fn now_utc(&self) -> DateTime<Utc> {
+        Local
+            .from_local_datetime(&self.now)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| self.now.and_utc())
+    }
+}
@@ -1,19 +1,26 @@
 use crate::Cli;
 use crate::Commands;
 use chrono::NaiveTime;
+use rtimelogger::audit::{self, LogFilter};
+use rtimelogger::cli::PeriodAction;
+use rtimelogger::clock::Clock;
 use rtimelogger::config::Config;
-use rtimelogger::events::create_missing_event;
+use rtimelogger::error::ValidationError;
+use rtimelogger::ics::{self, ImportAction, ImportFilter};
+use rtimelogger::logic::{PairSummary, compute_event_pairs, compute_event_summaries};
+use rtimelogger::output::{EventRecord, LogRecord, OutputFormat, SessionRecord, print_rows};
+use rtimelogger::timelog::{DayEntry, Timelog, pair_not_found, session_report};
+use rtimelogger::tracker::{self, TrackerFormat};
 use rtimelogger::utils::{
-    compress_backup, describe_position, mins2hhmm, print_separator, weekday_str,
+    Confirm, compress_backup, confirm, describe_position, mins2hhmm, print_separator, weekday_str,
 };
-use rtimelogger::{db, logic, utils};
+use rtimelogger::{Error, Result, db, lock, logic, progress, style, utils};
 use rusqlite::Connection;
-use std::io::{Write, stdin};
+use std::fs;
 use std::path::Path;
 use std::process::Command;
-use std::{fs, io};
 
-pub fn handle_config(cmd: &Commands) -> rusqlite::Result<()> {
+pub fn handle_config(cmd: &Commands) -> Result<()> {
     if let Commands::Config {
         print_config,
         edit_config,
@@ -21,9 +28,9 @@ pub fn handle_config(cmd: &Commands) -> rusqlite::Result<()> {
     } = cmd
     {
         if *print_config {
-            let config = Config::load();
+            let config = Config::load()?;
             println!("📄 Current configuration:");
-            println!("{}", serde_yaml::to_string(&config).unwrap());
+            println!("{}", serde_yaml::to_string(&config)?);
         }
 
         if *edit_config {
@@ -50,7 +57,7 @@ pub fn handle_config(cmd: &Commands) -> rusqlite::Result<()> {
 
             match status {
                 Ok(s) if s.success() => {
-                    println!(
+                    progress!(
                         "✅ Configuration file edited successfully with '{}'",
                         editor_to_use
                     );
@@ -64,7 +71,7 @@ pub fn handle_config(cmd: &Commands) -> rusqlite::Result<()> {
                     let fallback_status = Command::new(&default_editor).arg(&path).status();
                     match fallback_status {
                         Ok(s) if s.success() => {
-                            println!(
+                            progress!(
                                 "✅ Configuration file edited successfully with fallback '{}'",
                                 default_editor
                             );
@@ -85,11 +92,11 @@ pub fn handle_config(cmd: &Commands) -> rusqlite::Result<()> {
 }
 
 /// Handle the `init` command
-pub fn handle_init(cli: &Cli, db_path: &str) -> rusqlite::Result<()> {
+pub fn handle_init(cli: &Cli, db_path: &str) -> Result<()> {
     if let Some(custom) = &cli.db {
-        Config::init_all(Some(custom.clone()), cli.test).unwrap();
+        Config::init_all(Some(custom.clone()), cli.test)?;
     } else {
-        Config::init_all(None, cli.test).unwrap();
+        Config::init_all(None, cli.test)?;
     }
 
     if cli.test {
@@ -97,7 +104,7 @@ pub fn handle_init(cli: &Cli, db_path: &str) -> rusqlite::Result<()> {
         let conn = Connection::open(db_path)?;
         // Initialize DB (creates tables) and run pending migrations
         db::init_db(&conn)?;
-        println!("✅ Test database initialized at {}", db_path);
+        progress!("✅ Test database initialized at {}", db_path);
         // Log the init operation (non-fatal)
         if let Err(e) = db::ttlog(
             &conn,
@@ -112,7 +119,7 @@ pub fn handle_init(cli: &Cli, db_path: &str) -> rusqlite::Result<()> {
         let conn = Connection::open(db_path)?;
         // Initialize DB (creates tables) and run pending migrations
         db::init_db(&conn)?;
-        println!("✅ Database initialized at {}", db_path);
+        progress!("✅ Database initialized at {}", db_path);
         if let Err(e) = db::ttlog(
             &conn,
             "init",
@@ -126,119 +133,68 @@ pub fn handle_init(cli: &Cli, db_path: &str) -> rusqlite::Result<()> {
     Ok(())
 }
 
-pub fn handle_del(cmd: &Commands, conn: &mut Connection) -> rusqlite::Result<()> {
-    if let Commands::Del { pair, date } = cmd {
-        let date = date.trim();
-
-        // validate date
-        if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
-            eprintln!(
-                "\u{274c} Invalid date format: {} (expected YYYY-MM-DD)",
-                date
-            );
-            return Ok(());
-        }
+pub fn handle_del(cmd: &Commands, tl: &mut Timelog, mode: Confirm) -> Result<()> {
+    if let Commands::Del { pair, date, .. } = cmd {
+        let date = resolve_date(date, tl.clock())?;
+        let date = date.as_str();
 
         if let Some(pair_id) = pair {
             // Delete only a given pair for the specified date
-            let events = db::list_events_by_date(conn, date)?;
-            if events.is_empty() {
-                println!("⚠️  No events found for date {}", date);
-                return Ok(());
-            }
-            let enriched = compute_event_pairs(&events);
-            let ids_to_delete: Vec<i32> = enriched
-                .iter()
-                .filter(|e| e.pair == *pair_id)
-                .map(|e| e.event.id)
-                .collect();
-
-            if ids_to_delete.is_empty() {
-                println!("⚠️  Pair {} not found for date {}", pair_id, date);
-                return Ok(());
+            let day = tl.day_summary(date)?;
+            if !day.pairs.iter().any(|p| p.pair == *pair_id) {
+                return Err(pair_not_found(date, *pair_id));
             }
 
             // Confirmation prompt
-            print!(
-                "Are you sure to delete the pair {} of the date {} (N/y) ? ",
+            let prompt = format!(
+                "Are you sure to delete the pair {} of the date {}",
                 pair_id, date
             );
-            let _ = io::stdout().flush();
-            let mut input = String::new();
-            stdin().read_line(&mut input).unwrap_or(0);
-            let choice = input.trim().to_lowercase();
-            if choice != "y" {
+            if !confirm(&prompt, mode)? {
                 println!("Aborted. No rows deleted.");
                 return Ok(());
             }
 
-            match db::delete_events_by_ids_and_recompute_sessions(conn, &ids_to_delete, date) {
-                Ok(rows) => {
-                    println!(
-                        "🗑️  Deleted {} event(s) for pair {} on {}",
-                        rows, pair_id, date
-                    );
-                    let _ = db::ttlog(
-                        conn,
-                        "del",
-                        "Delete pair events on date",
-                        &format!("Deleted {} events for date={} pair={}", rows, date, pair_id),
-                    );
-                }
-                Err(e) => eprintln!("❌ Error deleting pair events: {}", e),
-            }
+            let rows = tl.delete_pair(date, *pair_id)?;
+            progress!(
+                "🗑️  Deleted {} event(s) for pair {} on {}",
+                rows,
+                pair_id,
+                date
+            );
         } else {
             // Delete the entire day records
-            let ev_n = db::count_events_by_date(conn, date).unwrap_or(0);
-            let ws_n = db::count_sessions_by_date(conn, date).unwrap_or(0);
+            let ev_n = db::count_events_by_date(tl.connection(), date).unwrap_or(0);
+            let ws_n = db::count_sessions_by_date(tl.connection(), date).unwrap_or(0);
 
             if ev_n == 0 && ws_n == 0 {
-                println!("⚠️  No events or work_sessions found for date {}", date);
-                return Ok(());
+                return Err(Error::invalid(format!(
+                    "No events or work_sessions found for date {}",
+                    date
+                )));
             }
 
             // Delete all records for the date (work_sessions + events)
-            print!(
-                "Are you sure to delete the records of the date {} (N/y) ? ",
-                date
-            );
-            let _ = io::stdout().flush();
-            let mut input = String::new();
-            stdin().read_line(&mut input).unwrap_or(0);
-            let choice = input.trim().to_lowercase();
-            if choice != "y" {
+            let prompt = format!("Are you sure to delete the records of the date {}", date);
+            if !confirm(&prompt, mode)? {
                 println!("Aborted. No rows deleted.");
                 return Ok(());
             }
 
-            match db::delete_events_by_date(conn, date) {
-                Ok(ev_rows) => match db::delete_sessions_by_date(conn, date) {
-                    Ok(ws_rows) => {
-                        println!(
-                            "🗑️  Deleted {} event(s) and {} work_session(s) for date {}",
-                            ev_rows, ws_rows, date
-                        );
-                        let _ = db::ttlog(
-                            conn,
-                            "del",
-                            "Delete all events and sessions for date",
-                            &format!(
-                                "Deleted date={} events={} work_sessions={}",
-                                date, ev_rows, ws_rows
-                            ),
-                        );
-                    }
-                    Err(e) => eprintln!("❌ Error deleting work_sessions for date {}: {}", date, e),
-                },
-                Err(e) => eprintln!("❌ Error deleting events for date {}: {}", date, e),
-            }
+            let (ev_rows, ws_rows) = tl.delete_day(date)?;
+            progress!(
+                "🗑️  Deleted {} event(s) and {} work_session(s) for date {}",
+                ev_rows,
+                ws_rows,
+                date
+            );
         }
     }
     Ok(())
 }
 
 /// Handle the `add` command
-pub fn handle_add(cmd: &Commands, conn: &mut Connection, config: &Config) -> rusqlite::Result<()> {
+pub fn handle_add(cmd: &Commands, tl: &mut Timelog) -> Result<()> {
     if let Commands::Add {
         date,
         pos_pos,
@@ -251,22 +207,19 @@ pub fn handle_add(cmd: &Commands, conn: &mut Connection, config: &Config) -> rus
         end,
         edit_pair,
         edit,
+        ..
     } = cmd
     {
-        // validate date
-        if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
-            eprintln!(
-                "\u{274c} Invalid date format: {} (expected YYYY-MM-DD)",
-                date
-            );
-            return Ok(());
-        }
+        let date = resolve_date(date, tl.clock())?;
+        let date = date.as_str();
 
         // merge positional and option values
-        let pos = pos.clone().or(pos_pos.clone());
-        let start = start.clone().or(start_pos.clone());
-        let lunch = (*lunch).or(*lunch_pos);
-        let end = end.clone().or(end_pos.clone());
+        let entry = DayEntry {
+            position: pos.clone().or(pos_pos.clone()),
+            start: start.clone().or(start_pos.clone()),
+            lunch: (*lunch).or(*lunch_pos),
+            end: end.clone().or(end_pos.clone()),
+        };
 
         // --------------------------------------------------
         // EDIT MODE (explicit only)
@@ -274,325 +227,62 @@ pub fn handle_add(cmd: &Commands, conn: &mut Connection, config: &Config) -> rus
         if *edit {
             let pair_id = match edit_pair {
                 Some(p) => *p,
-                None => {
-                    eprintln!("\u{26a0}\u{FE0F} Missing --pair <id> with --edit");
-                    return Ok(());
-                }
+                None => return Err(Error::invalid("Missing --pair <id> with --edit")),
             };
 
-            let events = db::list_events_by_date(conn, date)?;
-            if events.is_empty() {
-                eprintln!("\u{26a0}\u{FE0F} No events for date {} to edit", date);
-                return Ok(());
-            }
-
-            let enriched = compute_event_pairs(&events);
-            let mut in_event: Option<db::Event> = None;
-            let mut out_event: Option<db::Event> = None;
-            for ew in enriched.iter().filter(|e| e.pair == pair_id) {
-                if ew.event.kind == "in" {
-                    in_event = Some(ew.event.clone());
-                } else if ew.event.kind == "out" {
-                    out_event = Some(ew.event.clone());
-                }
-            }
-
-            if in_event.is_none() && out_event.is_none() {
-                eprintln!(
-                    "\u{26a0}\u{FE0F} Pair {} not found for date {}",
-                    pair_id, date
-                );
-                return Ok(());
-            }
-
-            // Basic time validations (condensed)
-            if let Some(s) = start.as_ref()
-                && NaiveTime::parse_from_str(s, "%H:%M").is_err()
-            {
-                eprintln!("\u{274c} Invalid start time: {}", s);
-                return Ok(());
-            }
-            if let Some(e_t) = end.as_ref()
-                && NaiveTime::parse_from_str(e_t, "%H:%M").is_err()
-            {
-                eprintln!("\u{274c} Invalid end time: {}", e_t);
-                return Ok(());
-            }
-            if let (Some(s), Some(e_t)) = (start.as_ref(), end.as_ref())
-                && let (Ok(ts), Ok(te)) = (
-                    NaiveTime::parse_from_str(s, "%H:%M"),
-                    NaiveTime::parse_from_str(e_t, "%H:%M"),
-                )
-                && te <= ts
-            {
-                eprintln!(
-                    "\u{274c} End time must be after start time ({} >= {})",
-                    e_t, s
-                );
-                return Ok(());
-            }
-
-            // Create missing events if the user tries to complete the pair
-            if let Some(sv) = start.as_ref()
-                && in_event.is_none()
-            {
-                in_event = create_missing_event(
-                    conn,
-                    date,
-                    sv.as_str(),
-                    "in",
-                    &pos,
-                    out_event.as_ref(),
-                    config,
-                )?;
-            }
-
-            // If user provided an end but the 'out' event is missing, create it using the shared helper
-            if let Some(ev_t) = end.as_ref()
-                && out_event.is_none()
-            {
-                out_event = create_missing_event(
-                    conn,
+            let edited = tl.edit_pair(date, pair_id, &entry)?;
+            if let Some(agg) = edited.position.as_ref() {
+                progress!(
+                    "\u{2705} Position {} set for {} (pair {})",
+                    agg,
                     date,
-                    ev_t.as_str(),
-                    "out",
-                    &pos,
-                    in_event.as_ref(),
-                    config,
-                )?;
-            }
-
-            // Apply edits on existing events
-            let mut changes: Vec<String> = Vec::new();
-
-            if let Some(p) = pos.as_ref() {
-                let p_norm = p.trim().to_uppercase();
-                if p_norm != "O" && p_norm != "R" && p_norm != "H" && p_norm != "C" && p_norm != "M"
-                {
-                    eprintln!("\u{274c} Invalid position: {}", p_norm);
-                    return Ok(());
-                }
-                if let Some(ie) = in_event.as_ref() {
-                    let _ = db::set_event_position(conn, ie.id, &p_norm);
-                }
-                if let Some(oe) = out_event.as_ref() {
-                    let _ = db::set_event_position(conn, oe.id, &p_norm);
-                }
-                // After updating event positions, compute aggregate across all events for that date
-                match db::aggregate_position_from_events(conn, date) {
-                    Ok(Some(agg)) => {
-                        // If aggregate is a single char (O/R/H/C/M), force it into work_sessions
-                        let _ = db::force_set_position(conn, date, &agg);
-                        println!(
-                            "\u{2705} Position {} set for {} (pair {})",
-                            agg, date, pair_id
-                        );
-                    }
-                    Ok(None) => {
-                        // No events for this date (unlikely here) -> fall back to provided p_norm
-                        let _ = db::force_set_position(conn, date, &p_norm);
-                        println!(
-                            "\u{2705} Position {} set for {} (pair {})",
-                            p_norm, date, pair_id
-                        );
-                    }
-                    Err(e) => eprintln!("\u{26a0}\u{FE0F} Failed to aggregate positions: {}", e),
-                }
-                changes.push(format!("pos={}", p_norm));
-            }
-
-            if let (Some(sv), Some(ie)) = (start.as_ref(), in_event.as_ref()) {
-                let _ = db::set_event_time(conn, ie.id, sv.as_str());
-                let _ = db::force_set_start(conn, date, sv.as_str());
-                println!("\u{2705} Start {} updated (pair {})", sv, pair_id);
-                changes.push(format!("start={}", sv));
+                    pair_id
+                );
             }
-
-            if let (Some(ev_t), Some(oe)) = (end.as_ref(), out_event.as_ref()) {
-                let _ = db::set_event_time(conn, oe.id, ev_t.as_str());
-                let _ = db::force_set_end(conn, date, ev_t.as_str());
-                println!("\u{2705} End {} updated (pair {})", ev_t, pair_id);
-                changes.push(format!("end={}", ev_t));
+            if let (Some(sv), Some(_)) = (entry.start.as_ref(), edited.in_event.as_ref()) {
+                progress!("\u{2705} Start {} updated (pair {})", sv, pair_id);
             }
-
-            if let Some(lv) = lunch {
-                if !(0..=90).contains(&lv) {
-                    eprintln!("\u{274c} Invalid lunch break: {}", lv);
-                    return Ok(());
-                }
-                if let Some(oe) = out_event.as_ref() {
-                    let _ = db::set_event_lunch(conn, oe.id, lv);
-                    let _ = db::force_set_lunch(conn, date, lv);
-                    println!("\u{2705} Lunch {} min updated (pair {})", lv, pair_id);
-                    changes.push(format!("lunch={}", lv));
-                }
+            if let (Some(ev_t), Some(_)) = (entry.end.as_ref(), edited.out_event.as_ref()) {
+                progress!("\u{2705} End {} updated (pair {})", ev_t, pair_id);
             }
-
-            if changes.is_empty() {
-                eprintln!(
-                    "\u{26a0}\u{FE0F} No fields provided to edit (use --pos/--in/--out/--lunch)"
-                );
-            } else if let Err(e) = db::ttlog(
-                conn,
-                "edit",
-                "Edit existing pair events",
-                &format!("date={} pair={} | {}", date, pair_id, changes.join(", ")),
-            ) {
-                eprintln!("\u{26a0}\u{FE0F} Failed to log edit: {}", e);
+            if let (Some(lv), Some(_)) = (entry.lunch, edited.out_event.as_ref()) {
+                progress!("\u{2705} Lunch {} min updated (pair {})", lv, pair_id);
             }
-
             return Ok(());
         }
 
         // --------------------------------------------------
         // NORMAL MODE (always create / upsert fields, never implicit edit of existing pair)
         // --------------------------------------------------
-
-        // Apply edits on existing events
-        let mut changes: Vec<String> = Vec::new();
-
-        // Handle position
-        if let Some(p) = pos.as_ref() {
-            let ptrim = p.trim().to_uppercase();
-            if ptrim != "O" && ptrim != "R" && ptrim != "H" && ptrim != "C" {
-                eprintln!(
-                    "\u{274c} Invalid position: {} (use O=office or R=remote or H=Holiday or C=On-Site)",
-                    ptrim
-                );
-                return Ok(());
-            }
-            let _ = db::upsert_position(conn, date, &ptrim);
-            let (pos_string, _) = describe_position(&ptrim);
-            println!("\u{2705} Position {} set for {}", pos_string, date);
-            changes.push(format!("position={}", p));
-        }
-
-        // Handle start time
-        if let Some(sv) = start.as_ref() {
-            if NaiveTime::parse_from_str(sv, "%H:%M").is_err() {
-                eprintln!("\u{274c} Invalid start time: {} (expected HH:MM)", sv);
-                return Ok(());
-            }
-            db::upsert_start(conn, date, sv.as_str())?;
-            println!("\u{2705} Start time {} registered for {}", sv, date);
-            changes.push(format!("start={}", sv));
-
-            // event in
-            let event_pos_owned: Option<String> = pos.as_ref().map(|p| p.trim().to_uppercase());
-            let args = db::AddEventArgs {
-                date,
-                time: sv.as_str(),
-                kind: "in",
-                position: event_pos_owned.as_deref(),
-                source: "cli",
-                meta: None,
-            };
-            if let Err(e) = db::add_event(conn, &args, config) {
-                eprintln!("\u{26a0}\u{FE0F} Failed to insert event (in): {}", e);
-            }
-            // After creating an event, recompute aggregated position and set work_sessions appropriately
-            if let Ok(Some(agg)) = db::aggregate_position_from_events(conn, date) {
-                let _ = db::force_set_position(conn, date, &agg);
-            }
+        let outcome = tl.add(date, &entry)?;
+        if let Some(p) = outcome.position.as_ref() {
+            let (pos_string, _) = describe_position(p);
+            progress!("\u{2705} Position {} set for {}", pos_string, date);
         }
-
-        // Handle lunch
-        if let Some(l) = lunch {
-            if !(0..=90).contains(&l) {
-                eprintln!(
-                    "\u{274c} Invalid lunch break: {} (must be between 0 and 90 minutes)",
-                    l
-                );
-                return Ok(());
-            }
-            db::upsert_lunch(conn, date, l)?;
-            println!("\u{2705} Lunch {} min registered for {}", l, date);
-            changes.push(format!("lunch={}", l));
-
-            // Also, if there is an out event present, set its lunch_break for compatibility
-            match db::last_out_before(conn, date, "23:59") {
-                Ok(Some(out_ev)) => {
-                    if out_ev.lunch_break == 0
-                        && let Err(e) = db::set_event_lunch(conn, out_ev.id, l)
-                    {
-                        eprintln!(
-                            "\u{26a0}\u{FE0F} Failed to set lunch on event {}: {}",
-                            out_ev.id, e
-                        );
-                    }
-                }
-                Ok(None) => {}
-                Err(e) => eprintln!(
-                    "\u{26a0}\u{FE0F} Error while searching for last out event: {}",
-                    e
-                ),
-            }
+        if let Some(ev) = outcome.in_event.as_ref() {
+            progress!("\u{2705} Start time {} registered for {}", ev.time, date);
         }
-
-        // Handle end time
-        if let Some(ev_t) = end.as_ref() {
-            if NaiveTime::parse_from_str(ev_t, "%H:%M").is_err() {
-                eprintln!("\u{274c} Invalid end time: {} (expected HH:MM)", ev_t);
-                return Ok(());
-            }
-            db::upsert_end(conn, date, ev_t.as_str())?;
-            println!("\u{2705} End time {} registered for {}", ev_t, date);
-            changes.push(format!("end={}", ev_t));
-
-            let event_pos_owned: Option<String> = pos.as_ref().map(|p| p.trim().to_uppercase());
-            let args = db::AddEventArgs {
-                date,
-                time: ev_t.as_str(),
-                kind: "out",
-                position: event_pos_owned.as_deref(),
-                source: "cli",
-                meta: None,
-            };
-            match db::add_event(conn, &args, config) {
-                Ok(event_id) => {
-                    if let Some(l) = lunch
-                        && l > 0
-                        && let Err(e) = db::set_event_lunch(conn, event_id as i32, l)
-                    {
-                        eprintln!(
-                            "\u{26a0}\u{FE0F} Failed to set lunch on out event {}: {}",
-                            event_id, e
-                        );
-                    }
-                }
-                Err(err) => {
-                    eprintln!("\u{26a0}\u{FE0F} Failed to insert event (out): {}", err);
-                }
-            }
-
-            // Recompute aggregate position after inserting out event
-            if let Ok(Some(agg)) = db::aggregate_position_from_events(conn, date) {
-                let _ = db::force_set_position(conn, date, &agg);
-            }
+        if let Some(l) = outcome.lunch {
+            progress!("\u{2705} Lunch {} min registered for {}", l, date);
         }
-
-        if pos.is_none() && start.is_none() && lunch.is_none() && end.is_none() {
-            eprintln!(
-                "\u{26a0}\u{FE0F} Please provide at least one of: position, start, lunch, end (or use --edit --pair)"
-            );
+        if let Some(ev) = outcome.out_event.as_ref() {
+            progress!("\u{2705} End time {} registered for {}", ev.time, date);
         }
 
-        // Log the add operation if we recorded changes
-        if !changes.is_empty() {
-            let msg = format!("date={} | {}", date, changes.join(", "));
-            if let Err(e) = db::ttlog(conn, "add", "Add record on events", &msg) {
-                eprintln!("⚠️ Failed to write internal log: {}", e);
-            }
+        // In quiet mode skip the recap table: scripts only care about the exit code.
+        if utils::is_quiet() {
+            return Ok(());
         }
 
-        // If the user provided only --pos (no events), keep existing behavior; otherwise aggregate handled above.
-        // Retrieve the id of the last session for the given date and print
+        // Retrieve the id of the last session for the given date and print it
+        let conn = tl.connection();
         match conn.prepare("SELECT id FROM work_sessions WHERE date = ?1 ORDER BY id DESC LIMIT 1")
         {
             Ok(mut stmt) => match stmt.query_row([date], |row| row.get::<_, i32>(0)) {
                 Ok(last_id) => {
                     println!();
-                    let _ = handle_list_with_highlight(None, None, conn, config, Some(last_id));
+                    let _ =
+                        handle_list_with_highlight(None, None, conn, tl.config(), Some(last_id));
                 }
                 Err(rusqlite::Error::QueryReturnedNoRows) => {}
                 Err(e) => eprintln!("\u{274c} Error retrieving session id: {}", e),
@@ -604,6 +294,34 @@ pub fn handle_add(cmd: &Commands, conn: &mut Connection, config: &Config) -> rus
     Ok(())
 }
 
+/// Resolve a date expression to an ISO day (`YYYY-MM-DD`), see [`utils::parse_date_expr`].
+pub fn resolve_date(expr: &str, clock: &dyn Clock) -> Result<String> {
+    utils::parse_date_expr(expr, clock.today())
+        .map(|d| utils::date2iso(&d))
+        .map_err(|_| ValidationError::Date(expr.trim().to_string()).into())
+}
+
+/// Resolve a range expression to the period understood by `db::list_sessions`
+/// (`YYYY`, `YYYY-MM` or `YYYY-MM-DD..YYYY-MM-DD`), see [`utils::parse_date_range`].
+pub fn resolve_period(expr: &str, clock: &dyn Clock) -> Result<String> {
+    utils::parse_date_range(expr, clock.today())
+        .map(|r| r.period())
+        .map_err(|_| ValidationError::Period(expr.trim().to_string()).into())
+}
+
+/// Resolve a range expression that must cover exactly one calendar month (`YYYY-MM`).
+pub fn resolve_month(expr: &str, clock: &dyn Clock) -> Result<String> {
+    utils::parse_date_range(expr, clock.today())
+        .ok()
+        .and_then(|r| r.month())
+        .ok_or_else(|| ValidationError::Period(expr.trim().to_string()).into())
+}
+
+fn resolve_range(expr: &str, clock: &dyn Clock) -> Result<utils::DateRange> {
+    utils::parse_date_range(expr, clock.today())
+        .map_err(|_| ValidationError::Date(expr.trim().to_string()).into())
+}
+
 pub struct HandleListArgs {
     pub period: Option<String>,
     pub pos: Option<String>,
@@ -612,18 +330,23 @@ pub struct HandleListArgs {
     pub events: bool,
     pub pairs: Option<usize>,
     pub summary: bool,
+    pub output: OutputFormat,
 }
 
 /// Compatible: wrapper that keeps the existing signature and calls the version with highlight = None
-#[allow(clippy::too_many_arguments)]
 pub fn handle_list(
     args: &HandleListArgs,
     conn: &Connection,
     config: &Config,
-) -> rusqlite::Result<()> {
+    clock: &dyn Clock,
+) -> Result<()> {
+    if !args.output.is_table() {
+        return print_list_records(args, conn, config, clock);
+    }
+
     if args.now {
         // Get today's date in YYYY-MM-DD
-        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let today = utils::date2iso(&clock.today());
 
         let wd_type = match config.show_weekday.as_str() {
             "Short" => 's',
@@ -667,7 +390,8 @@ pub fn handle_list(
             let work_minutes = utils::parse_work_duration_to_minutes(&config.min_work_duration);
             let sep_ch = config.separator_char.chars().next().unwrap_or('-');
             for s in sessions {
-                let (pos_string, pos_color) = describe_position(s.position.as_str());
+                let (pos_string, _) = describe_position(s.position.as_str());
+                let pos_fmt = style::paint_position(&s.position, &format!("{:<16}", pos_string));
                 let has_start = !s.start.trim().is_empty();
                 let has_end = !s.end.trim().is_empty();
 
@@ -681,36 +405,29 @@ pub fn handle_list(
                 if has_start && !has_end {
                     let expected =
                         logic::calculate_expected_exit(&s.start, work_minutes, s.lunch, config);
-                    let lunch_color = if s.lunch > 0 { "\x1b[0m" } else { "\x1b[90m" };
+                    let lunch_color = if s.lunch > 0 { "" } else { style::DIM };
                     let lunch_str = if s.lunch > 0 {
                         mins2hhmm(s.lunch, None).unwrap_or_default()
                     } else {
                         "-".to_string()
                     };
                     let lunch_fmt = format!("{:^5}", lunch_str);
-                    let end_color = if !s.end.is_empty() {
-                        "\x1b[0m"
-                    } else {
-                        "\x1b[90m"
-                    };
+                    let end_color = if !s.end.is_empty() { "" } else { style::DIM };
                     let end_str = if !s.end.is_empty() {
                         s.end
                     } else {
                         "-".to_string()
                     };
                     println!(
-                        "{:>3}: {} | {}{:<16}\x1b[0m | Start {} | {}Lunch {}\x1b[0m | {}End {}\x1b[0m | Expected {} | \x1b[90mSurplus {:^8}\x1b[0m",
+                        "{:>3}: {} | {} | Start {} | {} | {} | Expected {} | {}",
                         s.id,
                         date_shown,
-                        pos_color,
-                        pos_string,
+                        pos_fmt,
                         s.start,
-                        lunch_color,
-                        lunch_fmt,
-                        end_color,
-                        end_str,
+                        style::paint(lunch_color, &format!("Lunch {}", lunch_fmt)),
+                        style::paint(end_color, &format!("End {}", end_str)),
                         expected.format("%H:%M"),
-                        "-"
+                        style::paint(style::DIM, &format!("Surplus {:^8}", "-"))
                     );
                     if utils::is_last_day_of_month(&s.date) {
                         print_separator(sep_ch, 25, 110);
@@ -738,23 +455,19 @@ pub fn handle_list(
                         );
                         let surplus_minutes = surplus.num_minutes();
                         total_surplus += surplus_minutes;
-                        let color_code = if surplus_minutes < 0 {
-                            "\x1b[31m"
-                        } else {
-                            "\x1b[32m"
-                        };
                         println!(
-                            "{:>3}: {} | {}{:<16}\x1b[0m | Start {} | Lunch {:^5} | End {} | Expected {} | {}Surplus {:^8}\x1b[0m",
+                            "{:>3}: {} | {} | Start {} | Lunch {:^5} | End {} | Expected {} | {}",
                             s.id,
                             date_shown,
-                            pos_color,
-                            pos_string,
+                            pos_fmt,
                             s.start,
                             mins2hhmm(effective_lunch, None).unwrap_or_default(),
                             s.end,
                             expected.format("%H:%M"),
-                            color_code,
-                            format!("{}m", surplus_minutes)
+                            style::paint_surplus(
+                                surplus_minutes,
+                                &format!("Surplus {:^8}", format!("{}m", surplus_minutes))
+                            )
                         );
                     } else {
                         let expected =
@@ -768,33 +481,26 @@ pub fn handle_list(
                         );
                         let surplus_minutes = surplus.num_minutes();
                         total_surplus += surplus_minutes;
-                        let color_code = if surplus_minutes < 0 {
-                            "\x1b[31m"
-                        } else {
-                            "\x1b[32m"
-                        };
                         println!(
-                            "{:>3}: {} | {}{:<16}\x1b[0m | Start {} | Lunch {:^5} | End {} | Expected {} | {}Surplus {:^8}\x1b[0m",
+                            "{:>3}: {} | {} | Start {} | Lunch {:^5} | End {} | Expected {} | {}",
                             s.id,
                             date_shown,
-                            pos_color,
-                            pos_string,
+                            pos_fmt,
                             s.start,
                             mins2hhmm(s.lunch, None).unwrap_or_default(),
                             s.end,
                             expected.format("%H:%M"),
-                            color_code,
-                            format!("{}m", surplus_minutes)
+                            style::paint_surplus(
+                                surplus_minutes,
+                                &format!("Surplus {:^8}", format!("{}m", surplus_minutes))
+                            )
                         );
                     }
                     if utils::is_last_day_of_month(&s.date) {
                         print_separator(sep_ch, 25, 110);
                     }
                 } else {
-                    println!(
-                        "{:>3}: {} | {}{:<16}\x1b[0m | -",
-                        s.id, date_shown, pos_color, pos_string
-                    );
+                    println!("{:>3}: {} | {} | -", s.id, date_shown, pos_fmt);
                 }
             }
             let (hh, mm) = utils::mins2readable(total_surplus as i32);
@@ -847,6 +553,53 @@ pub fn handle_list(
     handle_list_with_highlight(args.period.clone(), args.pos.clone(), conn, config, None)
 }
 
+/// `list` with `--output json|csv`: same selection as the table views, emitted as records
+/// (sessions, events or pair summaries; see `rtimelogger::output`).
+fn print_list_records(
+    args: &HandleListArgs,
+    conn: &Connection,
+    config: &Config,
+    clock: &dyn Clock,
+) -> Result<()> {
+    let today = utils::date2iso(&clock.today());
+
+    if args.now && (args.details || args.events) {
+        let events = compute_event_pairs(&db::list_events_by_date(conn, &today)?);
+        let rows: Vec<EventRecord> = events.iter().map(EventRecord::from).collect();
+        return print_rows(args.output, &rows);
+    }
+
+    if args.events {
+        let events_all =
+            db::list_events_filtered(conn, args.period.as_deref(), args.pos.as_deref())?;
+        let mut enriched = compute_event_pairs(&events_all);
+        if args.summary {
+            let mut summaries = compute_event_summaries(&enriched);
+            if let Some(pf) = args.pairs {
+                summaries.retain(|r| r.pair == pf);
+            }
+            return print_rows(args.output, &summaries);
+        }
+        if let Some(pf) = args.pairs {
+            enriched.retain(|e| e.pair == pf);
+        }
+        let rows: Vec<EventRecord> = enriched.iter().map(EventRecord::from).collect();
+        return print_rows(args.output, &rows);
+    }
+
+    let sessions = if args.now {
+        db::list_sessions_by_date(conn, &today)?
+    } else {
+        let pos_upper = args.pos.as_ref().map(|p| p.trim().to_uppercase());
+        db::list_sessions(conn, args.period.as_deref(), pos_upper.as_deref())?
+    };
+    let rows: Vec<SessionRecord> = sessions
+        .into_iter()
+        .map(|s| SessionRecord::from(&session_report(s, config)))
+        .collect();
+    print_rows(args.output, &rows)
+}
+
 /// New version: supports printing with `highlight_id: Option<i32>`
 pub fn handle_list_with_highlight(
     period: Option<String>,
@@ -854,7 +607,7 @@ pub fn handle_list_with_highlight(
     conn: &Connection,
     config: &Config,
     highlight_id: Option<i32>,
-) -> rusqlite::Result<()> {
+) -> Result<()> {
     // Normalize pos to uppercase
     let pos_upper = pos.as_ref().map(|p| p.trim().to_uppercase());
 
@@ -898,6 +651,8 @@ pub fn handle_list_with_highlight(
                     logic::month_name(month),
                     year
                 );
+            } else if let Some((from, to)) = p.split_once("..") {
+                println!("📅 Saved sessions from {} to {}:", from, to);
             }
         } else if let Some(p) = pos.as_deref() {
             println!("📅 Saved sessions for position {}:", p);
@@ -916,7 +671,8 @@ pub fn handle_list_with_highlight(
     let sep_ch = config.separator_char.chars().next().unwrap_or('-');
 
     for s in sessions {
-        let (pos_string, pos_color) = describe_position(s.position.as_str());
+        let (pos_string, _) = describe_position(s.position.as_str());
+        let pos_fmt = style::paint_position(&s.position, &format!("{:<16}", pos_string));
         let has_start = !s.start.trim().is_empty();
         let has_end = !s.end.trim().is_empty();
 
@@ -931,7 +687,7 @@ pub fn handle_list_with_highlight(
             // Only start → calculate expected end
             let expected = logic::calculate_expected_exit(&s.start, work_minutes, s.lunch, config);
 
-            let lunch_color = if s.lunch > 0 { "\x1b[0m" } else { "\x1b[90m" };
+            let lunch_color = if s.lunch > 0 { "" } else { style::DIM };
             let lunch_str = if s.lunch > 0 {
                 mins2hhmm(s.lunch, None).unwrap_or_default()
             } else {
@@ -939,11 +695,7 @@ pub fn handle_list_with_highlight(
             };
             let lunch_fmt = format!("{:^5}", lunch_str);
 
-            let end_color = if !s.end.is_empty() {
-                "\x1b[0m"
-            } else {
-                "\x1b[90m"
-            };
+            let end_color = if !s.end.is_empty() { "" } else { style::DIM };
             let end_str = if !s.end.is_empty() {
                 s.end
             } else {
@@ -952,18 +704,15 @@ pub fn handle_list_with_highlight(
             let end_fmt = format!("{:^5}", end_str);
 
             println!(
-                "{:>3}: {} | {}{:<16}\x1b[0m | Start {} | {}Lunch {}\x1b[0m | {}End {}\x1b[0m | Expected {} | \x1b[90mSurplus {:^8}\x1b[0m",
+                "{:>3}: {} | {} | Start {} | {} | {} | Expected {} | {}",
                 s.id,
                 date_shown,
-                pos_color,
-                pos_string,
+                pos_fmt,
                 s.start,
-                lunch_color,
-                lunch_fmt,
-                end_color,
-                end_fmt,
+                style::paint(lunch_color, &format!("Lunch {}", lunch_fmt)),
+                style::paint(end_color, &format!("End {}", end_fmt)),
                 expected.format("%H:%M"),
-                "-",
+                style::paint(style::DIM, &format!("Surplus {:^8}", "-")),
             );
             // If this date is the last day of the month, print a separator after it
             if utils::is_last_day_of_month(&s.date) {
@@ -993,14 +742,6 @@ pub fn handle_list_with_highlight(
                 let surplus_minutes = surplus.num_minutes();
                 total_surplus += surplus_minutes;
 
-                let color_code = if surplus_minutes < 0 {
-                    "\x1b[31m" // red
-                } else if surplus_minutes > 0 {
-                    "\x1b[32m" // green
-                } else {
-                    "\x1b[0m"
-                };
-
                 let formatted_surplus = if surplus_minutes == 0 {
                     "0".to_string()
                 } else {
@@ -1015,17 +756,15 @@ pub fn handle_list_with_highlight(
                 let lunch_fmt = format!("{:^5}", lunch_str);
 
                 println!(
-                    "{:>3}: {} | {}{:<16}\x1b[0m | Start {} | Lunch {} | End {} | Expected {} | Surplus {}{:>4} min\x1b[0m",
+                    "{:>3}: {} | {} | Start {} | Lunch {} | End {} | Expected {} | Surplus {}",
                     s.id,
                     date_shown,
-                    pos_color,
-                    pos_string,
+                    pos_fmt,
                     s.start,
                     lunch_fmt,
                     s.end,
                     expected.format("%H:%M"),
-                    color_code,
-                    formatted_surplus
+                    style::paint_surplus(surplus_minutes, &format!("{:>4} min", formatted_surplus))
                 );
                 if utils::is_last_day_of_month(&s.date) {
                     print_separator(sep_ch, 25, 110);
@@ -1035,16 +774,21 @@ pub fn handle_list_with_highlight(
                 let lunch_fmt = format!("{:^5}", "-".to_string());
 
                 println!(
-                    "{:>3}: {} | {}{:<16}\x1b[0m | Start {} | \x1b[90mLunch {}\x1b[0m | End {} | \x1b[36mWorked {:>2} h {:02} min\x1b[0m",
+                    "{:>3}: {} | {} | Start {} | {} | End {} | {}",
                     s.id,
                     date_shown,
-                    pos_color,
-                    pos_string,
+                    pos_fmt,
                     s.start,
-                    lunch_fmt,
+                    style::paint(style::DIM, &format!("Lunch {}", lunch_fmt)),
                     s.end,
-                    duration.num_hours(),
-                    duration.num_minutes() % 60
+                    style::paint(
+                        style::INFO,
+                        &format!(
+                            "Worked {:>2} h {:02} min",
+                            duration.num_hours(),
+                            duration.num_minutes() % 60
+                        )
+                    )
                 );
                 if utils::is_last_day_of_month(&s.date) {
                     print_separator(sep_ch, 25, 110);
@@ -1060,16 +804,21 @@ pub fn handle_list_with_highlight(
             let lunch_fmt = format!("{:^5}", lunch_str);
 
             println!(
-                "{:>3}: {} | {}{:<16}\x1b[0m | \x1b[90mStart {:^5} | Lunch {} | End {:^5} | Expected {:^5} | Surplus {:>4} min\x1b[0m",
+                "{:>3}: {} | {} | {}",
                 s.id,
                 date_shown,
-                pos_color,
-                pos_string,
-                if has_start { &s.start } else { "-" },
-                lunch_fmt,
-                if has_end { &s.end } else { "-" },
-                "-",
-                "-",
+                pos_fmt,
+                style::paint(
+                    style::DIM,
+                    &format!(
+                        "Start {:^5} | Lunch {} | End {:^5} | Expected {:^5} | Surplus {:>4} min",
+                        if has_start { &s.start } else { "-" },
+                        lunch_fmt,
+                        if has_end { &s.end } else { "-" },
+                        "-",
+                        "-",
+                    )
+                )
             );
             if utils::is_last_day_of_month(&s.date) {
                 print_separator(sep_ch, 25, 110);
@@ -1082,12 +831,6 @@ pub fn handle_list_with_highlight(
         print_separator(sep_ch, 25, 110);
 
         if total_surplus != 0 {
-            let color_code = if total_surplus < 0 {
-                "\x1b[31m" // red
-            } else {
-                "\x1b[32m" // green
-            };
-
             let (hh, mm) = utils::mins2readable(total_surplus as i32);
             let formatted_total = format!(
                 "{}{}h {}m",
@@ -1099,8 +842,8 @@ pub fn handle_list_with_highlight(
             println!(
                 "{:>119}",
                 format!(
-                    "Σ Total surplus: {}{:>4}\x1b[0m",
-                    color_code, formatted_total
+                    "Σ Total surplus: {}",
+                    style::paint_surplus(total_surplus, &format!("{:>4}", formatted_total))
                 ),
             );
         } else {
@@ -1112,38 +855,326 @@ pub fn handle_list_with_highlight(
 }
 
 /// Print rows from the internal `log` table when requested
-pub fn handle_log(cmd: &Commands, conn: &Connection) -> rusqlite::Result<()> {
-    if matches!(cmd, Commands::Log { print: true }) {
-        let mut stmt = conn.prepare_cached(
-            "SELECT id, date, operation, target, message FROM log ORDER BY id ASC",
-        )?;
-        let rows = stmt.query_map([], |row| {
-            Ok((
-                row.get::<_, i32>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, String>(4)?,
-            ))
-        })?;
-
-        println!("📜 Internal log:");
-        for r in rows {
-            let (id, date, operation, target, message) = r?;
-            if target.is_empty() {
-                println!("{:>3}: {} | {} | {}", id, date, operation, message);
-            } else {
-                println!(
-                    "{:>3}: {} | {} ({}) | {}",
-                    id, date, operation, target, message
-                );
-            }
+pub fn handle_log(
+    cmd: &Commands,
+    conn: &Connection,
+    clock: &dyn Clock,
+    output: OutputFormat,
+    mode: Confirm,
+) -> Result<()> {
+    let Commands::Log {
+        print,
+        operation,
+        since,
+        until,
+        target,
+        limit,
+        tail,
+        migrations,
+        export,
+        format,
+        overwrite,
+    } = cmd
+    else {
+        return Ok(());
+    };
+    let filter = LogFilter {
+        operations: operation.clone(),
+        // `--since last-month` starts on the first day, `--until last-month` ends on the last one
+        since: since
+            .as_deref()
+            .map(|s| resolve_range(s, clock).map(|r| utils::date2iso(&r.from)))
+            .transpose()?,
+        until: until
+            .as_deref()
+            .map(|s| resolve_range(s, clock).map(|r| utils::date2iso(&r.to)))
+            .transpose()?,
+        target: target.clone(),
+        migrations: *migrations,
+        limit: *limit,
+        tail: *tail,
+    };
+    let filtered = !filter.operations.is_empty()
+        || filter.since.is_some()
+        || filter.until.is_some()
+        || filter.target.is_some()
+        || filter.limit.is_some()
+        || filter.tail.is_some()
+        || filter.migrations;
+    if !*print && !filtered && export.is_none() {
+        return Ok(());
+    }
+
+    let entries = audit::query_log(conn, &filter)?;
+
+    if let Some(file) = export {
+        return audit::export_log(
+            &entries,
+            Path::new(file),
+            format.as_deref(),
+            *overwrite,
+            mode,
+        );
+    }
+
+    if !output.is_table() {
+        let rows: Vec<LogRecord> = entries.iter().map(LogRecord::from).collect();
+        return print_rows(output, &rows);
+    }
+
+    println!("📜 Internal log:");
+    for e in &entries {
+        let mut line = format!("{:>3}: {} | {}", e.id, e.date, e.operation);
+        if !e.target.is_empty() {
+            line.push_str(&format!(" ({})", e.target));
+        }
+        line.push_str(&format!(" | {}", e.message));
+        if let Some(user) = &e.user {
+            line.push_str(&format!(" | by {}", user));
         }
+        if let Some(state) = e.state.as_deref().filter(|s| *s != "done") {
+            line.push_str(&format!(" [{}]", state));
+        }
+        println!("{}", line);
+        for c in &e.changes {
+            println!("       {}", style::paint(style::DIM, &format!("↳ {}", c)));
+        }
+    }
+    Ok(())
+}
+
+/// Handle `import --format ics`: preview and create absence records through `Timelog::add`.
+/// Tracker formats are handled by [`handle_tracker_import`].
+pub fn handle_import(cmd: &Commands, tl: &mut Timelog, output: OutputFormat) -> Result<()> {
+    let Commands::Import {
+        format,
+        file,
+        range,
+        categories,
+        summary,
+        pos,
+        include_weekends,
+        dry_run,
+        ..
+    } = cmd
+    else {
+        return Ok(());
+    };
+    let range = range
+        .as_deref()
+        .map(|r| {
+            utils::parse_date_range(r, tl.clock().today())
+                .map(|r| r.bounds())
+                .map_err(|e| Error::invalid(format!("invalid --range: {e}")))
+        })
+        .transpose()?;
+    let position = pos.as_deref().map(|p| p.trim().to_uppercase());
+    if let Some(p) = position.as_deref()
+        && !["O", "R", "H", "C"].contains(&p)
+    {
+        return Err(ValidationError::Position(p.to_string()).into());
+    }
+    if let Some(tracker) = TrackerFormat::parse(format) {
+        if !categories.is_empty() || summary.is_some() || *include_weekends {
+            return Err(Error::invalid(
+                "--category, --summary and --include-weekends only apply to --format ics",
+            ));
+        }
+        return handle_tracker_import(tl, output, tracker, file, range, position, *dry_run);
+    }
+    if !format.eq_ignore_ascii_case("ics") {
+        return Err(Error::invalid(format!(
+            "Unsupported import format '{}'. Use one of: ics, timewarrior, toggl, clockify",
+            format
+        )));
+    }
+    let position = position.unwrap_or_else(|| "H".to_string());
+    let filter = ImportFilter {
+        range,
+        categories: categories.clone(),
+        summary: summary
+            .as_deref()
+            .map(|re| {
+                regex::Regex::new(re)
+                    .map_err(|e| Error::invalid(format!("invalid --summary regex: {e}")))
+            })
+            .transpose()?,
+        include_weekends: *include_weekends,
+    };
+
+    let events = ics::parse_calendar(&fs::read_to_string(file)?)?;
+    let plan = ics::plan_import(tl.connection(), &events, &filter, &position, tl.force())?;
+
+    if output != OutputFormat::Table {
+        print_rows(output, &plan)?;
+    } else if plan.is_empty() {
+        println!("⚠️  No calendar events selected in {}", file);
+    } else {
+        let (label, _) = describe_position(&position);
+        println!("📅 Calendar days from {} (as {}):", file, label);
+        for day in &plan {
+            let action = match day.action {
+                ImportAction::Create if *dry_run => "would create",
+                ImportAction::Create => "create",
+                ImportAction::Exists => "skip: already recorded",
+                ImportAction::HasPunches => "skip: day has punches",
+                ImportAction::Locked => "skip: locked period (use --force)",
+                ImportAction::Duplicate => "skip: duplicate",
+            };
+            println!(
+                "  {} {} | {} | {}",
+                day.date,
+                weekday_str(&day.date, 'm'),
+                day.summary,
+                action
+            );
+        }
+    }
+
+    let to_create: Vec<&str> = plan
+        .iter()
+        .filter(|d| d.action == ImportAction::Create)
+        .map(|d| d.date.as_str())
+        .collect();
+    if *dry_run {
+        progress!(
+            "🔎 Dry run: {} day(s) would be imported, nothing written",
+            to_create.len()
+        );
+        return Ok(());
+    }
+    for date in &to_create {
+        let entry = DayEntry {
+            position: Some(position.clone()),
+            ..Default::default()
+        };
+        tl.add(date, &entry)?;
     }
+    progress!(
+        "✅ Imported {} day(s), skipped {}",
+        to_create.len(),
+        plan.len() - to_create.len()
+    );
     Ok(())
 }
 
-pub fn handle_backup(config: &Config, file: &str, compress: &bool) -> io::Result<()> {
+/// Handle `import --format timewarrior|toggl|clockify`: preview and create the tracked pairs
+/// through `Timelog::import_pair`.
+fn handle_tracker_import(
+    tl: &mut Timelog,
+    output: OutputFormat,
+    tracker: TrackerFormat,
+    file: &str,
+    range: Option<(String, String)>,
+    position: Option<String>,
+    dry_run: bool,
+) -> Result<()> {
+    let parsed = tracker::parse_intervals(tracker, &fs::read_to_string(file)?, &chrono::Local)?;
+    for start in &parsed.open {
+        eprintln!(
+            "⚠️  Skipping the open {} interval started at {}",
+            tracker.source(),
+            start
+        );
+    }
+    let plan = tracker::plan_import(
+        tl.connection(),
+        &parsed.intervals,
+        range.as_ref(),
+        tl.config().min_duration_lunch_break..=tl.config().max_duration_lunch_break,
+        tl.force(),
+    )?;
+
+    if output != OutputFormat::Table {
+        print_rows(output, &plan)?;
+    } else if plan.is_empty() {
+        println!("⚠️  No intervals to import in {}", file);
+    } else {
+        println!("⏱️  Intervals from {} ({}):", file, tracker.source());
+        for pair in &plan {
+            let action = match pair.action {
+                ImportAction::Create if dry_run => "would create",
+                ImportAction::Create => "create",
+                ImportAction::Locked => "skip: locked period (use --force)",
+                _ => "skip: already recorded",
+            };
+            let labels: Vec<&str> = pair
+                .meta
+                .projects
+                .iter()
+                .chain(&pair.meta.tags)
+                .map(String::as_str)
+                .collect();
+            println!(
+                "  {} {} | {}-{} lunch {:>2} | {} | {}",
+                pair.date,
+                weekday_str(&pair.date, 'm'),
+                pair.start,
+                pair.end,
+                pair.lunch,
+                labels.join(", "),
+                action
+            );
+        }
+    }
+
+    let to_create: Vec<_> = plan
+        .iter()
+        .filter(|p| p.action == ImportAction::Create)
+        .collect();
+    if dry_run {
+        progress!(
+            "🔎 Dry run: {} pair(s) would be imported, nothing written",
+            to_create.len()
+        );
+        return Ok(());
+    }
+    for pair in &to_create {
+        tl.import_pair(pair, position.as_deref(), tracker.source())?;
+    }
+    progress!(
+        "✅ Imported {} pair(s), skipped {}",
+        to_create.len(),
+        plan.len() - to_create.len()
+    );
+    Ok(())
+}
+
+/// Undo (or redo, when `redo` is true) the last `steps` operations.
+pub fn handle_undo(tl: &mut Timelog, steps: usize, redo: bool) -> Result<()> {
+    if steps == 0 {
+        return Err(Error::invalid("N must be at least 1"));
+    }
+    let reverted = if redo {
+        tl.redo(steps)?
+    } else {
+        tl.undo(steps)?
+    };
+    for r in &reverted {
+        progress!(
+            "{} {} #{}: {}",
+            if redo {
+                "↪️  Redone"
+            } else {
+                "↩️  Undone"
+            },
+            r.operation,
+            r.log_id,
+            r.message
+        );
+    }
+    if reverted.len() < steps {
+        eprintln!(
+            "⚠️  Only {} of {} operation(s) could be {}",
+            reverted.len(),
+            steps,
+            if redo { "redone" } else { "undone" }
+        );
+    }
+    Ok(())
+}
+
+pub fn handle_backup(config: &Config, file: &str, compress: &bool) -> Result<()> {
     let src = Path::new(&config.database);
     let dest = Path::new(file);
 
@@ -1157,7 +1188,7 @@ pub fn handle_backup(config: &Config, file: &str, compress: &bool) -> io::Result
     }
 
     fs::copy(src, dest)?;
-    println!("✅ Backup created: {}", dest.display());
+    progress!("✅ Backup created: {}", dest.display());
 
     // If compress is active → get the name of the compressed file
     let final_path = if *compress {
@@ -1174,7 +1205,7 @@ pub fn handle_backup(config: &Config, file: &str, compress: &bool) -> io::Result
                     e
                 );
             } else {
-                println!(
+                progress!(
                     "🗑️ Original uncompressed backup deleted: {}",
                     dest.display()
                 );
@@ -1201,174 +1232,7 @@ pub fn handle_backup(config: &Config, file: &str, compress: &bool) -> io::Result
     Ok(())
 }
 
-/// Support struct to enrich JSON output and compute pair/unmatched
-#[derive(serde::Serialize, Clone)]
-struct EventWithPair {
-    #[serde(flatten)]
-    event: db::Event,
-    pair: usize,
-    unmatched: bool,
-}
-
-/// Compute pair ids (per-date sequence) and unmatched flag for a slice of events.
-/// Rules:
-///  - Every 'in' event opens a new pair with an incremented pair id (per date) and unmatched=true
-///  - The first subsequent 'out' closes the earliest open pair (FIFO) and uses the same pair id,
-///    setting unmatched=false for both the 'in' and the 'out'
-///  - An 'out' without a preceding 'in' creates a new pair id with unmatched=true
-fn compute_event_pairs(events: &[db::Event]) -> Vec<EventWithPair> {
-    use std::collections::VecDeque;
-    let mut result: Vec<EventWithPair> = Vec::with_capacity(events.len());
-    let mut current_date = String::new();
-    let mut open_in_queue: VecDeque<usize> = VecDeque::new();
-    let mut pair_counter: usize = 0;
-    for ev in events {
-        if ev.date != current_date {
-            // reset for a new date
-            current_date = ev.date.clone();
-            open_in_queue.clear();
-            pair_counter = 0;
-        }
-        match ev.kind.as_str() {
-            "in" => {
-                pair_counter += 1;
-                result.push(EventWithPair {
-                    event: ev.clone(),
-                    pair: pair_counter,
-                    unmatched: true,
-                });
-                open_in_queue.push_back(result.len() - 1);
-            }
-            "out" => {
-                if let Some(in_idx) = open_in_queue.pop_front() {
-                    let pair_id = result[in_idx].pair;
-                    result[in_idx].unmatched = false; // closed match
-                    result.push(EventWithPair {
-                        event: ev.clone(),
-                        pair: pair_id,
-                        unmatched: false,
-                    });
-                } else {
-                    pair_counter += 1; // orphan out
-                    result.push(EventWithPair {
-                        event: ev.clone(),
-                        pair: pair_counter,
-                        unmatched: true,
-                    });
-                }
-            }
-            _ => {
-                pair_counter += 1;
-                result.push(EventWithPair {
-                    event: ev.clone(),
-                    pair: pair_counter,
-                    unmatched: true,
-                });
-            }
-        }
-    }
-    result
-}
-
-#[derive(serde::Serialize, Clone, Debug)]
-struct SummaryRow {
-    date: String,
-    pair: usize,
-    position: String,
-    start: String,
-    end: String,
-    lunch_minutes: i32,
-    duration_minutes: i32,
-    unmatched: bool,
-}
-
-fn compute_event_summaries(enriched: &[EventWithPair]) -> Vec<SummaryRow> {
-    use std::collections::BTreeMap;
-    #[derive(Default)]
-    struct Accum {
-        date: String,
-        pair: usize,
-        position: String,
-        start: Option<String>,
-        end: Option<String>,
-        lunch: i32,
-        unmatched_in: bool,
-        unmatched_out: bool,
-    }
-    let mut map: BTreeMap<(String, usize), Accum> = BTreeMap::new();
-    for e in enriched {
-        let key = (e.event.date.clone(), e.pair);
-        let acc = map.entry(key.clone()).or_insert_with(|| Accum {
-            date: key.0.clone(),
-            pair: key.1,
-            position: String::new(),
-            start: None,
-            end: None,
-            lunch: 0,
-            unmatched_in: false,
-            unmatched_out: false,
-        });
-        if e.event.kind == "in" {
-            if acc.start.is_none() {
-                acc.start = Some(e.event.time.clone());
-            }
-            if acc.position.is_empty() {
-                acc.position = e.event.position.clone();
-            }
-            if e.unmatched {
-                acc.unmatched_in = true;
-            }
-        } else if e.event.kind == "out" {
-            if acc.end.is_none() {
-                acc.end = Some(e.event.time.clone());
-            }
-            if acc.position.is_empty() {
-                acc.position = e.event.position.clone();
-            }
-            if e.event.lunch_break > 0 {
-                acc.lunch = e.event.lunch_break;
-            }
-            if e.unmatched {
-                acc.unmatched_out = true;
-            }
-        }
-    }
-    let mut rows: Vec<SummaryRow> = Vec::new();
-    for (_, acc) in map.into_iter() {
-        let unmatched = (acc.start.is_some() && acc.end.is_none())
-            || (acc.start.is_none() && acc.end.is_some());
-        // Compute duration
-        let mut duration_minutes = 0;
-        if let (Some(s), Some(e)) = (acc.start.as_ref(), acc.end.as_ref())
-            && let (Ok(st), Ok(et)) = (
-                NaiveTime::parse_from_str(s, "%H:%M"),
-                NaiveTime::parse_from_str(e, "%H:%M"),
-            )
-        {
-            let mut diff = (et - st).num_minutes() as i32;
-            if acc.lunch > 0 {
-                diff -= acc.lunch;
-            }
-            if diff < 0 {
-                diff = 0;
-            }
-            duration_minutes = diff;
-        }
-        rows.push(SummaryRow {
-            date: acc.date,
-            pair: acc.pair,
-            position: acc.position,
-            start: acc.start.unwrap_or_else(|| "-".to_string()),
-            end: acc.end.unwrap_or_else(|| "-".to_string()),
-            lunch_minutes: acc.lunch,
-            duration_minutes,
-            unmatched,
-        });
-    }
-    rows
-}
-
-fn print_events_summary(rows: &[SummaryRow], title: &str) {
+fn print_events_summary(rows: &[PairSummary], title: &str) {
     println!("\u{1F4CA} {}:", title);
     if rows.is_empty() {
         println!("(no pairs)");
@@ -1564,3 +1428,73 @@ fn print_events_table(events: &[db::Event], title: &str) {
     }
     print_events_table_with_pairs(&plain, &map, title, None);
 }
+
+/// Handle `period lock|unlock|list|verify`
+pub fn handle_period(action: &PeriodAction, tl: &Timelog, output: OutputFormat) -> Result<()> {
+    let conn = tl.connection();
+    match action {
+        PeriodAction::Lock { period } => {
+            let period = resolve_month(period, tl.clock())?;
+            let locked = lock::lock_period(conn, tl.clock(), &period)?;
+            progress!(
+                "🔒 Period {} locked ({} → {}, {} events)",
+                locked.period,
+                locked.start,
+                locked.end,
+                locked.events
+            );
+            println!("sha256: {}", locked.checksum);
+        }
+        PeriodAction::Unlock { period } => {
+            let period = resolve_month(period, tl.clock())?;
+            let unlocked = lock::unlock_period(conn, tl.clock(), &period)?;
+            progress!("🔓 Period {} unlocked", unlocked.period);
+        }
+        PeriodAction::List => {
+            let locks = lock::list_locks(conn)?;
+            if output != OutputFormat::Table {
+                return print_rows(output, &locks);
+            }
+            if locks.is_empty() {
+                println!("⚠️  No locked periods");
+                return Ok(());
+            }
+            println!("🔒 Locked periods:");
+            for l in &locks {
+                println!(
+                    "  {} | {} → {} | {:>4} events | locked {}{} | sha256 {}",
+                    l.period,
+                    l.start,
+                    l.end,
+                    l.events,
+                    l.locked_at,
+                    l.locked_by
+                        .as_deref()
+                        .map(|u| format!(" by {}", u))
+                        .unwrap_or_default(),
+                    l.checksum
+                );
+            }
+        }
+        PeriodAction::Verify { period } => {
+            let period = resolve_month(period, tl.clock())?;
+            let v = lock::verify(conn, &period)?;
+            if output != OutputFormat::Table {
+                print_rows(output, std::slice::from_ref(&v))?;
+            }
+            if !v.is_valid() {
+                return Err(Error::invalid(format!(
+                    "Checksum mismatch for {}: locked {} ({} events), now {} ({} events)",
+                    v.period, v.expected, v.expected_events, v.actual, v.actual_events
+                )));
+            }
+            progress!(
+                "✅ Period {} matches its lock ({} events, sha256 {})",
+                v.period,
+                v.actual_events,
+                v.actual
+            );
+        }
+    }
+    Ok(())
+}
@@ -1,7 +1,8 @@
+use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs;
-use std::io::{self, Write};
+use std::io::Write;
 use std::path::PathBuf;
 
 pub mod migrate; // use submodule at src/config/migrate.rs
@@ -18,6 +19,50 @@ pub struct Config {
     #[serde(default = "default_separator_char")]
     pub separator_char: String,
     pub show_weekday: String,
+    /// Colors used for positions and surplus in tables (see [`crate::style`])
+    #[serde(default)]
+    pub colors: ColorTheme,
+    /// Employee details printed on `rtimelogger timesheet`
+    #[serde(default)]
+    pub employee: EmployeeInfo,
+}
+
+/// Employee details (`employee:` section). Empty values leave a blank to fill in by hand.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmployeeInfo {
+    pub name: String,
+    pub id: String,
+    pub company: String,
+    pub manager: String,
+}
+
+/// Color theme (`colors:` section). Each value is a color spec such as `blue`,
+/// `on magenta bright_white bold` or raw SGR parameters like `45;97;1`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorTheme {
+    pub office: String,
+    pub remote: String,
+    pub client: String,
+    pub holiday: String,
+    pub mixed: String,
+    pub surplus_positive: String,
+    pub surplus_negative: String,
+}
+
+impl Default for ColorTheme {
+    fn default() -> Self {
+        Self {
+            office: "blue".to_string(),
+            remote: "cyan".to_string(),
+            client: "yellow".to_string(),
+            holiday: "on magenta bright_white bold".to_string(),
+            mixed: "magenta".to_string(),
+            surplus_positive: "green".to_string(),
+            surplus_negative: "red".to_string(),
+        }
+    }
 }
 
 fn default_min_lunch() -> i32 {
@@ -41,6 +86,8 @@ impl Default for Config {
             max_duration_lunch_break: default_max_lunch(),
             separator_char: default_separator_char(),
             show_weekday: "None".to_string(),
+            colors: ColorTheme::default(),
+            employee: EmployeeInfo::default(),
         }
     }
 }
@@ -68,14 +115,19 @@ impl Config {
     }
 
     /// Load configuration from file, or return defaults if not found
-    pub fn load() -> Self {
+    pub fn load() -> Result<Self> {
         let path = Self::config_file();
 
         if path.exists() {
-            let content = fs::read_to_string(&path).expect("❌ Failed to read configuration file");
-            serde_yaml::from_str(&content).expect("❌ Failed to parse configuration file")
+            let content = fs::read_to_string(&path)?;
+            serde_yaml::from_str(&content).map_err(|e| {
+                Error::Config(format!(
+                    "Failed to parse configuration file {:?}: {}",
+                    path, e
+                ))
+            })
         } else {
-            Config {
+            Ok(Config {
                 database: Self::database_file().to_string_lossy().to_string(),
                 default_position: "O".to_string(),
                 min_work_duration: "8h".to_string(),
@@ -83,12 +135,14 @@ impl Config {
                 max_duration_lunch_break: 90,
                 separator_char: default_separator_char(),
                 show_weekday: "None".to_string(),
-            }
+                colors: ColorTheme::default(),
+                employee: EmployeeInfo::default(),
+            })
         }
     }
 
     /// Initialize configuration and database files
-    pub fn init_all(custom_name: Option<String>, is_test: bool) -> io::Result<()> {
+    pub fn init_all(custom_name: Option<String>, is_test: bool) -> Result<()> {
         let dir = Self::config_dir();
         fs::create_dir_all(&dir)?;
 
@@ -112,14 +166,16 @@ impl Config {
             max_duration_lunch_break: 90,
             separator_char: default_separator_char(),
             show_weekday: "None".to_string(),
+            colors: ColorTheme::default(),
+            employee: EmployeeInfo::default(),
         };
 
         // Write config file
         if !is_test {
-            let yaml = serde_yaml::to_string(&config).unwrap();
+            let yaml = serde_yaml::to_string(&config)?;
             let mut file = fs::File::create(Self::config_file())?;
             file.write_all(yaml.as_bytes())?;
-            println!("✅ Config file: {:?}", Self::config_file());
+            crate::progress!("✅ Config file: {:?}", Self::config_file());
         }
 
         // Create empty DB file if not exists
@@ -127,7 +183,7 @@ impl Config {
             fs::File::create(&db_path)?;
         }
 
-        println!("✅ Database:    {:?}", db_path);
+        crate::progress!("✅ Database:    {:?}", db_path);
 
         Ok(())
     }
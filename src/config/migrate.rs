@@ -1,5 +1,6 @@
 // filepath: x:\Development\workspace\RustProjects\rtimelog\src\config\migrate.rs
-use rusqlite::{Connection, Error, OptionalExtension};
+use crate::error::{Error, Result};
+use rusqlite::{Connection, OptionalExtension};
 use serde_yaml::Value;
 use std::fs;
 use std::io;
@@ -106,7 +107,7 @@ const VERSION: &str = "20251006_0010_rename_rtimelog_to_rtimelogger";
 /// Run the config migration once. Idempotent when used via run_pending_migrations which
 /// already checks applied versions. This function returns Err on critical failures so the
 /// caller (migration runner) won't mark the migration as applied.
-pub fn run_config_migration(conn: &Connection) -> Result<(), Error> {
+pub fn run_config_migration(conn: &Connection) -> Result<()> {
     conn.execute_batch(
         "CREATE TABLE IF NOT EXISTS log (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -131,13 +132,13 @@ pub fn run_config_migration(conn: &Connection) -> Result<(), Error> {
     // 1) rename directory if needed
     if old_dir.exists() && !new_dir.exists() {
         fs::rename(&old_dir, &new_dir).map_err(|e| {
-            Error::SqliteFailure(
-                rusqlite::ffi::Error::new(1),
-                Some(format!(
+            Error::Io(io::Error::new(
+                e.kind(),
+                format!(
                     "Failed to rename config dir {:?} -> {:?}: {}",
                     old_dir, new_dir, e
-                )),
-            )
+                ),
+            ))
         })?;
     }
 
@@ -147,13 +148,13 @@ pub fn run_config_migration(conn: &Connection) -> Result<(), Error> {
 
     // attempt to move/copy file if present; helper will be a no-op if source missing or target exists
     move_or_copy(&old_conf, &new_conf).map_err(|e| {
-        Error::SqliteFailure(
-            rusqlite::ffi::Error::new(1),
-            Some(format!(
+        Error::Io(io::Error::new(
+            e.kind(),
+            format!(
                 "Failed to move config file {:?} -> {:?}: {}",
                 old_conf, new_conf, e
-            )),
-        )
+            ),
+        ))
     })?;
 
     // 3) update database name inside YAML
@@ -165,25 +166,22 @@ pub fn run_config_migration(conn: &Connection) -> Result<(), Error> {
                 }
             }
             Err(e) => {
-                return Err(Error::SqliteFailure(
-                    rusqlite::ffi::Error::new(1),
-                    Some(format!(
-                        "Failed to update config database reference {:?}: {}",
-                        new_conf, e
-                    )),
-                ));
+                return Err(Error::Config(format!(
+                    "Failed to update config database reference {:?}: {}",
+                    new_conf, e
+                )));
             }
         }
     }
 
     if !actions.is_empty() {
-        println!("ℹ️  Migration ({}): {}", VERSION, actions.join("; "));
+        crate::progress!("ℹ️  Migration ({}): {}", VERSION, actions.join("; "));
     }
 
     Ok(())
 }
 
-pub fn run_fs_migration_with(new_dir: PathBuf, old_dir: PathBuf) -> io::Result<()> {
+pub fn run_fs_migration_with(new_dir: PathBuf, old_dir: PathBuf) -> Result<()> {
     // All the logic of run_fs_migration, but using the provided parameters
     // ----------------------------------------------------------
     if old_dir.exists() && !new_dir.exists() && fs::rename(&old_dir, &new_dir).is_err() {
@@ -255,8 +253,8 @@ pub fn run_fs_migration_with(new_dir: PathBuf, old_dir: PathBuf) -> io::Result<(
 
 /// Filesystem-only migration: rename old config dir/file and DB from 'rtimelog' to 'rtimelogger'.
 /// This does NOT open or write to the database; it only manipulates files so it can run before
-/// a DB connection exists. It returns io::Result so the caller can decide how to handle failures.
-pub fn run_fs_migration() -> io::Result<()> {
+/// a DB connection exists. It returns an error so the caller can decide how to handle failures.
+pub fn run_fs_migration() -> Result<()> {
     run_fs_migration_with(super::Config::config_dir(), old_config_dir())
 }
 
@@ -270,7 +268,7 @@ fn old_config_dir() -> PathBuf {
     }
 }
 
-pub fn migrate_add_show_weekday(conn: &Connection) -> Result<(), Error> {
+pub fn migrate_add_show_weekday(conn: &Connection) -> Result<()> {
     let version = "20251008_0011_add_show_weekday";
 
     // check if already applied
@@ -283,12 +281,7 @@ pub fn migrate_add_show_weekday(conn: &Connection) -> Result<(), Error> {
 
     let conf_file = super::Config::config_file();
     if conf_file.exists() {
-        let content = fs::read_to_string(&conf_file).map_err(|e| {
-            Error::SqliteFailure(
-                rusqlite::ffi::Error::new(1),
-                Some(format!("Failed to read config {:?}: {}", conf_file, e)),
-            )
-        })?;
+        let content = fs::read_to_string(&conf_file)?;
         if let Ok(mut yaml) = serde_yaml::from_str::<Value>(&content)
             && let Some(map) = yaml.as_mapping_mut()
         {
@@ -298,10 +291,7 @@ pub fn migrate_add_show_weekday(conn: &Connection) -> Result<(), Error> {
 
                 // Serialize
                 let serialized = serde_yaml::to_string(&yaml).map_err(|e| {
-                    Error::SqliteFailure(
-                        rusqlite::ffi::Error::new(1),
-                        Some(format!("Failed to serialize config {:?}: {}", conf_file, e)),
-                    )
+                    Error::Config(format!("Failed to serialize config {:?}: {}", conf_file, e))
                 })?;
 
                 // Add YAML comment immediately after the 'show_weekday' line
@@ -320,20 +310,12 @@ pub fn migrate_add_show_weekday(conn: &Connection) -> Result<(), Error> {
                     }
                 }
 
-                fs::write(&conf_file, new_content).map_err(|e| {
-                    Error::SqliteFailure(
-                        rusqlite::ffi::Error::new(1),
-                        Some(format!(
-                            "Failed to write updated config {:?}: {}",
-                            conf_file, e
-                        )),
-                    )
-                })?;
+                fs::write(&conf_file, new_content)?;
             }
         }
     }
 
-    println!(
+    crate::progress!(
         "✅ Migration applied: {} — Add show_weekday parameter to config",
         version
     );
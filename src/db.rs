@@ -1,11 +1,14 @@
-use chrono::{NaiveTime, Utc};
-use rusqlite::{Connection, OptionalExtension, Result, ToSql, params};
-use serde::Serialize;
+use crate::clock::{Clock, SystemClock};
+use crate::error::{Result, ValidationError};
+use crate::lock;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use rusqlite::{Connection, OptionalExtension, ToSql, params};
+use serde::{Deserialize, Serialize};
 mod migrate;
 pub use migrate::run_pending_migrations;
 
 /// Represents a work session entry
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WorkSession {
     pub id: i32,
     pub date: String,
@@ -17,11 +20,12 @@ pub struct WorkSession {
 }
 
 /// Represents a single punch event (in/out)
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Event {
     pub id: i32,
     pub date: String,
     pub time: String,     // HH:MM
+    pub minutes: i32,     // minutes since midnight (normalized `time`)
     pub kind: String,     // "in" or "out"
     pub position: String, // O,R,H,C,M
     pub lunch_break: i32, // minutes, typically set on out
@@ -31,6 +35,31 @@ pub struct Event {
     pub created_at: String, // ISO timestamp
 }
 
+impl Event {
+    /// The event date as a typed `NaiveDate` (None if the stored text is malformed).
+    pub fn naive_date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.date, "%Y-%m-%d").ok()
+    }
+
+    /// The event time as a typed `NaiveTime`, built from the integer `minutes` column.
+    pub fn naive_time(&self) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt((self.minutes / 60) as u32, (self.minutes % 60) as u32, 0)
+    }
+
+    /// Date and time combined (local, naive).
+    pub fn datetime(&self) -> Option<NaiveDateTime> {
+        Some(self.naive_date()?.and_time(self.naive_time()?))
+    }
+
+    pub fn is_in(&self) -> bool {
+        self.kind == "in"
+    }
+
+    pub fn is_out(&self) -> bool {
+        self.kind == "out"
+    }
+}
+
 fn hhmm_to_minutes(s: &str) -> Option<i32> {
     let mut it = s.split(':');
     let h = it.next()?.parse::<i32>().ok()?;
@@ -38,6 +67,14 @@ fn hhmm_to_minutes(s: &str) -> Option<i32> {
     Some(h * 60 + m)
 }
 
+/// Normalize a time string ("9:05", "09:05") into canonical "HH:MM" plus minutes since midnight.
+/// Returns None if the value is not a valid time of day.
+pub fn normalize_time(s: &str) -> Option<(String, i32)> {
+    let t = NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()?;
+    let minutes = hhmm_to_minutes(&t.format("%H:%M").to_string())?;
+    Some((t.format("%H:%M").to_string(), minutes))
+}
+
 fn calculate_work_duration(start: &str, end: &str, lunch: i32) -> Option<i32> {
     let sm = hhmm_to_minutes(start)?;
     let em = hhmm_to_minutes(end)?;
@@ -49,7 +86,7 @@ fn calculate_work_duration(start: &str, end: &str, lunch: i32) -> Option<i32> {
     }
 }
 
-pub fn row_to_worksession(row: &rusqlite::Row) -> Result<WorkSession> {
+pub fn row_to_worksession(row: &rusqlite::Row) -> rusqlite::Result<WorkSession> {
     let start: Option<String> = row.get("start_time")?;
     let end: Option<String> = row.get("end_time")?;
     let lunch: i32 = row.get::<_, Option<i32>>("lunch_break")?.unwrap_or(0);
@@ -71,11 +108,12 @@ pub fn row_to_worksession(row: &rusqlite::Row) -> Result<WorkSession> {
     })
 }
 
-pub(crate) fn row_to_event(row: &rusqlite::Row) -> Result<Event> {
+pub(crate) fn row_to_event(row: &rusqlite::Row) -> rusqlite::Result<Event> {
     Ok(Event {
         id: row.get("id")?,
         date: row.get("date")?,
         time: row.get("time")?,
+        minutes: row.get("minutes")?,
         kind: row.get("kind")?,
         position: row.get("position")?,
         lunch_break: row.get("lunch_break")?,
@@ -96,15 +134,26 @@ fn build_filtered_query(
     let mut conditions = Vec::new();
     let mut params: Vec<String> = Vec::new();
 
+    // Period filters are expressed as a plain date range so SQLite can use the date indexes
     if let Some(p) = period {
         if p.len() == 4 {
-            conditions.push("strftime('%Y', date) = ?".to_string());
-            params.push(p.to_string());
+            conditions.push("date BETWEEN ? AND ?".to_string());
+            params.push(format!("{}-01-01", p));
+            params.push(format!("{}-12-31", p));
         } else if p.len() == 7 {
-            conditions.push("strftime('%Y-%m', date) = ?".to_string());
-            params.push(p.to_string());
+            conditions.push("date BETWEEN ? AND ?".to_string());
+            params.push(format!("{}-01", p));
+            params.push(format!("{}-31", p));
+        } else if let Some((from, to)) = p.split_once("..")
+            && crate::utils::iso2date(from).is_ok()
+            && crate::utils::iso2date(to).is_ok()
+        {
+            // explicit day range, see `utils::DateRange::period`
+            conditions.push("date BETWEEN ? AND ?".to_string());
+            params.push(from.to_string());
+            params.push(to.to_string());
         } else {
-            return Err(rusqlite::Error::InvalidQuery);
+            return Err(ValidationError::Period(p.to_string()).into());
         }
     }
 
@@ -183,7 +232,8 @@ pub fn aggregate_position_from_events(conn: &Connection, date: &str) -> Result<O
     }
 }
 
-/// Insert a new work session
+/// Insert a new work session.
+/// Refused for dates inside a locked period (see [`crate::lock`]).
 pub fn add_session(
     conn: &Connection,
     date: &str,
@@ -192,6 +242,7 @@ pub fn add_session(
     lunch: u32,
     end: &str,
 ) -> Result<()> {
+    lock::ensure_unlocked(conn, date)?;
     conn.execute(
         "INSERT INTO work_sessions (date, position, start_time, lunch_break, end_time)
          VALUES (?1, ?2, ?3, ?4, ?5)",
@@ -200,18 +251,26 @@ pub fn add_session(
     Ok(())
 }
 
+/// Delete a work session by id. Refused for dates inside a locked period (unknown ids pass).
 pub fn delete_session(conn: &Connection, id: i32) -> Result<usize> {
-    conn.execute("DELETE FROM work_sessions WHERE id = ?", [id])
+    if let Some(session) = get_session(conn, id)? {
+        lock::ensure_unlocked(conn, &session.date)?;
+    }
+    Ok(conn.execute("DELETE FROM work_sessions WHERE id = ?", [id])?)
 }
 
 /// Delete all work_sessions for a given date. Returns number of rows deleted.
+/// Refused for dates inside a locked period (see [`crate::lock`]).
 pub fn delete_sessions_by_date(conn: &Connection, date: &str) -> Result<usize> {
-    conn.execute("DELETE FROM work_sessions WHERE date = ?1", params![date])
+    lock::ensure_unlocked(conn, date)?;
+    Ok(conn.execute("DELETE FROM work_sessions WHERE date = ?1", params![date])?)
 }
 
 /// Delete all events for a given date. Returns number of rows deleted.
+/// Refused for dates inside a locked period (see [`crate::lock`]).
 pub fn delete_events_by_date(conn: &Connection, date: &str) -> Result<usize> {
-    conn.execute("DELETE FROM events WHERE date = ?1", params![date])
+    lock::ensure_unlocked(conn, date)?;
+    Ok(conn.execute("DELETE FROM events WHERE date = ?1", params![date])?)
 }
 
 /// Delete events by a list of ids. Returns number of rows deleted.
@@ -219,6 +278,9 @@ pub fn delete_events_by_ids(conn: &Connection, ids: &[i32]) -> Result<usize> {
     if ids.is_empty() {
         return Ok(0);
     }
+    for id in ids {
+        lock::ensure_event_unlocked(conn, *id)?;
+    }
     // Build a query with the appropriate number of placeholders
     let mut sql = String::from("DELETE FROM events WHERE id IN (");
     sql.push_str(&vec!["?"; ids.len()].join(","));
@@ -245,10 +307,11 @@ pub fn list_sessions(
     let params_refs: Vec<&dyn ToSql> = params.iter().map(|s| s as &dyn ToSql).collect();
     let rows = stmt.query_map(params_refs.as_slice(), row_to_worksession)?;
 
-    rows.collect::<Result<Vec<_>, _>>()
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
 }
 
 /// Generic upsert helper for a single field in `work_sessions` table.
+/// Refused for dates inside a locked period (see [`crate::lock`]).
 fn upsert_field<T: ToSql>(
     conn: &Connection,
     date: &str,
@@ -256,6 +319,7 @@ fn upsert_field<T: ToSql>(
     value: T,
     default_pos: &str,
 ) -> Result<()> {
+    lock::ensure_unlocked(conn, date)?;
     let update_sql = format!("UPDATE work_sessions SET {} = ?1 WHERE date = ?2", field);
     let mut stmt = conn.prepare_cached(&update_sql)?;
     let rows = stmt.execute(params![&value, date])?;
@@ -278,6 +342,7 @@ pub fn upsert_position(conn: &Connection, date: &str, pos: &str) -> Result<()> {
 
 /// Insert or update the start time (HH:MM) for a given date.
 pub fn upsert_start(conn: &Connection, date: &str, start: &str) -> Result<()> {
+    lock::ensure_unlocked(conn, date)?;
     // Custom logic: only update if start_time is empty
     let mut stmt = conn.prepare_cached(
         "UPDATE work_sessions SET start_time = ?1 WHERE date = ?2 AND (start_time = '' OR start_time IS NULL)",
@@ -308,8 +373,20 @@ pub fn upsert_end(conn: &Connection, date: &str, end: &str) -> Result<()> {
     upsert_field(conn, date, "end_time", end, "O")
 }
 
+/// Write a row in the internal `log` table, timestamped with the system clock.
 pub fn ttlog(conn: &Connection, operation: &str, target: &str, message: &str) -> Result<()> {
-    let now = Utc::now().to_rfc3339(); // ISO 8601
+    ttlog_with_clock(conn, &SystemClock, operation, target, message)
+}
+
+/// Same as [`ttlog`], taking "now" from the given clock.
+pub fn ttlog_with_clock(
+    conn: &Connection,
+    clock: &dyn Clock,
+    operation: &str,
+    target: &str,
+    message: &str,
+) -> Result<()> {
+    let now = clock.now_utc().to_rfc3339(); // ISO 8601
     let mut stmt = conn.prepare_cached(
         "INSERT INTO log (date, operation, target, message) VALUES (?1, ?2, ?3, ?4)",
     )?;
@@ -326,7 +403,7 @@ pub fn get_session(conn: &Connection, id: i32) -> Result<Option<WorkSession>> {
     match stmt.query_row([id], row_to_worksession) {
         Ok(ws) => Ok(Some(ws)),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(e),
+        Err(e) => Err(e.into()),
     }
 }
 
@@ -336,32 +413,42 @@ pub fn list_sessions_by_date(conn: &Connection, date: &str) -> Result<Vec<WorkSe
     let mut stmt = conn.prepare_cached(query)?;
     let rows = stmt.query_map([date], row_to_worksession)?;
 
-    rows.collect::<Result<Vec<_>, _>>()
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Retrieve a single event by id
+pub fn get_event(conn: &Connection, id: i64) -> Result<Option<Event>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, date, time, minutes, kind, position, lunch_break, pair, source, meta, created_at \
+        FROM events \
+        WHERE id = ?1",
+    )?;
+    Ok(stmt.query_row([id], row_to_event).optional()?)
 }
 
 /// List events for a specific date (ordered by time asc)
 pub fn list_events_by_date(conn: &Connection, date: &str) -> Result<Vec<Event>> {
     let mut stmt = conn.prepare_cached(
-        "SELECT id, date, time, kind, position, lunch_break, pair, source, meta, created_at \
+        "SELECT id, date, time, minutes, kind, position, lunch_break, pair, source, meta, created_at \
         FROM events \
         WHERE date = ?1 \
-        ORDER BY time ASC",
+        ORDER BY minutes ASC",
     )?;
     let rows = stmt.query_map([date], row_to_event)?;
 
-    rows.collect::<Result<Vec<_>, _>>()
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
 }
 
 /// List all events in the database ordered by date and time
 pub fn list_events(conn: &Connection) -> Result<Vec<Event>> {
     let mut stmt = conn.prepare_cached(
-        "SELECT id, date, time, kind, position, lunch_break, pair, source, meta, created_at \
+        "SELECT id, date, time, minutes, kind, position, lunch_break, pair, source, meta, created_at \
         FROM events \
-        ORDER BY date ASC, time ASC",
+        ORDER BY date ASC, minutes ASC",
     )?;
     let rows = stmt.query_map([], row_to_event)?;
 
-    rows.collect::<Result<Vec<_>, _>>()
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
 }
 
 /// List events filtered by optional period (YYYY or YYYY-MM) and position
@@ -370,35 +457,38 @@ pub fn list_events_filtered(
     period: Option<&str>,
     pos: Option<&str>,
 ) -> Result<Vec<Event>> {
-    let base_query = "SELECT id, date, time, kind, position, lunch_break, pair, source, meta, created_at FROM events";
+    let base_query = "SELECT id, date, time, minutes, kind, position, lunch_break, pair, source, meta, created_at FROM events";
     let (mut query, params) = build_filtered_query(base_query, period, pos)?;
 
-    query.push_str(" ORDER BY date ASC, time ASC");
+    query.push_str(" ORDER BY date ASC, minutes ASC");
 
     let mut stmt = conn.prepare_cached(&query)?;
     let param_refs: Vec<&dyn ToSql> = params.iter().map(|s| s as &dyn ToSql).collect();
     let rows = stmt.query_map(param_refs.as_slice(), row_to_event)?;
-    rows.collect::<Result<Vec<_>, _>>()
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
 }
 
 /// Find last out event before a given time on the same date
 pub fn last_out_before(conn: &Connection, date: &str, time: &str) -> Result<Option<Event>> {
     let mut stmt = conn.prepare_cached(
-        "SELECT id, date, time, kind, position, lunch_break, pair, source, meta, created_at \
+        "SELECT id, date, time, minutes, kind, position, lunch_break, pair, source, meta, created_at \
         FROM events \
-        WHERE date = ?1 AND kind = 'out' AND time < ?2 \
-        ORDER BY time DESC \
+        WHERE date = ?1 AND kind = 'out' AND minutes < ?2 \
+        ORDER BY minutes DESC \
         LIMIT 1",
     )?;
-    match stmt.query_row([date, time], row_to_event) {
+    let (_, minutes) =
+        normalize_time(time).ok_or_else(|| ValidationError::Time(time.to_string()))?;
+    match stmt.query_row(params![date, minutes], row_to_event) {
         Ok(ev) => Ok(Some(ev)),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(e),
+        Err(e) => Err(e.into()),
     }
 }
 
 /// Update lunch_break for a specific event (typically an 'out')
 pub fn set_event_lunch(conn: &Connection, event_id: i32, lunch: i32) -> Result<()> {
+    lock::ensure_event_unlocked(conn, event_id)?;
     conn.execute(
         "UPDATE events SET lunch_break = ?1 WHERE id = ?2",
         params![lunch, event_id],
@@ -406,17 +496,21 @@ pub fn set_event_lunch(conn: &Connection, event_id: i32, lunch: i32) -> Result<(
     Ok(())
 }
 
-/// Update time for a specific event
+/// Update time (and the derived `minutes` column) for a specific event
 pub fn set_event_time(conn: &Connection, event_id: i32, new_time: &str) -> Result<()> {
+    let (time, minutes) =
+        normalize_time(new_time).ok_or_else(|| ValidationError::Time(new_time.to_string()))?;
+    lock::ensure_event_unlocked(conn, event_id)?;
     conn.execute(
-        "UPDATE events SET time = ?1 WHERE id = ?2",
-        params![new_time, event_id],
+        "UPDATE events SET time = ?1, minutes = ?2 WHERE id = ?3",
+        params![time, minutes, event_id],
     )?;
     Ok(())
 }
 
 /// Update position for a specific event
 pub fn set_event_position(conn: &Connection, event_id: i32, new_pos: &str) -> Result<()> {
+    lock::ensure_event_unlocked(conn, event_id)?;
     conn.execute(
         "UPDATE events SET position = ?1 WHERE id = ?2",
         params![new_pos, event_id],
@@ -425,6 +519,7 @@ pub fn set_event_position(conn: &Connection, event_id: i32, new_pos: &str) -> Re
 }
 
 // Helper used by force_set_* to update or insert a legacy work_sessions row when forcing a single field.
+// Refused for dates inside a locked period, like the upserts.
 fn force_set_field<T: ToSql>(
     conn: &Connection,
     date: &str,
@@ -432,6 +527,7 @@ fn force_set_field<T: ToSql>(
     value: T,
     default_pos: &str,
 ) -> Result<()> {
+    lock::ensure_unlocked(conn, date)?;
     let sql = format!("UPDATE work_sessions SET {} = ?1 WHERE date = ?2", field);
     let changed = conn.execute(&sql, params![&value, date])?;
     if changed == 0 {
@@ -508,13 +604,25 @@ pub struct AddEventArgs<'a> {
 /// Insert an event and run auto-lunch logic if kind == 'in'.
 /// This function uses a transaction to ensure atomicity. It also performs dual-write
 /// to legacy `work_sessions` via existing upsert_* helpers to keep backwards compatibility.
-#[allow(clippy::too_many_arguments)]
 pub fn add_event(
     conn: &mut Connection,
     args: &AddEventArgs,
     config: &crate::config::Config,
 ) -> Result<i64> {
-    let tx = conn.transaction()?;
+    add_event_with_clock(conn, args, config, &SystemClock)
+}
+
+/// Same as [`add_event`], taking `created_at` and audit timestamps from the given clock.
+pub fn add_event_with_clock(
+    conn: &mut Connection,
+    args: &AddEventArgs,
+    config: &crate::config::Config,
+    clock: &dyn Clock,
+) -> Result<i64> {
+    let (time, minutes) =
+        normalize_time(args.time).ok_or_else(|| ValidationError::Time(args.time.to_string()))?;
+    lock::ensure_unlocked(conn, args.date)?;
+    let tx = conn.savepoint()?;
 
     // Determine position_to_use:
     // - if user provided position (Some) -> use it
@@ -527,43 +635,53 @@ pub fn add_event(
     };
     if args.position.is_none() && args.kind == "out" {
         let mut stmt = tx.prepare_cached(
-            "SELECT position FROM events WHERE date = ?1 AND kind = 'in' AND time <= ?2 ORDER BY time DESC LIMIT 1",
+            "SELECT position FROM events WHERE date = ?1 AND kind = 'in' AND minutes <= ?2 ORDER BY minutes DESC LIMIT 1",
         )?;
         if let Some(found_pos) = stmt
-            .query_row([args.date, args.time], |row| row.get::<_, String>(0))
+            .query_row(params![args.date, minutes], |row| row.get::<_, String>(0))
             .optional()?
         {
             position_to_use = found_pos;
         }
     }
 
-    tx.execute(
-        "INSERT INTO events (date, time, kind, position, lunch_break, source, meta, created_at) VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6, ?7)",
-        params![args.date, args.time, args.kind, position_to_use, args.source, args.meta.unwrap_or(""), Utc::now().to_rfc3339()],
+    // (date, time, kind) is unique: punching the same event twice returns the existing row
+    let inserted = tx.execute(
+        "INSERT INTO events (date, time, minutes, kind, position, lunch_break, source, meta, created_at) VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6, ?7, ?8) \
+        ON CONFLICT(date, time, kind) DO NOTHING",
+        params![args.date, time, minutes, args.kind, position_to_use, args.source, args.meta.unwrap_or(""), clock.now_utc().to_rfc3339()],
     )?;
 
-    let event_id = tx.last_insert_rowid();
+    let event_id = if inserted == 0 {
+        tx.query_row(
+            "SELECT id FROM events WHERE date = ?1 AND time = ?2 AND kind = ?3",
+            params![args.date, time, args.kind],
+            |row| row.get::<_, i64>(0),
+        )?
+    } else {
+        tx.last_insert_rowid()
+    };
 
     // Dual-write to legacy table to ease rollout
     if args.kind == "in" {
         // store start in legacy place
-        let _ = upsert_start(&tx, args.date, args.time);
+        let _ = upsert_start(&tx, args.date, &time);
     } else if args.kind == "out" {
-        let _ = upsert_end(&tx, args.date, args.time);
+        let _ = upsert_end(&tx, args.date, &time);
     }
 
     // If this is an 'in' event, attempt to populate lunch on the previous 'out' (auto-lunch)
     if args.kind == "in"
-        && let Some(prev_out) = last_out_before(&tx, args.date, args.time)?
+        && let Some(prev_out) = last_out_before(&tx, args.date, &time)?
     {
         // Exclude holiday positions
         if prev_out.position != "H" && position_to_use != "H" {
             // Ensure previous out has no lunch yet
             if prev_out.lunch_break == 0 {
                 // Parse times
-                if let (Ok(prev_time), Ok(new_time)) = (
-                    NaiveTime::parse_from_str(&prev_out.time, "%H:%M"),
-                    NaiveTime::parse_from_str(args.time, "%H:%M"),
+                if let (Some(prev_time), Ok(new_time)) = (
+                    prev_out.naive_time(),
+                    NaiveTime::parse_from_str(&time, "%H:%M"),
                 ) {
                     let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
                     let latest = NaiveTime::from_hms_opt(14, 30, 0).unwrap();
@@ -590,7 +708,7 @@ pub fn add_event(
                             );
                             tx.execute(
                                 "INSERT INTO log (date, operation, message) VALUES (?1, ?2, ?3)",
-                                params![Utc::now().to_rfc3339(), "auto_lunch", msg],
+                                params![clock.now_utc().to_rfc3339(), "auto_lunch", msg],
                             )?;
                         }
                     }
@@ -675,8 +793,9 @@ pub fn delete_events_by_ids_and_recompute_sessions(
     if ids.is_empty() {
         return Ok(0);
     }
+    lock::ensure_unlocked(conn, date)?;
 
-    let tx = conn.transaction()?;
+    let tx = conn.savepoint()?;
 
     // Execute delete inside a narrow scope so statement is dropped early
     let deleted = {
@@ -694,10 +813,10 @@ pub fn delete_events_by_ids_and_recompute_sessions(
     let mut remaining: Vec<Event> = Vec::new();
     {
         let mut sel = tx.prepare(
-            "SELECT id, date, time, kind, position, lunch_break, pair, source, meta, created_at \
+            "SELECT id, date, time, minutes, kind, position, lunch_break, pair, source, meta, created_at \
             FROM events \
             WHERE date = ?1 \
-            ORDER BY time ASC",
+            ORDER BY minutes ASC",
         )?;
         let remaining_rows = sel.query_map([date], row_to_event)?;
         for r in remaining_rows {
@@ -711,7 +830,11 @@ pub fn delete_events_by_ids_and_recompute_sessions(
         tx.execute("DELETE FROM work_sessions WHERE date = ?1", params![date])?;
     } else {
         // end_time = max time among remaining events
-        if let Some(max_time) = remaining.iter().map(|e| e.time.clone()).max() {
+        if let Some(max_time) = remaining
+            .iter()
+            .max_by_key(|e| e.minutes)
+            .map(|e| e.time.clone())
+        {
             // Update or insert end_time via existing helper using the transaction
             // We'll use direct SQL to update within the tx (force_set_end uses Connection methods)
             let changed = tx.execute(
@@ -730,9 +853,9 @@ pub fn delete_events_by_ids_and_recompute_sessions(
         let min_time_opt = remaining
             .iter()
             .filter(|e| e.kind == "in")
-            .map(|e| e.time.clone())
-            .min()
-            .or_else(|| remaining.iter().map(|e| e.time.clone()).min());
+            .min_by_key(|e| e.minutes)
+            .or_else(|| remaining.iter().min_by_key(|e| e.minutes))
+            .map(|e| e.time.clone());
         if let Some(min_time) = min_time_opt {
             let changed = tx.execute(
                 "UPDATE work_sessions SET start_time = ?1 WHERE date = ?2",
@@ -750,7 +873,7 @@ pub fn delete_events_by_ids_and_recompute_sessions(
         let last_out = remaining
             .iter()
             .filter(|e| e.kind == "out")
-            .max_by(|a, b| a.time.cmp(&b.time));
+            .max_by_key(|e| e.minutes);
         if let Some(out_ev) = last_out {
             let lunch_val = out_ev.lunch_break;
             let changed = tx.execute(
@@ -1,7 +1,8 @@
 use crate::config::Config;
 use crate::db;
+use crate::error::{Error, Result};
 use chrono::Utc;
-use rusqlite::{Connection, Error, OptionalExtension, Result, ffi, params};
+use rusqlite::{Connection, OptionalExtension, params};
 use serde_yaml::Value;
 use std::collections::HashSet;
 use std::fs;
@@ -61,20 +62,22 @@ fn upgrade_legacy_log_schema(conn: &Connection) -> Result<()> {
             DROP TABLE log_old;
             "#,
         )?;
-        println!("🔄 Upgraded legacy log table: added columns operation/target and migrated data.");
+        crate::progress!(
+            "🔄 Upgraded legacy log table: added columns operation/target and migrated data."
+        );
         return Ok(());
     }
 
     // Case 2: table has operation but lacks target -> add target column
     if has_operation && !has_target {
         conn.execute("ALTER TABLE log ADD COLUMN target TEXT DEFAULT ''", [])?;
-        println!("🔄 Added missing 'target' column to log table.");
+        crate::progress!("🔄 Added missing 'target' column to log table.");
     }
 
     Ok(())
 }
 
-fn query_pairs(stmt: &mut rusqlite::Statement<'_>) -> Result<Vec<(String, String)>, Error> {
+fn query_pairs(stmt: &mut rusqlite::Statement<'_>) -> Result<Vec<(String, String)>> {
     let rows = stmt.query_map([], |row| {
         Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
     })?;
@@ -89,11 +92,9 @@ fn query_pairs(stmt: &mut rusqlite::Statement<'_>) -> Result<Vec<(String, String
 /// Ensure the table used to track applied migrations exists.
 fn ensure_migrations_table(conn: &Connection) -> Result<(), Error> {
     // First attempt to upgrade any legacy log table schema
-    upgrade_legacy_log_schema(conn).map_err(|e| {
-        Error::SqliteFailure(
-            ffi::Error::new(1),
-            Some(format!("Failed to upgrade legacy log table: {}", e)),
-        )
+    upgrade_legacy_log_schema(conn).map_err(|e| Error::Migration {
+        version: "legacy_log_schema".to_string(),
+        source: Box::new(e),
     })?;
     // With the new strategy we use the `log` table to track migrations
     conn.execute_batch(
@@ -106,7 +107,8 @@ fn ensure_migrations_table(conn: &Connection) -> Result<(), Error> {
             message TEXT NOT NULL
         );
         "#,
-    )
+    )?;
+    Ok(())
 }
 
 /// Read already-applied migration versions
@@ -279,6 +281,21 @@ static ALL_MIGRATIONS: &[Migration] = &[
         description: "Add `pair` field to events table to group in/out pairs",
         up: migrate_add_pair_to_events,
     },
+    Migration {
+        version: "20251014_0013_add_minutes_and_unique_index_to_events",
+        description: "Add integer `minutes` column to events, normalize times and add unique index on (date, time, kind)",
+        up: migrate_add_minutes_to_events,
+    },
+    Migration {
+        version: "20251016_0014_add_snapshot_and_state_to_log",
+        description: "Add `snapshot` (JSON before/after events) and `state` (done/undone) columns to log for undo/redo",
+        up: migrate_add_snapshot_to_log,
+    },
+    Migration {
+        version: "20251018_0015_create_locked_periods",
+        description: "Create `locked_periods` table storing closed months and the checksum of their events",
+        up: migrate_create_locked_periods,
+    },
 ];
 
 pub fn run_pending_migrations(conn: &Connection) -> Result<(), Error> {
@@ -309,13 +326,16 @@ pub fn run_pending_migrations(conn: &Connection) -> Result<(), Error> {
     for m in ALL_MIGRATIONS {
         if !applied.contains(m.version) {
             // Apply the migration
-            (m.up)(conn)?;
+            (m.up)(conn).map_err(|e| Error::Migration {
+                version: m.version.to_string(),
+                source: Box::new(e),
+            })?;
             // Mark as applied
             mark_applied(conn, m.version)?;
-            println!("✅ Migration applied: {} — {}", m.version, m.description);
+            crate::progress!("✅ Migration applied: {} — {}", m.version, m.description);
         }
     }
-    println!();
+    crate::progress!();
     Ok(())
 }
 
@@ -340,7 +360,9 @@ fn migrate_to_030_rel(conn: &Connection) -> Result<()> {
     if let Some(sql) = table_sql
         && sql.contains("CHECK (position IN ('O','R'))")
     {
-        println!("⚠️  Old schema detected, migrating work_sessions to support 'H' (Holiday)...");
+        crate::progress!(
+            "⚠️  Old schema detected, migrating work_sessions to support 'H' (Holiday)..."
+        );
 
         conn.execute_batch(
             "
@@ -369,7 +391,7 @@ fn migrate_to_030_rel(conn: &Connection) -> Result<()> {
             "migrate_to_030_rel",
             "Migration table \'work_sessions\' completed.",
         )?;
-        println!("✅ Migration completed successfully.");
+        crate::progress!("✅ Migration completed successfully.");
     }
 
     Ok(())
@@ -383,7 +405,9 @@ fn migrate_to_032_rel(conn: &Connection) -> Result<()> {
     if let Some(sql) = table_sql
         && sql.contains("CHECK(position IN ('O','R','H'))")
     {
-        println!("⚠️  Old schema detected, migrating work_sessions to support 'C' (On-Site)...");
+        crate::progress!(
+            "⚠️  Old schema detected, migrating work_sessions to support 'C' (On-Site)..."
+        );
 
         conn.execute_batch(
             "
@@ -412,7 +436,7 @@ fn migrate_to_032_rel(conn: &Connection) -> Result<()> {
             "migrate_to_032_rel",
             "Migration table \'work_sessions\' completed.",
         )?;
-        println!("✅ Migration completed successfully.");
+        crate::progress!("✅ Migration completed successfully.");
     }
 
     Ok(())
@@ -424,18 +448,9 @@ pub fn migrate_to_033_rel(conn: &Connection) -> Result<(), Error> {
         return Ok(());
     }
 
-    let content = fs::read_to_string(&path).map_err(|e| {
-        Error::SqliteFailure(
-            ffi::Error::new(1), // code "Unknown error"
-            Some(format!("Failed to read config: {}", e)),
-        )
-    })?;
-    let mut value: Value = serde_yaml::from_str(&content).map_err(|e| {
-        Error::SqliteFailure(
-            ffi::Error::new(1),
-            Some(format!("Failed to parse config: {}", e)),
-        )
-    })?;
+    let content = fs::read_to_string(&path)?;
+    let mut value: Value = serde_yaml::from_str(&content)
+        .map_err(|e| Error::Config(format!("Failed to parse config: {}", e)))?;
 
     // If the YAML root is not a mapping (unexpected), skip the migration instead of failing.
     if let Some(obj) = value.as_mapping_mut() {
@@ -458,19 +473,10 @@ pub fn migrate_to_033_rel(conn: &Connection) -> Result<(), Error> {
         return Ok(());
     }
 
-    let new_yaml = serde_yaml::to_string(&value).map_err(|e| {
-        Error::SqliteFailure(
-            ffi::Error::new(1),
-            Some(format!("Failed to serialize config: {}", e)),
-        )
-    })?;
+    let new_yaml = serde_yaml::to_string(&value)
+        .map_err(|e| Error::Config(format!("Failed to serialize config: {}", e)))?;
 
-    fs::write(&path, new_yaml).map_err(|e| {
-        Error::SqliteFailure(
-            ffi::Error::new(1),
-            Some(format!("Failed to write config: {}", e)),
-        )
-    })?;
+    fs::write(&path, new_yaml)?;
 
     db::ttlog(
         conn,
@@ -478,7 +484,7 @@ pub fn migrate_to_033_rel(conn: &Connection) -> Result<(), Error> {
         "migrate_to_033_rel",
         "Migration configuration file completed.",
     )?;
-    println!("✅ Config file migrated: {:?}", path);
+    crate::progress!("✅ Config file migrated: {:?}", path);
 
     Ok(())
 }
@@ -506,7 +512,7 @@ fn migrate_to_034_rel(conn: &Connection) -> Result<()> {
         "migrate_to_034_rel",
         "Added indexes idx_work_sessions_date and idx_work_sessions_position",
     )?;
-    println!("✅ Created indexes for work_sessions (date, position)");
+    crate::progress!("✅ Created indexes for work_sessions (date, position)");
     Ok(())
 }
 
@@ -519,26 +525,23 @@ fn migrate_to_035_rel(conn: &Connection) -> Result<()> {
         return Ok(());
     }
 
-    let content =
-        fs::read_to_string(&path).map_err(|e| Error::ToSqlConversionFailure(Box::new(e)))?;
-    let mut value: Value =
-        serde_yaml::from_str(&content).map_err(|e| Error::ToSqlConversionFailure(Box::new(e)))?;
+    let content = fs::read_to_string(&path)?;
+    let mut value: Value = serde_yaml::from_str(&content)?;
 
     if let Some(map) = value.as_mapping_mut() {
         let key = Value::String("separator_char".to_string());
         if !map.contains_key(&key) {
             map.insert(key.clone(), Value::String("-".to_string()));
             // write back
-            let new_yaml = serde_yaml::to_string(&map)
-                .map_err(|e| Error::ToSqlConversionFailure(Box::new(e)))?;
-            fs::write(&path, new_yaml).map_err(|e| Error::ToSqlConversionFailure(Box::new(e)))?;
+            let new_yaml = serde_yaml::to_string(&map)?;
+            fs::write(&path, new_yaml)?;
             db::ttlog(
                 conn,
                 "migration_applied",
                 "migrate_to_035_rel",
                 "Inserted separator_char into config file",
             )?;
-            println!("✅ Config file updated with separator_char: {:?}", path);
+            crate::progress!("✅ Config file updated with separator_char: {:?}", path);
         }
     }
 
@@ -571,7 +574,7 @@ fn migrate_to_036_create_events(conn: &Connection) -> Result<()> {
         "migrate_to_036_create_events",
         "Created events table",
     )?;
-    println!("✅ Created events table");
+    crate::progress!("✅ Created events table");
 
     Ok(())
 }
@@ -655,7 +658,9 @@ fn migrate_to_038_add_m(conn: &Connection) -> Result<()> {
     if let Some(sql) = table_sql
         && sql.contains("CHECK(position IN ('O','R','H','C'))")
     {
-        println!("⚠️  Old schema detected, migrating work_sessions to support 'M' (Mixed)...");
+        crate::progress!(
+            "⚠️  Old schema detected, migrating work_sessions to support 'M' (Mixed)..."
+        );
         conn.execute_batch(
             "
             ALTER TABLE work_sessions RENAME TO work_sessions_old;
@@ -725,7 +730,7 @@ fn migrate_to_unify_schema_migrations(conn: &Connection) -> Result<()> {
             "migrate_to_unify_schema_migrations",
             "Imported schema_migrations into unified log and dropped legacy table",
         )?;
-        println!("✅ schema_migrations imported into log and dropped.");
+        crate::progress!("✅ schema_migrations imported into log and dropped.");
     }
 
     Ok(())
@@ -740,7 +745,7 @@ fn migrate_add_pair_to_events(conn: &Connection) -> Result<()> {
     if let Some(sql) = table_sql
         && !sql.contains("pair INTEGER NOT NULL DEFAULT 0")
     {
-        println!("⚠️  Adding 'pair' column to events table...");
+        crate::progress!("⚠️  Adding 'pair' column to events table...");
 
         conn.execute_batch(
             r#"
@@ -781,11 +786,11 @@ fn migrate_add_pair_to_events(conn: &Connection) -> Result<()> {
             "#
         )?;
 
-        println!("✅ 'pair' column added to events table.");
+        crate::progress!("✅ 'pair' column added to events table.");
 
         // Populate the 'pair' field for all existing records
         recalc_all_pairs(conn)?;
-        println!("✅ Populated 'pair' column for existing events");
+        crate::progress!("✅ Populated 'pair' column for existing events");
 
         db::ttlog(
             conn,
@@ -811,7 +816,7 @@ fn recalc_pairs_for_date(conn: &Connection, date: &str) -> Result<()> {
                 row.get::<_, String>(2)?,
             ))
         })?
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<rusqlite::Result<Vec<_>>>()?;
 
     let mut current_pair = 1;
     let mut last_in: Option<i32> = None;
@@ -845,7 +850,7 @@ fn recalc_all_pairs(conn: &Connection) -> Result<()> {
     let mut stmt = conn.prepare("SELECT DISTINCT date FROM events ORDER BY date ASC")?;
     let dates = stmt
         .query_map([], |row| row.get::<_, String>(0))?
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<rusqlite::Result<Vec<_>>>()?;
 
     for d in dates {
         recalc_pairs_for_date(conn, &d)?;
@@ -853,3 +858,190 @@ fn recalc_all_pairs(conn: &Connection) -> Result<()> {
 
     Ok(())
 }
+
+fn migrate_add_minutes_to_events(conn: &Connection) -> Result<()> {
+    // One transaction: a failed merge leaves the events table as it was
+    let tx = conn.unchecked_transaction()?;
+
+    // Add the `minutes` column only if missing (idempotent)
+    let has_minutes = tx
+        .prepare("PRAGMA table_info('events')")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .iter()
+        .any(|c| c == "minutes");
+    if !has_minutes {
+        tx.execute(
+            "ALTER TABLE events ADD COLUMN minutes INTEGER NOT NULL DEFAULT 0",
+            [],
+        )?;
+    }
+
+    // Normalize every stored time to canonical HH:MM and populate `minutes`.
+    // Times are parsed in Rust because legacy rows may contain non zero-padded values (e.g. "9:00").
+    let rows = {
+        let mut stmt = tx.prepare("SELECT id, time FROM events")?;
+        stmt.query_map([], |row| {
+            Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+    };
+    for (id, time) in rows {
+        if let Some((norm, minutes)) = db::normalize_time(&time) {
+            tx.execute(
+                "UPDATE events SET time = ?1, minutes = ?2 WHERE id = ?3",
+                params![norm, minutes, id],
+            )?;
+        }
+    }
+
+    // Merge duplicates (same date, time and kind) into the oldest row, otherwise the unique
+    // index cannot be created. A lunch or meta set only on a newer row is kept; rows whose
+    // position differs cannot be merged and abort the migration.
+    let removed = merge_duplicated_events(&tx)?;
+
+    tx.execute_batch(
+        "
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_events_uniq ON events(date, time, kind);
+        CREATE INDEX IF NOT EXISTS idx_events_date_minutes ON events(date, minutes);
+        ",
+    )?;
+
+    if removed > 0 {
+        db::ttlog(
+            &tx,
+            "migration_applied",
+            "migrate_add_minutes_to_events",
+            &format!(
+                "Merged {} duplicated events before creating unique index",
+                removed
+            ),
+        )?;
+    }
+    tx.commit()?;
+    crate::progress!("✅ Added 'minutes' column and unique (date, time, kind) index to events");
+
+    Ok(())
+}
+
+/// An events row taking part in a (date, time, kind) duplicate.
+struct DuplicatedEvent {
+    id: i32,
+    date: String,
+    key: String,
+    position: String,
+    lunch_break: i32,
+    meta: String,
+}
+
+/// Fold every group of events sharing (date, time, kind) into its lowest id, renumber the pairs
+/// of the touched dates and return the number of deleted rows.
+fn merge_duplicated_events(conn: &Connection) -> Result<usize> {
+    let rows = {
+        let mut stmt = conn.prepare(
+            "SELECT id, date, date || ' ' || time || ' ' || kind, position, lunch_break, COALESCE(meta, '') \
+             FROM events \
+             WHERE (date, time, kind) IN \
+                 (SELECT date, time, kind FROM events GROUP BY date, time, kind HAVING COUNT(*) > 1) \
+             ORDER BY date, time, kind, id",
+        )?;
+        stmt.query_map([], |row| {
+            Ok(DuplicatedEvent {
+                id: row.get(0)?,
+                date: row.get(1)?,
+                key: row.get(2)?,
+                position: row.get(3)?,
+                lunch_break: row.get(4)?,
+                meta: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+    };
+
+    let mut groups: Vec<Vec<DuplicatedEvent>> = Vec::new();
+    for row in rows {
+        match groups.last_mut() {
+            Some(group) if group[0].key == row.key => group.push(row),
+            _ => groups.push(vec![row]),
+        }
+    }
+
+    let conflicts: Vec<String> = groups
+        .iter()
+        .filter(|g| g.iter().any(|r| r.position != g[0].position))
+        .map(|g| {
+            let ids: Vec<String> = g.iter().map(|r| r.id.to_string()).collect();
+            format!("{} (ids {})", g[0].key, ids.join(", "))
+        })
+        .collect();
+    if !conflicts.is_empty() {
+        return Err(Error::invalid(format!(
+            "duplicated events with different positions, fix them before upgrading: {}",
+            conflicts.join("; ")
+        )));
+    }
+
+    let mut removed = 0;
+    for group in &groups {
+        let Some((keep, rest)) = group.split_first() else {
+            continue;
+        };
+        let lunch = group.iter().map(|r| r.lunch_break).find(|l| *l > 0);
+        let meta = group
+            .iter()
+            .map(|r| r.meta.as_str())
+            .find(|m| !m.is_empty());
+        conn.execute(
+            "UPDATE events SET lunch_break = ?1, meta = ?2 WHERE id = ?3",
+            params![lunch.unwrap_or(0), meta.unwrap_or(""), keep.id],
+        )?;
+        for dup in rest {
+            removed += conn.execute("DELETE FROM events WHERE id = ?1", params![dup.id])?;
+        }
+    }
+
+    // the dropped rows held pair numbers of their own
+    let mut dates: Vec<&str> = groups.iter().map(|g| g[0].date.as_str()).collect();
+    dates.dedup();
+    for date in dates {
+        recalc_pairs_for_date(conn, date)?;
+    }
+    Ok(removed)
+}
+
+fn migrate_add_snapshot_to_log(conn: &Connection) -> Result<()> {
+    let mut col_stmt = conn.prepare("PRAGMA table_info('log')")?;
+    let cols = col_stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    if !cols.iter().any(|c| c == "snapshot") {
+        conn.execute("ALTER TABLE log ADD COLUMN snapshot TEXT", [])?;
+    }
+    if !cols.iter().any(|c| c == "state") {
+        conn.execute("ALTER TABLE log ADD COLUMN state TEXT", [])?;
+    }
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_log_state ON log(state)", [])?;
+    crate::progress!("✅ Added 'snapshot' and 'state' columns to log");
+
+    Ok(())
+}
+
+fn migrate_create_locked_periods(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS locked_periods (
+            period     TEXT PRIMARY KEY,     -- YYYY-MM
+            start_date TEXT NOT NULL,        -- first locked day (YYYY-MM-DD)
+            end_date   TEXT NOT NULL,        -- last locked day (YYYY-MM-DD)
+            checksum   TEXT NOT NULL,        -- SHA-256 of the period's events at lock time
+            events     INTEGER NOT NULL DEFAULT 0,
+            locked_at  TEXT NOT NULL,
+            locked_by  TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_locked_periods_range ON locked_periods(start_date, end_date);
+        ",
+    )?;
+    crate::progress!("✅ Created 'locked_periods' table");
+
+    Ok(())
+}
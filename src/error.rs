@@ -0,0 +1,185 @@
+//! Crate-level error type.
+//!
+//! Every public function in `db`, `events`, `config` and `export` returns [`Result`], so
+//! callers embedding the library can tell invalid user input apart from database corruption,
+//! filesystem problems or failed migrations. The CLI maps each class to a distinct exit code
+//! (see [`Error::exit_code`]).
+
+use std::fmt;
+use std::io;
+
+/// Result alias used across the library.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Invalid user-provided values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Date not in `YYYY-MM-DD` format (or otherwise unparsable)
+    Date(String),
+    /// Time not in `HH:MM` format
+    Time(String),
+    /// Unknown working position
+    Position(String),
+    /// Lunch break neither 0 (none) nor within `min..=max` minutes
+    Lunch { lunch: i32, min: i32, max: i32 },
+    /// Period filter not in `YYYY` / `YYYY-MM` format
+    Period(String),
+    /// Change refused because the date falls in a locked period
+    Locked { date: String, period: String },
+    /// Any other invalid argument
+    Other(String),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Date(d) => {
+                write!(
+                    f,
+                    "Invalid date format: {} (expected YYYY-MM-DD, today, yesterday, mon, last fri, -2d)",
+                    d
+                )
+            }
+            ValidationError::Time(t) => write!(f, "Invalid time: {} (expected HH:MM)", t),
+            ValidationError::Position(p) => write!(
+                f,
+                "Invalid position: {} (use O=office, R=remote, H=holiday, C=on-site client, M=mixed)",
+                p
+            ),
+            ValidationError::Lunch { lunch, min, max } => write!(
+                f,
+                "Invalid lunch break: {} (must be 0 or between {} and {} minutes)",
+                lunch, min, max
+            ),
+            ValidationError::Period(p) => {
+                write!(
+                    f,
+                    "Invalid period: {} (expected YYYY, YYYY-MM, YYYY-Www, last-month, FROM..TO, ...)",
+                    p
+                )
+            }
+            ValidationError::Locked { date, period } => write!(
+                f,
+                "Date {} falls in locked period {} (run `period unlock {}` or pass --force)",
+                date, period, period
+            ),
+            ValidationError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Library error.
+#[derive(Debug)]
+pub enum Error {
+    /// SQLite failure (constraint violation, corrupted file, locked DB, ...)
+    Database(rusqlite::Error),
+    /// Filesystem or stream failure
+    Io(io::Error),
+    /// Configuration file could not be read, parsed or written
+    Config(String),
+    /// Invalid user input (bad date, time, position, lunch, ...)
+    Validation(ValidationError),
+    /// A schema or configuration migration failed
+    Migration { version: String, source: Box<Error> },
+    /// Export writer failure (csv, json, xlsx, pdf)
+    Export(String),
+}
+
+impl Error {
+    /// Process exit code associated to the error class.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Validation(_) => 2,
+            Error::Config(_) => 3,
+            Error::Io(_) => 4,
+            Error::Database(_) => 5,
+            Error::Migration { .. } => 6,
+            Error::Export(_) => 7,
+        }
+    }
+
+    /// Shortcut to build a `Validation(Other)` error from a message.
+    pub fn invalid(msg: impl Into<String>) -> Self {
+        Error::Validation(ValidationError::Other(msg.into()))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Database(e) => write!(f, "Database error: {}", e),
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Config(msg) => write!(f, "Configuration error: {}", msg),
+            Error::Validation(v) => write!(f, "{}", v),
+            Error::Migration { version, source } => {
+                write!(f, "Migration {} failed: {}", version, source)
+            }
+            Error::Export(msg) => write!(f, "Export failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Database(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::Migration { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Database(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<ValidationError> for Error {
+    fn from(e: ValidationError) -> Self {
+        Error::Validation(e)
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(e: serde_yaml::Error) -> Self {
+        Error::Config(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Export(e.to_string())
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        Error::Export(e.to_string())
+    }
+}
+
+impl From<rust_xlsxwriter::XlsxError> for Error {
+    fn from(e: rust_xlsxwriter::XlsxError) -> Self {
+        Error::Export(e.to_string())
+    }
+}
+
+impl From<minijinja::Error> for Error {
+    fn from(e: minijinja::Error) -> Self {
+        Error::Export(format!("template error: {:#}", e))
+    }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(e: zip::result::ZipError) -> Self {
+        Error::Export(e.to_string())
+    }
+}
@@ -1,12 +1,16 @@
+use crate::clock::Clock;
 use crate::config::Config;
 use crate::db;
 use crate::db::row_to_event;
+use crate::error::Result;
 use rusqlite::{Connection, params};
 
 /// Create a missing event (in/out) and return the created event.
 /// If an identical event (same date/time/kind) already exists, return it without duplicating.
 /// `pos_opt` may force the position (otherwise uses the default from `config`).
 /// `_prefer_other` is currently ignored (placeholder for future merge/matching logic).
+/// `created_at` is taken from `clock`. Refused for dates inside a locked period.
+#[allow(clippy::too_many_arguments)]
 pub fn create_missing_event(
     conn: &mut Connection,
     date: &str,
@@ -15,7 +19,8 @@ pub fn create_missing_event(
     pos_opt: &Option<String>, // Some("R") etc. or None
     _prefer_other: Option<&db::Event>,
     config: &Config,
-) -> rusqlite::Result<Option<db::Event>> {
+    clock: &dyn Clock,
+) -> Result<Option<db::Event>> {
     // Normalize/validate minimal parameters
     let kind = kind_val.trim().to_lowercase();
     if kind != "in" && kind != "out" {
@@ -30,20 +35,33 @@ pub fn create_missing_event(
         .trim()
         .to_string();
 
+    let (time_norm, minutes) = match db::normalize_time(time_val) {
+        Some(t) => t,
+        None => return Ok(None), // invalid time -> no insertion
+    };
+
     // 1) Check whether an identical event already exists (same date, same time, same kind)
-    if let Some(existing) = get_event_by_uniq(conn, date, time_val, &kind)? {
+    if let Some(existing) = get_event_by_uniq(conn, date, &time_norm, &kind)? {
         return Ok(Some(existing));
     }
 
+    crate::lock::ensure_unlocked(conn, date)?;
+
     // 2) Insert the missing event.
     //    Note: pair is left at 0 (DEFAULT) and can be recalculated later (migration/repair).
-    //    created_at is set via SQLite to avoid extra dependencies in Rust.
     conn.execute(
         r#"
-        INSERT INTO events (date, time, kind, position, lunch_break, pair, source, meta, created_at)
-        VALUES (?1, ?2, ?3, ?4, 0, 0, 'cli', '', strftime('%Y-%m-%dT%H:%M:%S','now'))
+        INSERT INTO events (date, time, minutes, kind, position, lunch_break, pair, source, meta, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, 0, 0, 'cli', '', ?6)
         "#,
-        params![date, time_val, kind, position],
+        params![
+            date,
+            time_norm,
+            minutes,
+            kind,
+            position,
+            clock.now_utc().format("%Y-%m-%dT%H:%M:%S").to_string()
+        ],
     )?;
 
     // 3) Retrieve the newly created event and return it.
@@ -53,16 +71,16 @@ pub fn create_missing_event(
 }
 
 /// Return an event by ID (column-name based mapping, robust to column order changes).
-fn get_event_by_id(conn: &Connection, id: i64) -> rusqlite::Result<db::Event> {
-    conn.query_row(
+fn get_event_by_id(conn: &Connection, id: i64) -> Result<db::Event> {
+    Ok(conn.query_row(
         r#"
-        SELECT id, date, time, kind, position, lunch_break, pair, source, meta, created_at
+        SELECT id, date, time, minutes, kind, position, lunch_break, pair, source, meta, created_at
         FROM events
         WHERE id = ?1
         "#,
         [id],
         row_to_event,
-    )
+    )?)
 }
 
 /// Find a unique event by (date, time, kind).
@@ -71,10 +89,10 @@ fn get_event_by_uniq(
     date: &str,
     time_val: &str,
     kind: &str,
-) -> rusqlite::Result<Option<db::Event>> {
+) -> Result<Option<db::Event>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT id, date, time, kind, position, lunch_break, pair, source, meta, created_at
+        SELECT id, date, time, minutes, kind, position, lunch_break, pair, source, meta, created_at
         FROM events
         WHERE date = ?1 AND time = ?2 AND kind = ?3
         LIMIT 1
@@ -114,6 +132,8 @@ mod tests {
             max_duration_lunch_break: 90,
             separator_char: "-".to_string(),
             show_weekday: "None".to_string(),
+            colors: Default::default(),
+            employee: Default::default(),
         };
 
         // Ensure no events initially
@@ -122,9 +142,17 @@ mod tests {
 
         // Call helper to create an 'in' missing event
         let pos = Some("R".to_string());
-        let created =
-            create_missing_event(&mut conn, "2025-10-03", "09:00", "in", &pos, None, &config)
-                .expect("create_missing_event");
+        let created = create_missing_event(
+            &mut conn,
+            "2025-10-03",
+            "09:00",
+            "in",
+            &pos,
+            None,
+            &config,
+            &crate::clock::SystemClock,
+        )
+        .expect("create_missing_event");
         assert!(created.is_some(), "expected event to be created");
         let ev = created.unwrap();
         assert_eq!(ev.kind, "in");
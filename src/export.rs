@@ -1,13 +1,22 @@
 use crate::cli::Commands;
-use crate::db;
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::db::{self, WorkSession};
+use crate::error::{Error, Result};
+use crate::logic::{PairSummary, compute_event_pairs, compute_event_summaries};
+use crate::ods;
 use crate::pdf_manager::PdfManager;
-use crate::utils::mins2readable;
+use crate::template::{ReportInfo, Template};
+use crate::timelog::{Balance, session_report};
+use crate::utils::{
+    Confirm, confirm, describe_position, mins2readable, parse_work_duration_to_minutes,
+};
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use rusqlite::Connection;
-use rust_xlsxwriter::{Color, Format, FormatAlign, FormatBorder, FormatPattern, Workbook};
+use rust_xlsxwriter::utility::row_col_to_cell;
+use rust_xlsxwriter::{Color, Format, FormatAlign, FormatBorder, FormatPattern, Formula, Workbook};
 use serde::Serialize;
-use serde_json::Value;
-use std::error::Error;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io;
 use std::io::Write;
@@ -31,14 +40,33 @@ struct SessionExport {
     id: i32,
     date: String,
     position: String,
+    #[serde(rename = "start_time")]
     start: String,
     lunch_break: i32,
+    #[serde(rename = "end_time")]
     end: String,
     work_duration: Option<String>,
 }
 
-fn get_headers(export_events: bool) -> Vec<&'static str> {
-    if export_events {
+/// A row type that can be exported in every format.
+///
+/// CSV and JSON use the `Serialize` impl; the table formats (XLSX, ODS, PDF) use `headers` and
+/// `cells`, so new datasets only need this impl to be exported everywhere.
+pub trait Tabular: Serialize {
+    /// Dataset name, used for sheet names and titles (`events`, `sessions`).
+    const NAME: &'static str;
+
+    /// Column headers, in the order of `cells`.
+    fn headers() -> Vec<&'static str>;
+
+    /// The row as display strings, one per header (empty for missing values).
+    fn cells(&self) -> Vec<String>;
+}
+
+impl Tabular for EventExport {
+    const NAME: &'static str = "events";
+
+    fn headers() -> Vec<&'static str> {
         vec![
             "id",
             "date",
@@ -49,7 +77,26 @@ fn get_headers(export_events: bool) -> Vec<&'static str> {
             "pair",
             "source",
         ]
-    } else {
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.date.clone(),
+            self.time.clone(),
+            self.kind.clone(),
+            self.position.clone(),
+            self.lunch_break.to_string(),
+            self.pair.to_string(),
+            self.source.clone(),
+        ]
+    }
+}
+
+impl Tabular for SessionExport {
+    const NAME: &'static str = "sessions";
+
+    fn headers() -> Vec<&'static str> {
         vec![
             "id",
             "date",
@@ -60,165 +107,868 @@ fn get_headers(export_events: bool) -> Vec<&'static str> {
             "work_duration",
         ]
     }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.date.clone(),
+            self.position.clone(),
+            self.start.clone(),
+            self.lunch_break.to_string(),
+            self.end.clone(),
+            self.work_duration.clone().unwrap_or_default(),
+        ]
+    }
 }
 
-/// Convert events into &[Vec<String>]
-fn events_to_table(events: &[EventExport]) -> Vec<Vec<String>> {
-    events
-        .iter()
-        .map(|e| {
-            vec![
-                e.id.to_string(),
-                e.date.clone(),
-                e.time.clone(),
-                e.kind.clone(),
-                e.position.clone(),
-                e.lunch_break.to_string(),
-                e.pair.to_string(),
-                e.source.clone(),
-            ]
-        })
-        .collect()
+impl Tabular for PairSummary {
+    const NAME: &'static str = "summary";
+
+    fn headers() -> Vec<&'static str> {
+        vec![
+            "date",
+            "pair",
+            "position",
+            "start",
+            "end",
+            "lunch_minutes",
+            "duration_minutes",
+            "unmatched",
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.date.clone(),
+            self.pair.to_string(),
+            self.position.clone(),
+            self.start.clone(),
+            self.end.clone(),
+            self.lunch_minutes.to_string(),
+            self.duration_minutes.to_string(),
+            self.unmatched.to_string(),
+        ]
+    }
+}
+
+/// One day of `export --daily`; expected and surplus are empty when the day is not counted
+/// (same rule as `Timelog::balance`).
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct DailyExport {
+    pub date: String,
+    pub position: String,
+    pub worked_minutes: Option<i64>,
+    pub expected_minutes: Option<i64>,
+    pub surplus_minutes: Option<i64>,
+}
+
+impl DailyExport {
+    /// The figures `list` shows for `session` (see [`session_report`]).
+    pub fn new(session: db::WorkSession, config: &Config) -> Self {
+        let expected = parse_work_duration_to_minutes(&config.min_work_duration);
+        let r = session_report(session, config);
+        DailyExport {
+            expected_minutes: r.surplus_minutes.map(|_| expected),
+            worked_minutes: r.worked_minutes,
+            surplus_minutes: r.surplus_minutes,
+            date: r.session.date,
+            position: r.session.position,
+        }
+    }
+}
+
+impl Tabular for DailyExport {
+    const NAME: &'static str = "daily";
+
+    fn headers() -> Vec<&'static str> {
+        vec![
+            "date",
+            "position",
+            "worked_minutes",
+            "expected_minutes",
+            "surplus_minutes",
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let opt = |v: Option<i64>| v.map(|m| m.to_string()).unwrap_or_default();
+        vec![
+            self.date.clone(),
+            self.position.clone(),
+            opt(self.worked_minutes),
+            opt(self.expected_minutes),
+            opt(self.surplus_minutes),
+        ]
+    }
+}
+
+/// One month of `export --monthly`, totalled over the counted days (as `Timelog::balance`).
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct MonthlyExport {
+    /// `YYYY-MM`
+    pub month: String,
+    /// Counted days (with a surplus)
+    pub days: usize,
+    pub worked_minutes: i64,
+    pub expected_minutes: i64,
+    pub surplus_minutes: i64,
+}
+
+impl Tabular for MonthlyExport {
+    const NAME: &'static str = "monthly";
+
+    fn headers() -> Vec<&'static str> {
+        vec![
+            "month",
+            "days",
+            "worked_minutes",
+            "expected_minutes",
+            "surplus_minutes",
+        ]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.month.clone(),
+            self.days.to_string(),
+            self.worked_minutes.to_string(),
+            self.expected_minutes.to_string(),
+            self.surplus_minutes.to_string(),
+        ]
+    }
+}
+
+/// Dataset selected by the `export` flags (default: events).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dataset {
+    Events,
+    Sessions,
+    Summary,
+    Daily,
+    Monthly,
+}
+
+impl Dataset {
+    fn name(self) -> &'static str {
+        match self {
+            Dataset::Events => EventExport::NAME,
+            Dataset::Sessions => SessionExport::NAME,
+            Dataset::Summary => PairSummary::NAME,
+            Dataset::Daily => DailyExport::NAME,
+            Dataset::Monthly => MonthlyExport::NAME,
+        }
+    }
+}
+
+/// Rows of `data` as strings, in the column order of `T::headers`.
+pub fn to_table<T: Tabular>(data: &[T]) -> Vec<Vec<String>> {
+    data.iter().map(Tabular::cells).collect()
+}
+
+/// Columns of a dataset to export (`--columns`), as positions in `Tabular::headers`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Columns {
+    headers: Vec<&'static str>,
+    indexes: Vec<usize>,
+}
+
+impl Columns {
+    /// Every column of `T`, in its natural order.
+    pub fn all<T: Tabular>() -> Self {
+        let headers = T::headers();
+        let indexes = (0..headers.len()).collect();
+        Columns { headers, indexes }
+    }
+
+    /// The named columns of `T`, in the given order.
+    pub fn select<T: Tabular>(names: &[String]) -> Result<Self> {
+        let available = T::headers();
+        let mut selected = Columns {
+            headers: Vec::new(),
+            indexes: Vec::new(),
+        };
+        for name in names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+            let Some(i) = available.iter().position(|h| *h == name) else {
+                return Err(Error::invalid(format!(
+                    "Unknown column '{}' for {}. Available: {}",
+                    name,
+                    T::NAME,
+                    available.join(", ")
+                )));
+            };
+            selected.headers.push(available[i]);
+            selected.indexes.push(i);
+        }
+        if selected.indexes.is_empty() {
+            return Err(Error::invalid("--columns needs at least one column"));
+        }
+        Ok(selected)
+    }
+
+    pub fn headers(&self) -> &[&'static str] {
+        &self.headers
+    }
+
+    /// The selected cells of a row, in column order.
+    pub fn pick(&self, cells: &[String]) -> Vec<String> {
+        self.indexes.iter().map(|&i| cells[i].clone()).collect()
+    }
+}
+
+/// Headers and display cells of a dataset, as written by the table formats (XLSX, ODS, PDF).
+#[derive(Debug, Clone)]
+pub struct Table {
+    /// Dataset name (`Tabular::NAME`), used for sheet names
+    pub name: &'static str,
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// The `columns` of every row of `data`.
+    pub fn new<T: Tabular>(data: &[T], columns: &Columns) -> Self {
+        Table {
+            name: T::NAME,
+            headers: columns.headers().to_vec(),
+            rows: data.iter().map(|r| columns.pick(&r.cells())).collect(),
+        }
+    }
+
+    /// Every column of `data`.
+    pub fn from_rows<T: Tabular>(data: &[T]) -> Self {
+        Table::new(data, &Columns::all::<T>())
+    }
+}
+
+/// Streaming CSV / JSON writer: rows are written as they are read, never collected.
+enum RowWriter<W: Write> {
+    Csv {
+        wtr: Box<csv::Writer<W>>,
+        rows: usize,
+    },
+    Json {
+        out: W,
+        rows: usize,
+    },
 }
 
-/// Convert sessions into &[Vec<String>]
-fn sessions_to_table(sessions: &[SessionExport]) -> Vec<Vec<String>> {
-    sessions
+impl<W: Write> RowWriter<W> {
+    fn new(fmt: &str, out: W) -> Self {
+        if fmt == "csv" {
+            RowWriter::Csv {
+                wtr: Box::new(csv::Writer::from_writer(out)),
+                rows: 0,
+            }
+        } else {
+            RowWriter::Json { out, rows: 0 }
+        }
+    }
+
+    fn write<T: Tabular>(&mut self, row: &T, columns: &Columns) -> Result<()> {
+        match self {
+            RowWriter::Csv { wtr, rows } => {
+                // header with the first row: an empty dataset gives an empty file
+                if *rows == 0 {
+                    wtr.write_record(columns.headers())?;
+                }
+                wtr.write_record(columns.pick(&row.cells()))?;
+                *rows += 1;
+            }
+            RowWriter::Json { out, rows } => {
+                let fields = json_fields(row, columns)?
+                    .into_iter()
+                    .map(|(h, v)| Ok(format!("    {}: {}", serde_json::to_string(h)?, v)))
+                    .collect::<Result<Vec<_>>>()?;
+                let sep = if *rows == 0 { "[\n" } else { ",\n" };
+                write!(out, "{}  {{\n{}\n  }}", sep, fields.join(",\n"))?;
+                *rows += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            RowWriter::Csv { mut wtr, .. } => wtr.flush()?,
+            RowWriter::Json { mut out, rows } => {
+                out.write_all(if rows == 0 { b"[]\n" } else { b"\n]\n" })?;
+                out.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The selected columns of `row` with their typed values (from the `Serialize` impl).
+fn json_fields<T: Tabular>(
+    row: &T,
+    columns: &Columns,
+) -> Result<Vec<(&'static str, serde_json::Value)>> {
+    let value = serde_json::to_value(row)?;
+    Ok(columns
+        .headers()
         .iter()
-        .map(|s| {
-            vec![
-                s.id.to_string(),
-                s.date.clone(),
-                s.position.clone(),
-                s.start.clone(),
-                s.lunch_break.to_string(),
-                s.end.clone(),
-                s.work_duration.clone().unwrap_or_default(),
-            ]
+        .map(|h| (*h, value.get(*h).cloned().unwrap_or_default()))
+        .collect())
+}
+
+/// Where and how an export is written.
+struct ExportTarget<'a> {
+    fmt: String,
+    /// `None` for stdout (`--file -`)
+    path: Option<&'a Path>,
+    report: PdfReport,
+    /// `--template` and what it shows besides the rows
+    template: Option<(Template, ReportInfo)>,
+    /// Rules of the XLSX surplus column
+    config: &'a Config,
+}
+
+impl ExportTarget<'_> {
+    fn writer(&self) -> Result<Box<dyn Write>> {
+        Ok(match self.path {
+            Some(path) => Box::new(io::BufWriter::new(File::create(path)?)),
+            None => Box::new(io::BufWriter::new(io::stdout().lock())),
         })
-        .collect()
-}
-
-fn export_to_format<T: serde::Serialize + std::fmt::Debug>(
-    fmt: &str,
-    data: &[T],
-    path: &Path,
-    export_events: bool,
-) -> Result<(), Box<dyn Error>> {
-    match fmt {
-        "csv" => export_csv(data, path)?,
-        "json" => export_json(data, path)?,
-        "xlsx" => export_xlsx(data, path)?,
-        "pdf" => export_pdf(data, path, export_events)?,
+    }
+}
+
+/// Filters of the exported rows (`--range`, `--pos`, `--kind`).
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Inclusive `(from, to)` dates
+    pub bounds: Option<(String, String)>,
+    pub pos: Option<String>,
+    /// Only applied to the `events` table
+    pub kind: Option<String>,
+}
+
+impl ExportFilter {
+    pub fn range(bounds: Option<(String, String)>) -> Self {
+        ExportFilter {
+            bounds,
+            ..ExportFilter::default()
+        }
+    }
+}
+
+/// Page title and totals block of a PDF export.
+#[derive(Debug, Clone, Default)]
+pub struct PdfReport {
+    pub title: String,
+    /// `(label, value)` lines of the summary block
+    pub summary: Vec<(String, String)>,
+}
+
+/// Write the rows produced by `rows` in the target format; CSV and JSON are streamed.
+fn write_dataset<T: Tabular>(
+    target: &ExportTarget,
+    columns: Option<&[String]>,
+    rows: impl FnOnce(&mut dyn FnMut(T) -> Result<()>) -> Result<()>,
+) -> Result<()> {
+    let columns = match columns {
+        Some(names) => Columns::select::<T>(names)?,
+        None => Columns::all::<T>(),
+    };
+
+    if let Some((template, info)) = &target.template {
+        let mut rendered = Vec::new();
+        rows(&mut |row| {
+            let fields = json_fields(&row, &columns)?;
+            rendered.push(
+                fields
+                    .into_iter()
+                    .map(|(h, v)| (h.to_string(), v))
+                    .collect::<serde_json::Map<_, _>>(),
+            );
+            Ok(())
+        })?;
+        let text = template.render(info, columns.headers(), &rendered)?;
+        let mut out = target.writer()?;
+        out.write_all(text.as_bytes())?;
+        out.flush()?;
+        if let Some(path) = target.path {
+            crate::progress!("✅ Rendered {} to {}", template.name, path.display());
+        }
+        return Ok(());
+    }
+
+    let Some(path) = target
+        .path
+        .filter(|_| !matches!(target.fmt.as_str(), "csv" | "json"))
+    else {
+        let mut writer = RowWriter::new(&target.fmt, target.writer()?);
+        rows(&mut |row| writer.write(&row, &columns))?;
+        writer.finish()?;
+        if let Some(path) = target.path {
+            crate::progress!("✅ Exported data to {}", path.display());
+        }
+        return Ok(());
+    };
+
+    let mut data = Vec::new();
+    rows(&mut |row| {
+        data.push(row);
+        Ok(())
+    })?;
+    let table = Table::new(&data, &columns);
+    match target.fmt.as_str() {
+        "xlsx" => export_xlsx(&table, path, target.config),
+        "pdf" => export_pdf(&table, path, &target.report),
+        "ods" => export_ods(&table, path),
         _ => unreachable!(),
     }
-    Ok(())
 }
 
 /// Main export handler
-pub fn handle_export(cmd: &Commands, conn: &Connection) -> Result<(), Box<dyn Error>> {
+pub fn handle_export(
+    cmd: &Commands,
+    conn: &Connection,
+    config: &Config,
+    clock: &dyn Clock,
+    mode: Confirm,
+) -> Result<()> {
     if let Commands::Export {
         format,
         file,
         range,
-        events,
+        events: _,
         sessions,
+        summary,
+        daily,
+        monthly,
+        columns,
+        pos,
+        kind,
+        template,
         force,
     } = cmd
     {
-        // Validate format
-        let fmt = format.to_lowercase();
-        if !["csv", "json", "xlsx", "pdf"].contains(&fmt.as_str()) {
-            eprintln!("❌ Unsupported format '{}'. Use one of: csv, json", format);
-            std::process::exit(1);
+        // Validate format (a template replaces it)
+        let template = template.as_deref().map(Template::load).transpose()?;
+        let fmt = if template.is_some() {
+            "template".to_string()
+        } else {
+            format.to_lowercase()
+        };
+        if template.is_none()
+            && !["csv", "json", "xlsx", "ods", "pdf", "ics", "html", "timew"]
+                .contains(&fmt.as_str())
+        {
+            return Err(Error::invalid(format!(
+                "Unsupported format '{}'. Use one of: csv, json, xlsx, ods, pdf, ics, html, timew",
+                format
+            )));
         }
-
-        // Check output file path is absolute
-        let path = Path::new(file);
-        if !path.is_absolute() {
-            eprintln!("❌ Output file path must be absolute: {}", file);
-            std::process::exit(1);
+        // clap keeps the dataset flags mutually exclusive
+        let dataset = if *sessions {
+            Dataset::Sessions
+        } else if *summary {
+            Dataset::Summary
+        } else if *daily {
+            Dataset::Daily
+        } else if *monthly {
+            Dataset::Monthly
+        } else {
+            Dataset::Events
+        };
+        // ics, html and timew are documents built from the events, not tables
+        let document = matches!(fmt.as_str(), "ics" | "html" | "timew");
+        if document && dataset != Dataset::Events {
+            return Err(Error::invalid(format!(
+                "The {} format is built from events (pairs and holidays); drop --{}",
+                fmt,
+                dataset.name()
+            )));
+        }
+        if kind.is_some() && (dataset != Dataset::Events || document) {
+            return Err(Error::invalid(
+                "--kind only applies to the raw events (--events) in a tabular format",
+            ));
+        }
+        if columns.is_some() && document {
+            return Err(Error::invalid(format!(
+                "--columns is not supported by the {} format",
+                fmt
+            )));
         }
 
-        // new check
-        ensure_writable(path, *force)?;
-
-        let date_bounds: Option<(String, String)> = if let Some(r) = range.as_deref() {
-            Some(parse_range(r).map_err(|e| format!("invalid --range: {e}"))?)
-        } else {
+        // `-` streams to stdout; files must be absolute paths
+        let path = if file == "-" {
+            if !matches!(
+                fmt.as_str(),
+                "csv" | "json" | "ics" | "html" | "timew" | "template"
+            ) {
+                return Err(Error::invalid(format!(
+                    "The {} format cannot be written to stdout; use --file <absolute path>",
+                    fmt
+                )));
+            }
             None
+        } else {
+            let path = Path::new(file);
+            if !path.is_absolute() {
+                return Err(Error::invalid(format!(
+                    "Output file path must be absolute: {}",
+                    file
+                )));
+            }
+            ensure_writable(path, *force, mode)?;
+            Some(path)
+        };
+
+        let range = range
+            .as_deref()
+            .map(|r| {
+                crate::utils::parse_date_range(r, clock.today())
+                    .map_err(|e| Error::invalid(format!("invalid --range: {e}")))
+            })
+            .transpose()?;
+        let date_bounds = range.as_ref().map(|r| r.bounds());
+        // canonical label of the range for titles and templates
+        let range = range.map(|r| r.period());
+        let filter = ExportFilter {
+            bounds: date_bounds.clone(),
+            pos: pos.as_ref().map(|p| p.trim().to_uppercase()),
+            kind: kind.clone(),
         };
 
-        // dataset selection (default: events)
-        let export_events = if *events { true } else { !(*sessions) };
+        if fmt == "ics" {
+            let (events, sessions) = load_calendar_data(conn, &filter)?;
+            let ics = crate::ics::build_calendar(&events, &sessions, clock.now_utc());
+            match path {
+                Some(path) => {
+                    std::fs::write(path, ics)?;
+                    crate::progress!("✅ Exported calendar to {}", path.display());
+                }
+                None => io::stdout().lock().write_all(ics.as_bytes())?,
+            }
+            return Ok(());
+        }
+        if fmt == "timew" {
+            let events = load_raw_events(conn, &filter)?;
+            let data = crate::tracker::timew_data(&events, &chrono::Local);
+            match path {
+                Some(path) => {
+                    std::fs::write(path, data)?;
+                    crate::progress!("✅ Timewarrior data written to {}", path.display());
+                }
+                None => io::stdout().lock().write_all(data.as_bytes())?,
+            }
+            return Ok(());
+        }
+        if fmt == "html" {
+            let events = load_raw_events(conn, &filter)?;
+            let sessions = load_work_sessions(conn, &filter)?;
+            let days = crate::html_report::report_days(sessions, &events, config);
+            let title = format!(
+                "rTimelogger – report {}",
+                range.as_deref().unwrap_or("(all dates)")
+            );
+            let html = crate::html_report::render(&title, &config.employee, &days);
+            match path {
+                Some(path) => {
+                    std::fs::write(path, html)?;
+                    crate::progress!("✅ HTML report written to {}", path.display());
+                }
+                None => io::stdout().lock().write_all(html.as_bytes())?,
+            }
+            return Ok(());
+        }
 
-        if export_events {
-            let data = load_events(conn, date_bounds)?;
-            export_to_format(&fmt, &data, path, export_events)?;
+        let title = format!(
+            "rTimelogger – {} {}",
+            dataset.name(),
+            range.as_deref().unwrap_or("(all dates)")
+        );
+        let report = if fmt == "pdf" {
+            PdfReport {
+                title: title.clone(),
+                summary: pdf_summary(conn, config, &filter)?,
+            }
         } else {
-            let data = load_sessions(conn, date_bounds)?;
-            export_to_format(&fmt, &data, path, export_events)?;
+            PdfReport::default()
+        };
+        let template = match template {
+            Some(template) => {
+                let info = ReportInfo {
+                    title,
+                    dataset: dataset.name().to_string(),
+                    range: range.clone(),
+                    generated: clock.now_local().format("%Y-%m-%d %H:%M").to_string(),
+                    employee: config.employee.clone(),
+                    totals: range_totals(range.clone(), &load_daily(conn, config, &filter)?),
+                };
+                Some((template, info))
+            }
+            None => None,
+        };
+        let target = ExportTarget {
+            fmt,
+            path,
+            report,
+            template,
+            config,
+        };
+        let columns = columns.as_deref();
+
+        match dataset {
+            Dataset::Events => {
+                write_dataset(&target, columns, |f| for_each_event(conn, &filter, f))?
+            }
+            Dataset::Sessions => write_dataset(&target, columns, |f| {
+                for_each_session(conn, config, &filter, f)
+            })?,
+            Dataset::Summary => {
+                // pairs are built from all the events of a day, then filtered by position
+                let events = load_raw_events(conn, &ExportFilter::range(filter.bounds.clone()))?;
+                let pairs = compute_event_summaries(&compute_event_pairs(&events))
+                    .into_iter()
+                    .filter(|p| filter.pos.as_ref().is_none_or(|pos| p.position == *pos));
+                write_dataset(&target, columns, |f| pairs.into_iter().try_for_each(f))?
+            }
+            Dataset::Daily => {
+                let days = load_daily(conn, config, &filter)?;
+                write_dataset(&target, columns, |f| days.into_iter().try_for_each(f))?
+            }
+            Dataset::Monthly => {
+                let months = monthly_totals(&load_daily(conn, config, &filter)?);
+                write_dataset(&target, columns, |f| months.into_iter().try_for_each(f))?
+            }
         }
     }
 
     Ok(())
 }
 
-fn build_query_with_range(
+/// `base_select` with the WHERE clause of `filter` and `order_clause` appended.
+fn build_query(
     base_select: &str,
-    bounds: Option<(String, String)>,
+    filter: &ExportFilter,
     order_clause: &str,
 ) -> (String, Vec<String>) {
     let mut sql = String::from(base_select);
+    let mut conditions: Vec<String> = Vec::new();
     let mut owned_params: Vec<String> = Vec::new();
-    if let Some((start, end)) = bounds {
-        sql.push_str(" WHERE date BETWEEN ?1 AND ?2");
-        owned_params.push(start);
-        owned_params.push(end);
+    if let Some((start, end)) = &filter.bounds {
+        owned_params.push(start.clone());
+        owned_params.push(end.clone());
+        conditions.push(format!(
+            "date BETWEEN ?{} AND ?{}",
+            owned_params.len() - 1,
+            owned_params.len()
+        ));
+    }
+    if let Some(pos) = &filter.pos {
+        owned_params.push(pos.clone());
+        conditions.push(format!("position = ?{}", owned_params.len()));
+    }
+    if let Some(kind) = &filter.kind {
+        owned_params.push(kind.clone());
+        conditions.push(format!("kind = ?{}", owned_params.len()));
+    }
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
     }
     sql.push_str(order_clause);
     (sql, owned_params)
 }
 
-fn load_events(
+/// Run `sql` and pass every mapped row to `f` as it is read.
+fn for_each_row<T>(
     conn: &Connection,
-    bounds: Option<(String, String)>,
-) -> rusqlite::Result<Vec<EventExport>> {
-    let (sql, owned_params) = build_query_with_range(
-        r#"
-        SELECT id, date, time, kind, position, lunch_break, pair, source, meta, created_at
-        FROM events
-        "#,
-        bounds,
-        " ORDER BY date, time",
-    );
-
+    (sql, owned_params): (String, Vec<String>),
+    map: impl Fn(&rusqlite::Row) -> rusqlite::Result<T>,
+    f: &mut dyn FnMut(T) -> Result<()>,
+) -> Result<()> {
     let mut stmt = conn.prepare(&sql)?;
     let param_refs: Vec<&dyn rusqlite::ToSql> = owned_params
         .iter()
         .map(|s| s as &dyn rusqlite::ToSql)
         .collect();
-    let rows = stmt.query_map(param_refs.as_slice(), |row| {
-        db::row_to_event(row).map(|ev| EventExport {
-            id: ev.id,
-            date: ev.date,
-            time: ev.time,
-            kind: ev.kind,
-            position: ev.position,
-            lunch_break: ev.lunch_break,
-            pair: ev.pair,
-            source: ev.source,
-        })
+    let mut rows = stmt.query(param_refs.as_slice())?;
+    while let Some(row) = rows.next()? {
+        f(map(row)?)?;
+    }
+    Ok(())
+}
+
+/// Collect the mapped rows of `sql`.
+fn load_rows<T>(
+    conn: &Connection,
+    query: (String, Vec<String>),
+    map: impl Fn(&rusqlite::Row) -> rusqlite::Result<T>,
+) -> Result<Vec<T>> {
+    let mut rows = Vec::new();
+    for_each_row(conn, query, map, &mut |row| {
+        rows.push(row);
+        Ok(())
     })?;
+    Ok(rows)
+}
+
+const EVENTS_SELECT: &str = "SELECT id, date, time, minutes, kind, position, lunch_break, pair, source, meta, created_at FROM events";
+
+fn for_each_event(
+    conn: &Connection,
+    filter: &ExportFilter,
+    f: &mut dyn FnMut(EventExport) -> Result<()>,
+) -> Result<()> {
+    let query = build_query(EVENTS_SELECT, filter, " ORDER BY date, minutes");
+    for_each_row(
+        conn,
+        query,
+        |row| {
+            db::row_to_event(row).map(|ev| EventExport {
+                id: ev.id,
+                date: ev.date,
+                time: ev.time,
+                kind: ev.kind,
+                position: ev.position,
+                lunch_break: ev.lunch_break,
+                pair: ev.pair,
+                source: ev.source,
+            })
+        },
+        f,
+    )
+}
+
+/// Raw events and holiday sessions of the range, for the ics export.
+fn load_calendar_data(
+    conn: &Connection,
+    filter: &ExportFilter,
+) -> Result<(Vec<db::Event>, Vec<db::WorkSession>)> {
+    let events = load_raw_events(conn, filter)?;
+    let sessions = load_work_sessions(conn, filter)?
+        .into_iter()
+        .filter(|s| s.position == "H")
+        .collect();
+
+    Ok((events, sessions))
+}
+
+/// Raw events matching `filter`, ordered by date and time.
+fn load_raw_events(conn: &Connection, filter: &ExportFilter) -> Result<Vec<db::Event>> {
+    let query = build_query(EVENTS_SELECT, filter, " ORDER BY date, minutes");
+    load_rows(conn, query, db::row_to_event)
+}
+
+/// One row per session matching `filter`, with the figures shown by `list`.
+fn load_daily(
+    conn: &Connection,
+    config: &Config,
+    filter: &ExportFilter,
+) -> Result<Vec<DailyExport>> {
+    let rows = load_work_sessions(conn, filter)?
+        .into_iter()
+        .map(|s| DailyExport::new(s, config))
+        .collect();
+    Ok(rows)
+}
+
+/// Totals of the daily rows per `YYYY-MM`, over the days with a surplus.
+pub fn monthly_totals<'a>(days: impl IntoIterator<Item = &'a DailyExport>) -> Vec<MonthlyExport> {
+    let mut months: BTreeMap<&str, MonthlyExport> = BTreeMap::new();
+    for day in days {
+        let month = day.date.get(..7).unwrap_or(&day.date);
+        let m = months.entry(month).or_insert_with(|| MonthlyExport {
+            month: month.to_string(),
+            days: 0,
+            worked_minutes: 0,
+            expected_minutes: 0,
+            surplus_minutes: 0,
+        });
+        let (Some(surplus), Some(expected)) = (day.surplus_minutes, day.expected_minutes) else {
+            continue;
+        };
+        m.days += 1;
+        m.worked_minutes += day.worked_minutes.unwrap_or_default();
+        m.expected_minutes += expected;
+        m.surplus_minutes += surplus;
+    }
+    months.into_values().collect()
+}
+
+/// Totals of the daily rows over the days with a surplus, as `Timelog::balance` computes them.
+fn range_totals(period: Option<String>, days: &[DailyExport]) -> Balance {
+    let months = monthly_totals(days);
+    Balance {
+        period,
+        days: months.iter().map(|m| m.days).sum(),
+        worked_minutes: months.iter().map(|m| m.worked_minutes).sum(),
+        expected_minutes: months.iter().map(|m| m.expected_minutes).sum(),
+        surplus_minutes: months.iter().map(|m| m.surplus_minutes).sum(),
+    }
+}
 
-    rows.collect()
+/// Raw work_sessions rows matching `filter`.
+fn load_work_sessions(conn: &Connection, filter: &ExportFilter) -> Result<Vec<db::WorkSession>> {
+    let query = build_query(
+        "SELECT id, date, position, start_time, lunch_break, end_time FROM work_sessions",
+        filter,
+        " ORDER BY date",
+    );
+    load_rows(conn, query, db::row_to_worksession)
 }
 
-fn load_sessions(
+/// Totals of the range for the PDF summary block: worked time and surplus (same rules as
+/// `list`) and the number of days per position.
+pub fn pdf_summary(
     conn: &Connection,
-    bounds: Option<(String, String)>,
-) -> rusqlite::Result<Vec<SessionExport>> {
-    let (sql, owned_params) = build_query_with_range(
+    config: &Config,
+    filter: &ExportFilter,
+) -> Result<Vec<(String, String)>> {
+    // `kind` only applies to the events: the totals cover the sessions of the range and position
+    let filter = ExportFilter {
+        kind: None,
+        ..filter.clone()
+    };
+    let reports: Vec<_> = load_work_sessions(conn, &filter)?
+        .into_iter()
+        .map(|s| session_report(s, config))
+        .collect();
+    let worked: i64 = reports.iter().filter_map(|r| r.worked_minutes).sum();
+    let surplus: i64 = reports.iter().filter_map(|r| r.surplus_minutes).sum();
+
+    let mut lines = vec![
+        ("Total worked".to_string(), format_minutes(worked)),
+        ("Surplus".to_string(), format_minutes(surplus)),
+        ("Days".to_string(), reports.len().to_string()),
+    ];
+    let mut by_position: Vec<(String, usize)> = Vec::new();
+    for r in &reports {
+        match by_position
+            .iter_mut()
+            .find(|(pos, _)| *pos == r.session.position)
+        {
+            Some((_, n)) => *n += 1,
+            None => by_position.push((r.session.position.clone(), 1)),
+        }
+    }
+    let order = ["O", "R", "C", "M", "H"];
+    by_position.sort_by_key(|(pos, _)| order.iter().position(|o| o == pos).unwrap_or(order.len()));
+    for (pos, n) in by_position {
+        let (label, _) = describe_position(&pos);
+        lines.push((format!("  {}", label), n.to_string()));
+    }
+    Ok(lines)
+}
+
+/// Minutes as `HHh MMm`, prefixed with `-` when negative.
+pub(crate) fn format_minutes(minutes: i64) -> String {
+    let (hh, mm) = mins2readable(minutes as i32);
+    format!("{}{}h {}m", if minutes < 0 { "-" } else { "" }, hh, mm)
+}
+
+fn for_each_session(
+    conn: &Connection,
+    config: &Config,
+    filter: &ExportFilter,
+    f: &mut dyn FnMut(SessionExport) -> Result<()>,
+) -> Result<()> {
+    let query = build_query(
         r#"
         SELECT
           id,
@@ -229,306 +979,468 @@ fn load_sessions(
           end_time
         FROM work_sessions
         "#,
-        bounds,
+        filter,
         " ORDER BY date, start_time",
     );
-
-    let mut stmt = conn.prepare(&sql)?;
-    let param_refs: Vec<&dyn rusqlite::ToSql> = owned_params
-        .iter()
-        .map(|s| s as &dyn rusqlite::ToSql)
-        .collect();
-    let rows = stmt.query_map(param_refs.as_slice(), |row| {
-        db::row_to_worksession(row).map(|ws| SessionExport {
-            id: ws.id,
-            date: ws.date,
-            position: ws.position,
-            start: ws.start,
-            lunch_break: ws.lunch,
-            end: ws.end,
-            work_duration: ws.work_duration.map(|m| {
-                let (hh, mm) = mins2readable(m);
-                format!("{}h {}m", hh, mm)
-            }),
-        })
-    })?;
-
-    rows.collect()
+    for_each_row(
+        conn,
+        query,
+        |row| {
+            db::row_to_worksession(row).map(|ws| {
+                let ws = session_report(ws, config).session;
+                SessionExport {
+                    id: ws.id,
+                    date: ws.date,
+                    position: ws.position,
+                    start: ws.start,
+                    lunch_break: ws.lunch,
+                    end: ws.end,
+                    work_duration: ws.work_duration.map(|m| {
+                        let (hh, mm) = mins2readable(m);
+                        format!("{}h {}m", hh, mm)
+                    }),
+                }
+            })
+        },
+        f,
+    )
 }
 
-fn ensure_writable(path: &Path, force: bool) -> Result<(), Box<dyn Error>> {
-    if !path.exists() {
+/// Refuse to replace an existing `path` unless `force` is set or the user confirms it.
+pub(crate) fn ensure_writable(path: &Path, force: bool, mode: Confirm) -> Result<()> {
+    if !path.exists() || force {
         return Ok(());
     }
-    if force {
-        return Ok(());
-    }
-
-    // Interactive prompt
-    eprint!(
-        "⚠️  File '{}' already exists. Overwrite? [y/N]: ",
-        path.display()
-    );
-    io::stderr().flush().ok();
-
-    let mut answer = String::new();
-    io::stdin().read_line(&mut answer)?;
-    let ans = answer.trim().to_ascii_lowercase();
-
-    if ans == "y" || ans == "yes" {
+    let prompt = format!("⚠️  File '{}' already exists. Overwrite", path.display());
+    if confirm(&prompt, mode)? {
         Ok(())
     } else {
-        Err("Export cancelled: existing file not overwritten"
-            .to_string()
-            .into())
+        Err(Error::Export(
+            "Export cancelled: existing file not overwritten".to_string(),
+        ))
     }
 }
 
 /// Export data as JSON
-fn export_json<T: Serialize>(data: &[T], path: &Path) -> Result<(), Box<dyn Error>> {
+pub(crate) fn export_json<T: Serialize>(data: &[T], path: &Path) -> Result<()> {
     let json_data = serde_json::to_string_pretty(data)?;
     let mut file = File::create(path)?;
     file.write_all(json_data.as_bytes())?;
-    println!("✅ Exported data to {}", path.display());
+    crate::progress!("✅ Exported data to {}", path.display());
     Ok(())
 }
 
-fn export_csv<T: Serialize>(data: &[T], path: &Path) -> Result<(), Box<dyn Error>> {
+pub(crate) fn export_csv<T: Serialize>(data: &[T], path: &Path) -> Result<()> {
     let mut wtr = csv::Writer::from_path(path)?;
     for item in data {
         wtr.serialize(item)?;
     }
     wtr.flush()?;
-    println!("✅ Exported data to {}", path.display());
+    crate::progress!("✅ Exported data to {}", path.display());
     Ok(())
 }
 
-pub fn export_xlsx<T: Serialize>(data: &[T], path: &Path) -> Result<(), Box<dyn Error>> {
-    println!("📘 Exporting to XLSX: {}", path.display());
+/// Header of the formula column added next to `work_duration` in XLSX exports.
+const SURPLUS_HEADER: &str = "surplus_minutes";
+/// Excel duration format: hours are not wrapped at 24
+const DURATION_FORMAT: &str = "[h]:mm";
+const SURPLUS_FORMAT: &str = "+0;-0;0";
+
+/// How a column is written to XLSX, chosen from its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum XlsxKind {
+    /// Strings (dates and times become Excel serials, see `parse_to_excel_date`) and plain numbers
+    Value,
+    /// `7h 30m` strings written as Excel durations, summed in the totals row
+    Duration,
+    /// Whole minutes, summed in the totals row
+    Minutes,
+}
+
+fn xlsx_kind(header: &str) -> XlsxKind {
+    match header {
+        "work_duration" => XlsxKind::Duration,
+        "lunch_break" => XlsxKind::Minutes,
+        h if h.ends_with("_minutes") => XlsxKind::Minutes,
+        _ => XlsxKind::Value,
+    }
+}
+
+/// Columns a `sessions` row needs for its `session_report` in the XLSX surplus column.
+const SURPLUS_COLUMNS: [&str; 5] = [
+    "position",
+    "start_time",
+    "lunch_break",
+    "end_time",
+    "work_duration",
+];
+
+/// Terms of the XLSX `surplus_minutes` formula of a session row, from its `session_report`.
+#[derive(Debug, Clone, Copy)]
+struct SurplusTerms {
+    /// `min_work_duration` of the config
+    expected: i64,
+    /// Lunch counted beyond the recorded `lunch_break` (e.g. the minimum lunch of office days)
+    lunch_extra: i64,
+}
+
+impl SurplusTerms {
+    /// The terms of a row, `None` when `list` shows no surplus for it.
+    fn of(headers: &[String], cells: &[String], config: &Config) -> Option<Self> {
+        let cell = |name: &str| {
+            headers
+                .iter()
+                .position(|h| h == name)
+                .map(|c| cells[c].clone())
+        };
+        let session = WorkSession {
+            id: 0,
+            date: cell("date").unwrap_or_default(),
+            position: cell("position")?,
+            start: cell("start_time")?,
+            lunch: cell("lunch_break")?.parse().ok()?,
+            end: cell("end_time")?,
+            work_duration: None,
+        };
+        let worked = parse_work_duration_to_minutes(&cell("work_duration")?);
+        let surplus = session_report(session, config).surplus_minutes?;
+        let expected = parse_work_duration_to_minutes(&config.min_work_duration);
+        Some(SurplusTerms {
+            expected,
+            lunch_extra: worked - expected - surplus,
+        })
+    }
+
+    /// The surplus formula of the duration `cell`.
+    fn formula(&self, cell: &str) -> String {
+        let lunch = match self.lunch_extra {
+            0 => String::new(),
+            m => format!("{:+}", -m),
+        };
+        format!(
+            "=IF(ISNUMBER({cell}),ROUND({cell}*1440,0)-{}{lunch},\"\")",
+            self.expected
+        )
+    }
+}
+
+/// Cells of an XLSX row and the surplus terms of its `surplus_minutes` column.
+type XlsxRow = (Vec<String>, Option<SurplusTerms>);
+
+/// Totals row cell of a month sheet, referenced from the summary sheet.
+struct XlsxTotal {
+    header: String,
+    cell: String,
+    value: f64,
+    num_format: &'static str,
+}
+
+/// Export to XLSX: one worksheet per month (by the `date` column) with autofilter, frozen header
+/// and a `SUM` totals row, preceded by a `Summary` sheet linking the monthly totals.
+///
+/// `work_duration` is written as a real duration (`[h]:mm`); when the rows are sessions, it is
+/// followed by a `surplus_minutes` formula column with the `session_report` surplus of `config`.
+pub fn export_xlsx(table: &Table, path: &Path, config: &Config) -> Result<()> {
+    crate::progress!("📘 Exporting to XLSX: {}", path.display());
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| Error::invalid("invalid path"))?;
 
-    // Create a new workbook and add a worksheet.
     let mut workbook = Workbook::new();
-    let worksheet = workbook.add_worksheet();
 
-    if data.is_empty() {
+    if table.rows.is_empty() {
+        let worksheet = workbook.add_worksheet();
         worksheet.write(0, 0, "No data available")?;
-        workbook.save(path.to_str().ok_or("invalid path")?)?;
-        println!("✅ XLSX export completed (empty dataset).");
+        workbook.save(path_str)?;
+        crate::progress!("✅ XLSX export completed (empty dataset).");
         return Ok(());
     }
 
-    // Serialize dynamically to obtain headers and values
-    let json = serde_json::to_value(data)?;
-    let arr = json.as_array().ok_or("invalid data serialization")?;
-    let first_obj = arr[0].as_object().ok_or("first row is not an object")?;
+    let mut headers: Vec<String> = table.headers.iter().map(|h| h.to_string()).collect();
+    let mut rows: Vec<XlsxRow> = table.rows.iter().map(|r| (r.clone(), None)).collect();
+    let is_session = SURPLUS_COLUMNS
+        .iter()
+        .all(|name| headers.iter().any(|h| h == name));
+    if let Some(c) = headers.iter().position(|h| h == "work_duration")
+        && is_session
+    {
+        for (cells, terms) in rows.iter_mut() {
+            *terms = SurplusTerms::of(&headers, cells, config);
+            // empty placeholder, so cells stay aligned with headers
+            cells.insert(c + 1, String::new());
+        }
+        headers.insert(c + 1, SURPLUS_HEADER.to_string());
+    }
 
-    let headers: Vec<String> = first_obj.keys().cloned().collect();
+    // Group rows by month, keeping their order inside each month
+    let date_col = headers.iter().position(|h| h == "date");
+    let mut months: Vec<(String, Vec<&XlsxRow>)> = Vec::new();
+    for row in &rows {
+        let month = date_col
+            .and_then(|c| row.0[c].get(..7))
+            .unwrap_or(table.name)
+            .to_string();
+        match months.iter_mut().find(|(m, _)| *m == month) {
+            Some((_, month_rows)) => month_rows.push(row),
+            None => months.push((month, vec![row])),
+        }
+    }
+    months.sort_by(|a, b| a.0.cmp(&b.0));
 
-    // Visual table style: emulate Excel "Blue, Medium 16"
-    // Header: deep blue background, white bold text, thin border
-    let header_format = Format::new()
+    workbook.add_worksheet().set_name("Summary")?;
+    let mut totals = Vec::with_capacity(months.len());
+    for (month, month_rows) in &months {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(month)?;
+        totals.push(write_xlsx_month(worksheet, &headers, month_rows)?);
+    }
+    write_xlsx_summary(workbook.worksheet_from_index(0)?, &months, &totals)?;
+
+    workbook.save(path_str)?;
+    crate::progress!(
+        "✅ XLSX export completed: {} month sheet(s) and a summary.",
+        months.len()
+    );
+    Ok(())
+}
+
+// Visual table style: emulate Excel "Blue, Medium 16"
+// Header: deep blue background, white bold text, thin border
+fn xlsx_header_format() -> Format {
+    Format::new()
         .set_bold()
         .set_font_color(Color::RGB(0xFFFFFF))
         .set_background_color(Color::RGB(0x2F75B5))
         .set_pattern(FormatPattern::Solid)
-        .set_border(FormatBorder::Thin);
-
-    // Banded row colors (light blue / white) and border will be applied per-cell
-    let band1_color = Color::RGB(0xEAF3FB); // light blue
-    let band2_color = Color::RGB(0xFFFFFF); // white
+        .set_border(FormatBorder::Thin)
+}
 
-    // Base number format alignment (border will be added per-cell)
-    let num_align = FormatAlign::Right;
+fn xlsx_total_format() -> Format {
+    Format::new()
+        .set_bold()
+        .set_border(FormatBorder::Thin)
+        .set_border_top(FormatBorder::Double)
+}
 
-    // Write headers (shifted one row down to leave room for the label)
+/// Write the rows of one month, the totals row, autofilter and frozen header.
+fn write_xlsx_month(
+    worksheet: &mut rust_xlsxwriter::Worksheet,
+    headers: &[String],
+    rows: &[&XlsxRow],
+) -> Result<Vec<XlsxTotal>> {
+    let header_format = xlsx_header_format();
     for (c, header) in headers.iter().enumerate() {
         worksheet.write_with_format(0u32, c as u16, header, &header_format)?;
     }
 
-    // Freeze the first row so the header remains visible when scrolling.
-    // Use the worksheet API to freeze panes if available. Try common method name
-    // and ignore error at compile time if not present; we'll remove the call if
-    // the compiler reports the method is absent.
-    let _ = worksheet.set_freeze_panes(1, 0);
+    // Banded row colors (light blue / white) and border are applied per-cell
+    let band1_color = Color::RGB(0xEAF3FB); // light blue
+    let band2_color = Color::RGB(0xFFFFFF); // white
 
     // Track column widths (approximate, measured with Unicode width)
     let mut col_widths: Vec<usize> = headers
         .iter()
         .map(|h| UnicodeWidthStr::width(h.as_str()))
         .collect();
+    let mut sums = vec![0.0f64; headers.len()];
 
-    // Write rows (data starts two rows down: label row + header row)
-    for (r, item) in arr.iter().enumerate() {
+    for (r, (cells, terms)) in rows.iter().enumerate() {
         let row = (r + 1) as u32;
-        let obj = item.as_object().ok_or("row is not an object")?;
-
-        for (c, key) in headers.iter().enumerate() {
-            let v = obj.get(key).unwrap_or(&Value::Null);
-            match v {
-                Value::String(s) => {
-                    // Try to parse date/time strings and write as Excel dates
-                    if let Some((num_format, serial)) = parse_to_excel_date(s) {
-                        // Determine band color for this data row (use original index r)
-                        let bg = if (r % 2) == 0 {
-                            band1_color
-                        } else {
-                            band2_color
-                        };
-                        let fmt = Format::new()
-                            .set_num_format(num_format)
-                            .set_background_color(bg)
-                            .set_pattern(FormatPattern::Solid)
-                            .set_border(FormatBorder::Thin);
-                        worksheet.write_with_format(row, c as u16, serial, &fmt)?;
-                        col_widths[c] = col_widths[c].max(UnicodeWidthStr::width(s.as_str()));
-                    } else if !s.is_empty() {
-                        let bg = if (r % 2) == 0 {
-                            band1_color
-                        } else {
-                            band2_color
-                        };
-                        let fmt = Format::new()
-                            .set_background_color(bg)
-                            .set_pattern(FormatPattern::Solid)
-                            .set_border(FormatBorder::Thin);
-                        worksheet.write_with_format(row, c as u16, s, &fmt)?;
-                        col_widths[c] = col_widths[c].max(UnicodeWidthStr::width(s.as_str()));
-                    } else {
-                        // empty string: skip writing to avoid creating an explicit cell
-                    }
-                }
-                Value::Number(n) => {
-                    let num = n.as_f64().ok_or("invalid number")?;
-                    // right align for numbers, keep band background and border
-                    let bg = if (r % 2) == 0 {
-                        band1_color
-                    } else {
-                        band2_color
-                    };
-                    let fmt = Format::new()
-                        .set_align(num_align)
-                        .set_background_color(bg)
-                        .set_pattern(FormatPattern::Solid)
-                        .set_border(FormatBorder::Thin);
-                    worksheet.write_with_format(row, c as u16, num, &fmt)?;
-                    col_widths[c] =
-                        col_widths[c].max(UnicodeWidthStr::width(n.to_string().as_str()));
-                }
-                Value::Bool(b) => {
-                    let s = b.to_string();
-                    let bg = if (r % 2) == 0 {
-                        band1_color
-                    } else {
-                        band2_color
-                    };
-                    let fmt = Format::new()
-                        .set_background_color(bg)
-                        .set_pattern(FormatPattern::Solid)
-                        .set_border(FormatBorder::Thin);
-                    worksheet.write_with_format(row, c as u16, &s, &fmt)?;
-                    col_widths[c] = col_widths[c].max(UnicodeWidthStr::width(s.as_str()));
-                }
-                _ => {
-                    // Null / other: skip writing (no border)
+        let bg = if (r % 2) == 0 {
+            band1_color
+        } else {
+            band2_color
+        };
+        let base = Format::new()
+            .set_background_color(bg)
+            .set_pattern(FormatPattern::Solid)
+            .set_border(FormatBorder::Thin);
+
+        for (c, (key, s)) in headers.iter().zip(cells.iter()).enumerate() {
+            let col = c as u16;
+            if key == SURPLUS_HEADER && c > 0 && headers[c - 1] == "work_duration" {
+                let fmt = base
+                    .clone()
+                    .set_num_format(SURPLUS_FORMAT)
+                    .set_align(FormatAlign::Right);
+                // worked minutes (duration cell on the left) minus the expected minutes and extra lunch
+                let Some(terms) = terms else {
+                    worksheet.write_blank(row, col, &fmt)?;
+                    continue;
+                };
+                let worked = parse_work_duration_to_minutes(&cells[c - 1]);
+                let surplus = (worked - terms.expected - terms.lunch_extra) as f64;
+                sums[c] += surplus;
+                let formula = Formula::new(terms.formula(&row_col_to_cell(row, col - 1)))
+                    .set_result(surplus.to_string());
+                worksheet.write_formula_with_format(row, col, formula, &fmt)?;
+                continue;
+            }
+
+            let kind = xlsx_kind(key);
+            if s.is_empty() {
+                // empty value: skip writing to avoid creating an explicit cell
+                continue;
+            }
+            if kind == XlsxKind::Duration {
+                let minutes = parse_work_duration_to_minutes(s) as f64;
+                sums[c] += minutes;
+                let fmt = base.clone().set_num_format(DURATION_FORMAT);
+                worksheet.write_number_with_format(row, col, minutes / 1440.0, &fmt)?;
+            } else if let Some((num_format, serial)) = parse_to_excel_date(s) {
+                // Dates and times are written as Excel serials
+                let fmt = base.clone().set_num_format(num_format);
+                worksheet.write_with_format(row, col, serial, &fmt)?;
+            } else if let Ok(num) = s.parse::<f64>() {
+                if kind == XlsxKind::Minutes {
+                    sums[c] += num;
                 }
+                // right align for numbers, keep band background and border
+                let fmt = base.clone().set_align(FormatAlign::Right);
+                worksheet.write_with_format(row, col, num, &fmt)?;
+            } else {
+                worksheet.write_with_format(row, col, s, &base)?;
             }
+            col_widths[c] = col_widths[c].max(UnicodeWidthStr::width(s.as_str()));
         }
     }
 
+    // Totals row: SUM of the duration and minutes columns
+    let last_row = rows.len() as u32;
+    let total_row = last_row + 1;
+    let total_format = xlsx_total_format();
+    worksheet.write_with_format(total_row, 0, "Total", &total_format)?;
+    let mut totals = Vec::new();
+    for (c, key) in headers.iter().enumerate() {
+        let col = c as u16;
+        let (value, num_format) = match xlsx_kind(key) {
+            XlsxKind::Value => continue,
+            XlsxKind::Duration => (sums[c] / 1440.0, DURATION_FORMAT),
+            XlsxKind::Minutes if key == SURPLUS_HEADER => (sums[c], SURPLUS_FORMAT),
+            XlsxKind::Minutes => (sums[c], "0"),
+        };
+        let formula = Formula::new(format!(
+            "=SUM({}:{})",
+            row_col_to_cell(1, col),
+            row_col_to_cell(last_row, col)
+        ))
+        .set_result(value.to_string());
+        let fmt = total_format.clone().set_num_format(num_format);
+        worksheet.write_formula_with_format(total_row, col, formula, &fmt)?;
+        totals.push(XlsxTotal {
+            header: key.clone(),
+            cell: row_col_to_cell(total_row, col),
+            value,
+            num_format,
+        });
+    }
+
+    worksheet.autofilter(0, 0, last_row, (headers.len() - 1) as u16)?;
+    // Freeze the first row so the header remains visible when scrolling.
+    worksheet.set_freeze_panes(1, 0)?;
+
     // Convert character widths to Excel column width units (approximation).
     // Excel column width roughly equals number of '0' chars that fit; we add a padding.
     for (c, w) in col_widths.iter().enumerate() {
         let width_chars = *w as f64 + 2.0; // padding
         worksheet.set_column_width(c as u16, width_chars)?;
     }
-
-    workbook.save(path.to_str().ok_or("invalid path")?)?;
-    println!("✅ XLSX export completed with styling.");
-    Ok(())
+    Ok(totals)
 }
 
-pub fn export_pdf<T: Serialize>(
-    data: &[T],
-    path: &Path,
-    export_events: bool,
-) -> Result<(), Box<dyn Error>> {
-    println!("📘 Exporting to PDF: {}", path.display());
-
-    let headers = get_headers(export_events);
-    let data_vec = if export_events {
-        events_to_table(unsafe {
-            // Safety: we ensure T is EventExport when export_events is true
-            &*(data as *const [T] as *const [EventExport])
+/// One row per month linking the totals of its sheet, plus a grand total.
+fn write_xlsx_summary(
+    worksheet: &mut rust_xlsxwriter::Worksheet,
+    months: &[(String, Vec<&XlsxRow>)],
+    totals: &[Vec<XlsxTotal>],
+) -> Result<()> {
+    let header_format = xlsx_header_format();
+    let columns: Vec<(&str, &'static str)> = totals
+        .first()
+        .map(|t| {
+            t.iter()
+                .map(|total| (total.header.as_str(), total.num_format))
+                .collect()
         })
-    } else {
-        sessions_to_table(unsafe {
-            // Safety: we ensure T is SessionExport when export_events is false
-            &*(data as *const [T] as *const [SessionExport])
-        })
-    };
-    let mut pdf = PdfManager::new();
-    pdf.write_table(&headers, &data_vec); // 'data' deve essere &[Vec<String>]
-    pdf.save(path)?;
+        .unwrap_or_default();
+    worksheet.write_with_format(0, 0, "month", &header_format)?;
+    worksheet.write_with_format(0, 1, "rows", &header_format)?;
+    for (i, (header, _)) in columns.iter().enumerate() {
+        worksheet.write_with_format(0, (i + 2) as u16, *header, &header_format)?;
+    }
 
-    println!("✅ PDF export completed.");
-    Ok(())
-}
+    let cell_format = Format::new().set_border(FormatBorder::Thin);
+    for (m, ((month, rows), month_totals)) in months.iter().zip(totals).enumerate() {
+        let row = (m + 1) as u32;
+        worksheet.write_with_format(row, 0, month, &cell_format)?;
+        worksheet.write_with_format(row, 1, rows.len() as f64, &cell_format)?;
+        for (i, total) in month_totals.iter().enumerate() {
+            let formula = Formula::new(format!("='{}'!{}", month, total.cell))
+                .set_result(total.value.to_string());
+            let fmt = cell_format.clone().set_num_format(total.num_format);
+            worksheet.write_formula_with_format(row, (i + 2) as u16, formula, &fmt)?;
+        }
+    }
 
-fn parse_range(range: &str) -> Result<(String, String), String> {
-    // YYYY
-    if range.len() == 4 && range.chars().all(|c| c.is_ascii_digit()) {
-        let y = range.to_string();
-        return Ok((format!("{y}-01-01"), format!("{y}-12-31")));
+    let last_row = months.len() as u32;
+    let total_row = last_row + 1;
+    let total_format = xlsx_total_format();
+    worksheet.write_with_format(total_row, 0, "Total", &total_format)?;
+    for col in 1..(columns.len() + 2) as u16 {
+        let value: f64 = if col == 1 {
+            months.iter().map(|(_, rows)| rows.len() as f64).sum()
+        } else {
+            totals.iter().map(|t| t[(col - 2) as usize].value).sum()
+        };
+        let num_format = if col == 1 {
+            "0"
+        } else {
+            columns[(col - 2) as usize].1
+        };
+        let formula = Formula::new(format!(
+            "=SUM({}:{})",
+            row_col_to_cell(1, col),
+            row_col_to_cell(last_row, col)
+        ))
+        .set_result(value.to_string());
+        let fmt = total_format.clone().set_num_format(num_format);
+        worksheet.write_formula_with_format(total_row, col, formula, &fmt)?;
     }
 
-    // YYYY-MM
-    if range.len() == 7 && &range[4..5] == "-" {
-        let y: i32 = range[0..4].parse().map_err(|_| "invalid year")?;
-        let m: u32 = range[5..7].parse().map_err(|_| "invalid month")?;
-        let last = month_last_day(y, m).ok_or("invalid month in range")?;
-        return Ok((format!("{y}-{m:02}-01"), format!("{y}-{m:02}-{last:02}")));
+    worksheet.set_freeze_panes(1, 0)?;
+    worksheet.set_column_width(0, 10)?;
+    for col in 1..(columns.len() + 2) as u16 {
+        worksheet.set_column_width(col, 16)?;
     }
+    Ok(())
+}
 
-    // YYYY-MM-{dd..dd}
-    if range.len() >= 15
-        && &range[4..5] == "-"
-        && range.contains("..")
-        && range.contains('{')
-        && range.ends_with('}')
-    {
-        let y: i32 = range[0..4].parse().map_err(|_| "invalid year")?;
-        let m: u32 = range[5..7].parse().map_err(|_| "invalid month")?;
-        let inside = &range[8..]; // expected "{dd..dd}"
-        if !(inside.starts_with('{') && inside.ends_with('}')) {
-            return Err("invalid day range brace".into());
-        }
-        let inner = &inside[1..inside.len() - 1]; // "dd..dd"
-        let parts: Vec<&str> = inner.split("..").collect();
-        if parts.len() != 2 {
-            return Err("invalid day range syntax".into());
-        }
-        let d1: u32 = parts[0].parse().map_err(|_| "invalid start day")?;
-        let d2: u32 = parts[1].parse().map_err(|_| "invalid end day")?;
-        let last = month_last_day(y, m).ok_or("invalid month in range")?;
-        if d1 == 0 || d2 == 0 || d1 > d2 || d2 > last {
-            return Err("day range out of bounds".into());
-        }
-        return Ok((format!("{y}-{m:02}-{d1:02}"), format!("{y}-{m:02}-{d2:02}")));
+pub fn export_pdf(table: &Table, path: &Path, report: &PdfReport) -> Result<()> {
+    crate::progress!("📘 Exporting to PDF: {}", path.display());
+
+    let mut pdf = PdfManager::new();
+    if !report.title.is_empty() {
+        pdf.set_title(&report.title);
+    }
+    pdf.write_table(&table.headers, &table.rows);
+    if !report.summary.is_empty() {
+        pdf.write_summary("Summary", &report.summary);
     }
+    let pages = pdf.page_count();
+    pdf.save(path)?;
 
-    Err("unsupported --range format (use YYYY, YYYY-MM, or YYYY-MM-{dd..dd})".into())
+    crate::progress!("✅ PDF export completed ({} page(s)).", pages);
+    Ok(())
 }
 
-fn month_last_day(y: i32, m: u32) -> Option<u32> {
-    match m {
-        1 | 3 | 5 | 7 | 8 | 10 | 12 => Some(31),
-        4 | 6 | 9 | 11 => Some(30),
-        2 => {
-            let leap = (y % 4 == 0 && y % 100 != 0) || (y % 400 == 0);
-            Some(if leap { 29 } else { 28 })
-        }
-        _ => None,
-    }
+/// Export to an OpenDocument spreadsheet (same columns as the PDF table)
+pub fn export_ods(table: &Table, path: &Path) -> Result<()> {
+    crate::progress!("📗 Exporting to ODS: {}", path.display());
+
+    ods::write_ods(path, table.name, &table.headers, &table.rows)?;
+
+    crate::progress!("✅ ODS export completed ({} row(s)).", table.rows.len());
+    Ok(())
 }
 
 // Try parsing common date/time formats and convert to Excel serial number.
@@ -0,0 +1,417 @@
+//! Self-contained HTML report (`export --format html`).
+//!
+//! A single file with inline CSS and SVG (no scripts, fonts or other external assets): a monthly
+//! table, a bar chart of the hours worked per day, the cumulative surplus as a line chart and a pie
+//! of the days per position. Figures are the `export --daily` and `--monthly` ones; open pairs
+//! are counted from the events.
+
+use crate::config::{Config, EmployeeInfo};
+use crate::db::{Event, WorkSession};
+use crate::export::{DailyExport, MonthlyExport, monthly_totals};
+use crate::logic::{compute_event_pairs, compute_event_summaries, month_name};
+use crate::utils::{describe_position, signed_hhmm, weekday_str};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Chart colors per position code.
+const POSITION_COLORS: [(&str, &str); 5] = [
+    ("O", "#2f75b5"),
+    ("R", "#70ad47"),
+    ("C", "#ed7d31"),
+    ("M", "#8064a2"),
+    ("H", "#a5a5a5"),
+];
+const OTHER_COLOR: &str = "#7f7f7f";
+
+const CHART_WIDTH: f64 = 900.0;
+const CHART_HEIGHT: f64 = 260.0;
+const MARGIN_LEFT: f64 = 48.0;
+const MARGIN_RIGHT: f64 = 10.0;
+const MARGIN_TOP: f64 = 10.0;
+const MARGIN_BOTTOM: f64 = 28.0;
+
+const STYLE: &str = r#"
+body { font-family: "DejaVu Sans", Arial, sans-serif; margin: 2em auto; max-width: 960px; color: #222; }
+h1 { font-size: 1.6em; margin-bottom: 0.2em; }
+h2 { font-size: 1.2em; margin-top: 1.6em; border-bottom: 1px solid #ccc; }
+.meta { color: #555; }
+table { border-collapse: collapse; margin: 0.5em 0; }
+th { background: #2f75b5; color: #fff; text-align: left; }
+th, td { border: 1px solid #bbb; padding: 3px 10px; }
+tbody tr:nth-child(odd) { background: #eaf3fb; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+tr.total td { font-weight: bold; border-top: 3px double #555; }
+.neg { color: #c00000; }
+.chart { width: 100%; height: auto; }
+.chart text { font-size: 11px; fill: #444; }
+.pie { display: flex; align-items: center; gap: 2em; }
+.legend span { display: inline-block; width: 0.9em; height: 0.9em; margin-right: 0.4em; vertical-align: middle; }
+.legend li { list-style: none; margin: 0.2em 0; }
+"#;
+
+/// One day of the report: the `export --daily` row of its session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportDay {
+    #[serde(flatten)]
+    pub day: DailyExport,
+    /// Pairs missing their in or out
+    pub unmatched: usize,
+}
+
+impl ReportDay {
+    fn worked_minutes(&self) -> i64 {
+        self.day.worked_minutes.unwrap_or_default()
+    }
+}
+
+/// Totals of one month of the report: the `export --monthly` row plus the holidays.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportMonth {
+    #[serde(flatten)]
+    pub totals: MonthlyExport,
+    pub holidays: usize,
+}
+
+/// Days of the report, one per session (ordered by date), with the open pairs of the events.
+pub fn report_days(
+    sessions: Vec<WorkSession>,
+    events: &[Event],
+    config: &Config,
+) -> Vec<ReportDay> {
+    let mut unmatched: BTreeMap<String, usize> = BTreeMap::new();
+    for pair in compute_event_summaries(&compute_event_pairs(events)) {
+        if pair.unmatched {
+            *unmatched.entry(pair.date).or_default() += 1;
+        }
+    }
+    sessions
+        .into_iter()
+        .map(|session| {
+            let day = DailyExport::new(session, config);
+            ReportDay {
+                unmatched: unmatched.get(&day.date).copied().unwrap_or_default(),
+                day,
+            }
+        })
+        .collect()
+}
+
+/// Totals of `days` per `YYYY-MM`, as `export --monthly` computes them.
+pub fn report_months(days: &[ReportDay]) -> Vec<ReportMonth> {
+    monthly_totals(days.iter().map(|d| &d.day))
+        .into_iter()
+        .map(|totals| ReportMonth {
+            holidays: days
+                .iter()
+                .filter(|d| d.day.position == "H" && d.day.date.starts_with(&totals.month))
+                .count(),
+            totals,
+        })
+        .collect()
+}
+
+/// The whole HTML document.
+pub fn render(title: &str, employee: &EmployeeInfo, days: &[ReportDay]) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+        title = escape(title)
+    );
+    let mut meta = Vec::new();
+    if !employee.name.is_empty() {
+        meta.push(escape(&employee.name));
+    }
+    if !employee.company.is_empty() {
+        meta.push(escape(&employee.company));
+    }
+    if let (Some(first), Some(last)) = (days.first(), days.last()) {
+        meta.push(format!("{} – {}", first.day.date, last.day.date));
+    }
+    let _ = writeln!(html, "<p class=\"meta\">{}</p>", meta.join(" · "));
+
+    if days.is_empty() {
+        html.push_str("<p>No data in this range.</p>\n</body>\n</html>\n");
+        return html;
+    }
+
+    html.push_str("<h2>Monthly totals</h2>\n");
+    html.push_str(&monthly_table(&report_months(days)));
+    html.push_str("<h2>Hours worked per day</h2>\n");
+    html.push_str(&bar_chart(days));
+    html.push_str("<h2>Cumulative surplus</h2>\n");
+    html.push_str(&surplus_chart(days));
+    html.push_str("<h2>Days per position</h2>\n");
+    html.push_str(&position_pie(days));
+    html.push_str("<h2>Days</h2>\n");
+    html.push_str(&daily_table(days));
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn monthly_table(months: &[ReportMonth]) -> String {
+    let mut html = String::from(
+        "<table>\n<thead><tr><th>Month</th><th>Days</th><th>Holidays</th><th>Worked</th><th>Expected</th><th>Surplus</th></tr></thead>\n<tbody>\n",
+    );
+    for m in months {
+        let label = format!(
+            "{} {}",
+            month_name(&m.totals.month[5..]),
+            &m.totals.month[..4]
+        );
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td>{}</tr>",
+            label,
+            m.totals.days,
+            m.holidays,
+            signed_hhmm(m.totals.worked_minutes),
+            signed_hhmm(m.totals.expected_minutes),
+            surplus_cell(Some(m.totals.surplus_minutes))
+        );
+    }
+    let sum = |f: fn(&ReportMonth) -> i64| months.iter().map(f).sum::<i64>();
+    let _ = writeln!(
+        html,
+        "<tr class=\"total\"><td>Total</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td>{}</tr>",
+        sum(|m| m.totals.days as i64),
+        sum(|m| m.holidays as i64),
+        signed_hhmm(sum(|m| m.totals.worked_minutes)),
+        signed_hhmm(sum(|m| m.totals.expected_minutes)),
+        surplus_cell(Some(sum(|m| m.totals.surplus_minutes)))
+    );
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+fn daily_table(days: &[ReportDay]) -> String {
+    let mut html = String::from(
+        "<details>\n<summary>Show every day</summary>\n<table>\n<thead><tr><th>Date</th><th>Day</th><th>Position</th><th>Worked</th><th>Surplus</th><th>Note</th></tr></thead>\n<tbody>\n",
+    );
+    for d in days {
+        let note = if d.unmatched > 0 {
+            format!("{} open pair(s)", d.unmatched)
+        } else {
+            String::new()
+        };
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td>{}<td>{}</td></tr>",
+            d.day.date,
+            weekday_str(&d.day.date, 'm'),
+            escape(&describe_position(&d.day.position).0),
+            signed_hhmm(d.worked_minutes()),
+            surplus_cell(d.day.surplus_minutes),
+            note
+        );
+    }
+    html.push_str("</tbody>\n</table>\n</details>\n");
+    html
+}
+
+fn surplus_cell(minutes: Option<i64>) -> String {
+    match minutes {
+        None => "<td class=\"num\"></td>".to_string(),
+        Some(m) if m < 0 => format!("<td class=\"num neg\">{}</td>", signed_hhmm(m)),
+        Some(m) => format!("<td class=\"num\">+{}</td>", signed_hhmm(m)),
+    }
+}
+
+/// Bars of the worked hours per day, colored by position.
+fn bar_chart(days: &[ReportDay]) -> String {
+    let plot_w = CHART_WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_h = CHART_HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let max_minutes = days.iter().map(|d| d.worked_minutes()).max().unwrap_or(0);
+    let top = ((max_minutes as f64 / 60.0) * 1.1).ceil().max(1.0);
+    let y = |hours: f64| MARGIN_TOP + plot_h - hours / top * plot_h;
+    let slot = plot_w / days.len() as f64;
+
+    let mut svg = svg_open("Hours worked per day");
+    let step = (top / 5.0).ceil().max(1.0);
+    let mut h = 0.0;
+    while h <= top {
+        grid_line(&mut svg, y(h), &format!("{}h", h));
+        h += step;
+    }
+    let label_every = days.len().div_ceil(31);
+    for (i, d) in days.iter().enumerate() {
+        let x = MARGIN_LEFT + i as f64 * slot;
+        let hours = d.worked_minutes() as f64 / 60.0;
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{} {} – {}</title></rect>",
+            x + slot * 0.1,
+            y(hours),
+            slot * 0.8,
+            MARGIN_TOP + plot_h - y(hours),
+            position_color(&d.day.position),
+            d.day.date,
+            escape(&describe_position(&d.day.position).0),
+            signed_hhmm(d.worked_minutes())
+        );
+        if i % label_every == 0 {
+            x_label(&mut svg, x + slot / 2.0, &d.day.date[8..]);
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Line of the running total of the daily surplus.
+fn surplus_chart(days: &[ReportDay]) -> String {
+    let plot_w = CHART_WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_h = CHART_HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let mut total = 0;
+    let cumulative: Vec<i64> = days
+        .iter()
+        .map(|d| {
+            total += d.day.surplus_minutes.unwrap_or(0);
+            total
+        })
+        .collect();
+    let hours = |m: i64| m as f64 / 60.0;
+    let low = hours(cumulative.iter().copied().min().unwrap_or(0).min(0)).floor();
+    let high = hours(cumulative.iter().copied().max().unwrap_or(0).max(0)).ceil();
+    let (low, high) = if low == high {
+        (low - 1.0, high + 1.0)
+    } else {
+        (low, high)
+    };
+    let y = |h: f64| MARGIN_TOP + (high - h) / (high - low) * plot_h;
+    let slot = plot_w / days.len() as f64;
+    let x = |i: usize| MARGIN_LEFT + i as f64 * slot + slot / 2.0;
+
+    let mut svg = svg_open("Cumulative surplus");
+    grid_line(&mut svg, y(high), &format!("{:+}h", high));
+    grid_line(&mut svg, y(0.0), "0h");
+    grid_line(&mut svg, y(low), &format!("{:+}h", low));
+    let points: Vec<String> = cumulative
+        .iter()
+        .enumerate()
+        .map(|(i, m)| format!("{:.1},{:.1}", x(i), y(hours(*m))))
+        .collect();
+    let _ = writeln!(
+        svg,
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"#2f75b5\" stroke-width=\"2\"/>",
+        points.join(" ")
+    );
+    let label_every = days.len().div_ceil(31);
+    for (i, (d, m)) in days.iter().zip(&cumulative).enumerate() {
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"2.5\" fill=\"{}\"><title>{}: {}</title></circle>",
+            x(i),
+            y(hours(*m)),
+            if *m < 0 { "#c00000" } else { "#2f75b5" },
+            d.day.date,
+            signed_hhmm(*m)
+        );
+        if i % label_every == 0 {
+            x_label(&mut svg, x(i), &d.day.date[8..]);
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Pie of the number of days per position, with a legend.
+fn position_pie(days: &[ReportDay]) -> String {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for d in days {
+        match counts.iter_mut().find(|(p, _)| *p == d.day.position) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((d.day.position.clone(), 1)),
+        }
+    }
+    let order = |p: &str| {
+        POSITION_COLORS
+            .iter()
+            .position(|(code, _)| *code == p)
+            .unwrap_or(POSITION_COLORS.len())
+    };
+    counts.sort_by_key(|(p, _)| order(p));
+    let total = days.len() as f64;
+
+    let (cx, cy, r) = (110.0, 110.0, 100.0);
+    let mut svg = String::from(
+        "<div class=\"pie\">\n<svg viewBox=\"0 0 220 220\" width=\"220\" height=\"220\" role=\"img\" aria-label=\"Days per position\">\n",
+    );
+    if let [(pos, n)] = counts.as_slice() {
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{cx}\" cy=\"{cy}\" r=\"{r}\" fill=\"{}\"><title>{}: {}</title></circle>",
+            position_color(pos),
+            escape(&describe_position(pos).0),
+            n
+        );
+    } else {
+        let mut angle = -std::f64::consts::FRAC_PI_2;
+        for (pos, n) in &counts {
+            let sweep = *n as f64 / total * std::f64::consts::TAU;
+            let (x0, y0) = (cx + r * angle.cos(), cy + r * angle.sin());
+            angle += sweep;
+            let (x1, y1) = (cx + r * angle.cos(), cy + r * angle.sin());
+            let large = if sweep > std::f64::consts::PI { 1 } else { 0 };
+            let _ = writeln!(
+                svg,
+                "<path d=\"M{cx},{cy} L{x0:.2},{y0:.2} A{r},{r} 0 {large} 1 {x1:.2},{y1:.2} Z\" fill=\"{}\" stroke=\"#fff\"><title>{}: {}</title></path>",
+                position_color(pos),
+                escape(&describe_position(pos).0),
+                n
+            );
+        }
+    }
+    svg.push_str("</svg>\n<ul class=\"legend\">\n");
+    for (pos, n) in &counts {
+        let _ = writeln!(
+            svg,
+            "<li><span style=\"background:{}\"></span>{}: {} day(s) ({:.0}%)</li>",
+            position_color(pos),
+            escape(&describe_position(pos).0),
+            n,
+            *n as f64 / total * 100.0
+        );
+    }
+    svg.push_str("</ul>\n</div>\n");
+    svg
+}
+
+fn svg_open(label: &str) -> String {
+    format!(
+        "<svg class=\"chart\" viewBox=\"0 0 {CHART_WIDTH} {CHART_HEIGHT}\" role=\"img\" aria-label=\"{label}\">\n"
+    )
+}
+
+fn grid_line(svg: &mut String, y: f64, label: &str) {
+    let _ = writeln!(
+        svg,
+        "<line x1=\"{MARGIN_LEFT}\" y1=\"{y:.1}\" x2=\"{}\" y2=\"{y:.1}\" stroke=\"#ddd\"/><text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{label}</text>",
+        CHART_WIDTH - MARGIN_RIGHT,
+        MARGIN_LEFT - 6.0,
+        y + 4.0
+    );
+}
+
+fn x_label(svg: &mut String, x: f64, label: &str) {
+    let _ = writeln!(
+        svg,
+        "<text x=\"{x:.1}\" y=\"{}\" text-anchor=\"middle\">{label}</text>",
+        CHART_HEIGHT - 10.0
+    );
+}
+
+fn position_color(pos: &str) -> &'static str {
+    POSITION_COLORS
+        .iter()
+        .find(|(code, _)| *code == pos)
+        .map(|(_, color)| *color)
+        .unwrap_or(OTHER_COLOR)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
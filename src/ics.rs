@@ -0,0 +1,502 @@
+//! iCalendar (RFC 5545) export (`rtimelogger export --format ics`) and absence import.
+//!
+//! Every matched in/out pair becomes a `VEVENT` (floating local time, i.e. shown at the same
+//! wall-clock time by every calendar app) with position and lunch break in the description.
+//! Holidays (`H`) become all-day events. UIDs are derived from the event ids (or from the date
+//! for holidays recorded only in `work_sessions`), so importing a newer export into the same
+//! calendar updates the existing entries instead of duplicating them.
+//!
+//! The import side (`rtimelogger import --format ics`) reads all-day (or explicitly selected)
+//! events and turns each covered day into an absence record, see [`plan_import`].
+
+use crate::db::{self, Event, WorkSession};
+use crate::error::{Error, Result};
+use crate::lock;
+use crate::logic::{compute_event_pairs, compute_event_summaries};
+use crate::utils::{self, describe_position, mins2readable};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use regex::Regex;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Domain appended to every UID.
+pub const UID_DOMAIN: &str = "rtimelogger";
+
+/// Build a `VCALENDAR` from events (ordered by date and time) and legacy sessions.
+/// Only sessions with position `H` are used, for days without events.
+pub fn build_calendar(
+    events: &[Event],
+    sessions: &[WorkSession],
+    dtstamp: DateTime<Utc>,
+) -> String {
+    let stamp = dtstamp.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!(
+            "PRODID:-//rTimelogger//rtimelogger {}//EN",
+            env!("CARGO_PKG_VERSION")
+        ),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:rTimelogger".to_string(),
+    ];
+
+    let enriched = compute_event_pairs(events);
+    let days_with_events: HashSet<&str> = events.iter().map(|e| e.date.as_str()).collect();
+    for summary in compute_event_summaries(&enriched) {
+        if summary.unmatched {
+            continue;
+        }
+        let id_of = |kind: &str| {
+            enriched
+                .iter()
+                .find(|e| {
+                    e.event.date == summary.date && e.pair == summary.pair && e.event.kind == kind
+                })
+                .map(|e| e.event.id)
+        };
+        let (Some(in_id), Some(out_id)) = (id_of("in"), id_of("out")) else {
+            continue;
+        };
+        let Ok(date) = NaiveDate::parse_from_str(&summary.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let (label, _) = describe_position(&summary.position);
+
+        if summary.position == "H" {
+            lines.extend(all_day_event(
+                &format!("absence-{}@{}", in_id, UID_DOMAIN),
+                &stamp,
+                date,
+                &label,
+            ));
+            continue;
+        }
+
+        let (Ok(start), Ok(end)) = (
+            NaiveTime::parse_from_str(&summary.start, "%H:%M"),
+            NaiveTime::parse_from_str(&summary.end, "%H:%M"),
+        ) else {
+            continue;
+        };
+        let dtstart = date.and_time(start);
+        let mut dtend = date.and_time(end);
+        if dtend <= dtstart {
+            // pair crossing midnight
+            dtend += Duration::days(1);
+        }
+        let (hh, mm) = mins2readable(summary.duration_minutes);
+        let description = format!(
+            "Position: {}\nLunch: {} min\nWorked: {}h {}m",
+            label, summary.lunch_minutes, hh, mm
+        );
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:pair-{}-{}@{}", in_id, out_id, UID_DOMAIN));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART:{}", dtstart.format("%Y%m%dT%H%M%S")));
+        lines.push(format!("DTEND:{}", dtend.format("%Y%m%dT%H%M%S")));
+        lines.push(format!(
+            "SUMMARY:{}",
+            escape_text(&format!("Work ({})", label))
+        ));
+        lines.push(format!("DESCRIPTION:{}", escape_text(&description)));
+        lines.push(format!("CATEGORIES:{}", escape_text(&label)));
+        lines.push("TRANSP:OPAQUE".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+
+    for s in sessions
+        .iter()
+        .filter(|s| s.position == "H" && !days_with_events.contains(s.date.as_str()))
+    {
+        let Ok(date) = NaiveDate::parse_from_str(&s.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let (label, _) = describe_position("H");
+        lines.extend(all_day_event(
+            &format!("absence-{}@{}", s.date, UID_DOMAIN),
+            &stamp,
+            date,
+            &label,
+        ));
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&fold_line(&line));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn all_day_event(uid: &str, stamp: &str, date: NaiveDate, label: &str) -> Vec<String> {
+    let next = date.succ_opt().unwrap_or(date);
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}", stamp),
+        format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+        format!("DTEND;VALUE=DATE:{}", next.format("%Y%m%d")),
+        format!("SUMMARY:{}", escape_text(label)),
+        format!("CATEGORIES:{}", escape_text(label)),
+        "TRANSP:TRANSPARENT".to_string(),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+/// Escape a TEXT value (RFC 5545 §3.3.11).
+pub fn escape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Fold a content line at 75 octets without splitting UTF-8 characters (RFC 5545 §3.1).
+fn fold_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += len;
+    }
+    out
+}
+
+/// A `VEVENT` read from an iCalendar file by [`parse_calendar`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarEvent {
+    pub uid: Option<String>,
+    pub summary: String,
+    pub categories: Vec<String>,
+    /// First day covered
+    pub start: NaiveDate,
+    /// Last day covered (inclusive)
+    pub end: NaiveDate,
+    /// `DTSTART;VALUE=DATE` event
+    pub all_day: bool,
+    /// Has an `RRULE` (recurrences are not expanded)
+    pub recurring: bool,
+    /// `STATUS:CANCELLED`
+    pub cancelled: bool,
+}
+
+impl CalendarEvent {
+    /// Days covered by the event, in order.
+    pub fn days(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.start.iter_days().take_while(|d| *d <= self.end)
+    }
+}
+
+/// Read the `VEVENT`s of an iCalendar document. Times are reduced to their date (no
+/// time-zone conversion); all-day `DTEND` values are exclusive as per RFC 5545.
+pub fn parse_calendar(text: &str) -> Result<Vec<CalendarEvent>> {
+    let unfolded = text
+        .replace("\r\n ", "")
+        .replace("\r\n\t", "")
+        .replace("\n ", "")
+        .replace("\n\t", "");
+
+    let mut events = Vec::new();
+    let mut current: Option<RawEvent> = None;
+    for raw_line in unfolded.lines() {
+        let line = raw_line.trim_end_matches('\r');
+        let Some((name, params, value)) = split_property(line) else {
+            continue;
+        };
+        match (name.as_str(), value) {
+            ("BEGIN", v) if v.eq_ignore_ascii_case("VEVENT") => current = Some(RawEvent::default()),
+            ("END", v) if v.eq_ignore_ascii_case("VEVENT") => {
+                if let Some(raw) = current.take() {
+                    events.push(raw.finish()?);
+                }
+            }
+            _ => {
+                if let Some(raw) = current.as_mut() {
+                    raw.set(&name, &params, value);
+                }
+            }
+        }
+    }
+    Ok(events)
+}
+
+#[derive(Default)]
+struct RawEvent {
+    uid: Option<String>,
+    summary: String,
+    categories: Vec<String>,
+    start: Option<(NaiveDate, bool)>,
+    end: Option<(NaiveDate, bool, bool)>,
+    duration_days: Option<i64>,
+    recurring: bool,
+    cancelled: bool,
+}
+
+impl RawEvent {
+    fn set(&mut self, name: &str, params: &str, value: &str) {
+        let date_only = params.to_ascii_uppercase().contains("VALUE=DATE")
+            && !params.to_ascii_uppercase().contains("VALUE=DATE-TIME");
+        match name {
+            "UID" => self.uid = Some(value.to_string()),
+            "SUMMARY" => self.summary = unescape_text(value),
+            "CATEGORIES" => self.categories.extend(
+                split_unescaped(value)
+                    .into_iter()
+                    .map(|c| c.trim().to_string())
+                    .filter(|c| !c.is_empty()),
+            ),
+            "DTSTART" => {
+                self.start = parse_ical_date(value).map(|(d, _)| (d, date_only || value.len() == 8))
+            }
+            "DTEND" => {
+                self.end = parse_ical_date(value)
+                    .map(|(d, midnight)| (d, date_only || value.len() == 8, midnight))
+            }
+            "DURATION" => self.duration_days = parse_duration_days(value),
+            "RRULE" => self.recurring = true,
+            "STATUS" => self.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> Result<CalendarEvent> {
+        let (start, all_day) = self.start.ok_or_else(|| {
+            Error::invalid(format!(
+                "Calendar event without a valid DTSTART: {}",
+                if self.summary.is_empty() {
+                    self.uid.as_deref().unwrap_or("?")
+                } else {
+                    &self.summary
+                }
+            ))
+        })?;
+        let end = match (self.end, self.duration_days) {
+            // all-day DTEND is exclusive; so is a timed end at midnight of a later day
+            (Some((d, true, _)), _) | (Some((d, false, true)), _) if d > start => {
+                d.pred_opt().unwrap_or(d)
+            }
+            (Some((d, _, _)), _) if d >= start => d,
+            (None, Some(days)) if days > 1 && all_day => start + Duration::days(days - 1),
+            _ => start,
+        };
+        Ok(CalendarEvent {
+            uid: self.uid,
+            summary: self.summary,
+            categories: self.categories,
+            start,
+            end,
+            all_day,
+            recurring: self.recurring,
+            cancelled: self.cancelled,
+        })
+    }
+}
+
+/// Split `NAME;PARAMS:VALUE` (colons inside quoted parameter values are ignored).
+fn split_property(line: &str) -> Option<(String, String, &str)> {
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                let head = &line[..i];
+                let (name, params) = head.split_once(';').unwrap_or((head, ""));
+                return Some((
+                    name.to_ascii_uppercase(),
+                    params.to_string(),
+                    &line[i + 1..],
+                ));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `YYYYMMDD` or `YYYYMMDDTHHMMSS[Z]` → (date, time is midnight).
+fn parse_ical_date(value: &str) -> Option<(NaiveDate, bool)> {
+    let date = NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()?;
+    let midnight = value.get(9..15).map(|t| t == "000000").unwrap_or(true);
+    Some((date, midnight))
+}
+
+/// Whole days of a `P<n>D` / `P<n>W` duration.
+fn parse_duration_days(value: &str) -> Option<i64> {
+    let v = value.trim_start_matches('+').strip_prefix('P')?;
+    if let Some(d) = v.strip_suffix('D') {
+        return d.parse().ok();
+    }
+    v.strip_suffix('W')?.parse::<i64>().ok().map(|w| w * 7)
+}
+
+fn unescape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Split a comma separated TEXT list, honouring `\,` escapes.
+fn split_unescaped(s: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in s.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ',' {
+            parts.push(unescape_text(&current));
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    parts.push(unescape_text(&current));
+    parts
+}
+
+/// Filters of `rtimelogger import --format ics`.
+#[derive(Debug, Clone, Default)]
+pub struct ImportFilter {
+    /// Only days between these dates (inclusive)
+    pub range: Option<(String, String)>,
+    /// Keep events carrying one of these categories (case-insensitive)
+    pub categories: Vec<String>,
+    /// Keep events whose summary matches
+    pub summary: Option<Regex>,
+    /// Also import Saturdays and Sundays
+    pub include_weekends: bool,
+}
+
+impl ImportFilter {
+    /// All-day events are taken unless a filter rejects them; timed events only when they
+    /// are explicitly selected by a category or summary filter.
+    fn selects(&self, ev: &CalendarEvent) -> bool {
+        let tagged = !self.categories.is_empty() || self.summary.is_some();
+        let category_ok = self.categories.is_empty()
+            || ev
+                .categories
+                .iter()
+                .any(|c| self.categories.iter().any(|f| f.eq_ignore_ascii_case(c)));
+        let summary_ok = self
+            .summary
+            .as_ref()
+            .is_none_or(|re| re.is_match(&ev.summary));
+        category_ok && summary_ok && (ev.all_day || tagged)
+    }
+}
+
+/// What importing a day does.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    /// A record with the requested position (or the tracked pair) will be created
+    Create,
+    /// The day already has that position (or the pair is already recorded)
+    Exists,
+    /// The day has punches: left untouched
+    HasPunches,
+    /// The day is in a locked period (see [`crate::lock`])
+    Locked,
+    /// Already covered by a previous calendar event
+    Duplicate,
+}
+
+/// One day of an import plan.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportDay {
+    pub date: String,
+    pub summary: String,
+    pub position: String,
+    pub action: ImportAction,
+}
+
+/// Days that importing `events` with `position` would touch, and what happens to each; locked
+/// days are only created when `force` is set.
+pub fn plan_import(
+    conn: &Connection,
+    events: &[CalendarEvent],
+    filter: &ImportFilter,
+    position: &str,
+    force: bool,
+) -> Result<Vec<ImportDay>> {
+    let mut seen: HashSet<NaiveDate> = HashSet::new();
+    let mut days = Vec::new();
+    for ev in events {
+        if ev.cancelled || !filter.selects(ev) {
+            continue;
+        }
+        if ev.recurring {
+            eprintln!(
+                "⚠️  Skipping recurring calendar event '{}' (recurrences are not expanded)",
+                ev.summary
+            );
+            continue;
+        }
+        for day in ev.days() {
+            let date = utils::date2iso(&day);
+            if let Some((from, to)) = &filter.range
+                && (date.as_str() < from.as_str() || date.as_str() > to.as_str())
+            {
+                continue;
+            }
+            if !filter.include_weekends && matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+                continue;
+            }
+            let action = if !seen.insert(day) {
+                ImportAction::Duplicate
+            } else if db::count_events_by_date(conn, &date)? > 0 {
+                ImportAction::HasPunches
+            } else if db::list_sessions_by_date(conn, &date)?
+                .iter()
+                .any(|s| s.position == position)
+            {
+                ImportAction::Exists
+            } else if !force && lock::lock_for_date(conn, &date)?.is_some() {
+                ImportAction::Locked
+            } else {
+                ImportAction::Create
+            };
+            days.push(ImportDay {
+                date,
+                summary: ev.summary.clone(),
+                position: position.to_string(),
+                action,
+            });
+        }
+    }
+    days.sort_by(|a, b| a.date.cmp(&b.date));
+    Ok(days)
+}
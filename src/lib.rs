@@ -10,11 +10,29 @@
 #![allow(rustdoc::broken_intra_doc_links)]
 #![doc = include_str!("../README.md")]
 
+pub mod audit;
 pub mod cli;
+pub mod clock;
 pub mod config;
 pub mod db;
+pub mod error;
 pub mod events;
 pub mod export;
+pub mod html_report;
+pub mod ics;
+pub mod lock;
 pub mod logic;
+pub mod ods;
+pub mod output;
 pub mod pdf_manager;
+pub mod style;
+pub mod template;
+pub mod timelog;
+pub mod timesheet;
+pub mod tracker;
+pub mod tui;
+pub mod undo;
 pub mod utils;
+
+pub use error::{Error, Result};
+pub use timelog::Timelog;
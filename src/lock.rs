@@ -0,0 +1,345 @@
+//! Locked periods (`rtimelogger period lock|unlock|list|verify`).
+//!
+//! Once a month has been submitted (e.g. to payroll) it can be locked: its range is stored in
+//! `locked_periods` together with a SHA-256 checksum of its events. Every function changing
+//! events (`db` setters, [`crate::Timelog`] mutations, undo/redo) refuses dates inside a
+//! locked range with [`ValidationError::Locked`]. A forced operation (the CLI's `--force`,
+//! [`crate::Timelog::set_force`]) passes `force` to [`guard`], which clears its dates for the
+//! current transaction; once the change succeeded, [`Overrides::record`] writes a
+//! `lock_override` row to the `log` table in the same transaction.
+
+use crate::clock::Clock;
+use crate::db;
+use crate::error::{Error, Result, ValidationError};
+use crate::utils;
+use chrono::{Datelike, NaiveDate};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Operation written to `log` when a locked period is changed with `--force`.
+pub const OVERRIDE_OPERATION: &str = "lock_override";
+
+/// A row of `locked_periods`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LockedPeriod {
+    /// `YYYY-MM`
+    pub period: String,
+    pub start: String,
+    pub end: String,
+    /// SHA-256 (hex) of the period's events when it was locked
+    pub checksum: String,
+    /// Number of events covered by the checksum
+    pub events: usize,
+    pub locked_at: String,
+    pub locked_by: Option<String>,
+}
+
+/// Result of [`verify`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Verification {
+    pub period: String,
+    /// Checksum stored at lock time
+    pub expected: String,
+    /// Checksum of the events as they are now
+    pub actual: String,
+    pub expected_events: usize,
+    pub actual_events: usize,
+}
+
+impl Verification {
+    pub fn is_valid(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+/// First and last day of a `YYYY-MM` period.
+pub fn month_range(period: &str) -> Result<(String, String)> {
+    let invalid =
+        || ValidationError::Other(format!("Invalid period: {} (expected YYYY-MM)", period));
+    let first = NaiveDate::parse_from_str(&format!("{}-01", period.trim()), "%Y-%m-%d")
+        .map_err(|_| invalid())?;
+    if period.trim().len() != 7 {
+        return Err(invalid().into());
+    }
+    let next = if first.month() == 12 {
+        NaiveDate::from_ymd_opt(first.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(first.year(), first.month() + 1, 1)
+    }
+    .ok_or_else(invalid)?;
+    let last = next.pred_opt().ok_or_else(invalid)?;
+    Ok((utils::date2iso(&first), utils::date2iso(&last)))
+}
+
+/// SHA-256 (hex) of the events between `start` and `end` (inclusive) and their count.
+///
+/// Only the user-visible fields (date, time, kind, position, lunch) are hashed, in
+/// chronological order, so ids and bookkeeping columns do not affect the result.
+pub fn checksum(conn: &Connection, start: &str, end: &str) -> Result<(String, usize)> {
+    let mut stmt = conn.prepare(
+        "SELECT date, time, kind, position, lunch_break FROM events \
+         WHERE date BETWEEN ?1 AND ?2 ORDER BY date ASC, minutes ASC, kind ASC, id ASC",
+    )?;
+    let rows = stmt
+        .query_map(params![start, end], |row| {
+            Ok(format!(
+                "{}|{}|{}|{}|{}\n",
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, i32>(4)?
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut hasher = Sha256::new();
+    for line in &rows {
+        hasher.update(line.as_bytes());
+    }
+    let hex = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    Ok((hex, rows.len()))
+}
+
+/// Lock a `YYYY-MM` period, storing the checksum of its current events.
+pub fn lock_period(conn: &Connection, clock: &dyn Clock, period: &str) -> Result<LockedPeriod> {
+    let period = period.trim();
+    let (start, end) = month_range(period)?;
+    if get_lock(conn, period)?.is_some() {
+        return Err(Error::invalid(format!(
+            "Period {} is already locked",
+            period
+        )));
+    }
+    let (sum, events) = checksum(conn, &start, &end)?;
+    let locked = LockedPeriod {
+        period: period.to_string(),
+        start,
+        end,
+        checksum: sum,
+        events,
+        locked_at: clock.now_utc().to_rfc3339(),
+        locked_by: utils::current_user(),
+    };
+    conn.execute(
+        "INSERT INTO locked_periods (period, start_date, end_date, checksum, events, locked_at, locked_by) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            locked.period,
+            locked.start,
+            locked.end,
+            locked.checksum,
+            locked.events as i64,
+            locked.locked_at,
+            locked.locked_by
+        ],
+    )?;
+    db::ttlog_with_clock(
+        conn,
+        clock,
+        "lock",
+        period,
+        &format!(
+            "Locked period {} ({} events, sha256={})",
+            period, events, locked.checksum
+        ),
+    )?;
+    Ok(locked)
+}
+
+/// Remove the lock of a period. Returns the removed lock.
+pub fn unlock_period(conn: &Connection, clock: &dyn Clock, period: &str) -> Result<LockedPeriod> {
+    let period = period.trim();
+    month_range(period)?;
+    let locked = get_lock(conn, period)?
+        .ok_or_else(|| Error::invalid(format!("Period {} is not locked", period)))?;
+    conn.execute("DELETE FROM locked_periods WHERE period = ?1", [period])?;
+    db::ttlog_with_clock(
+        conn,
+        clock,
+        "unlock",
+        period,
+        &format!(
+            "Unlocked period {} (was locked at {})",
+            period, locked.locked_at
+        ),
+    )?;
+    Ok(locked)
+}
+
+/// All locked periods, oldest first.
+pub fn list_locks(conn: &Connection) -> Result<Vec<LockedPeriod>> {
+    let mut stmt = conn.prepare(&format!("{} ORDER BY start_date ASC", SELECT_LOCK))?;
+    let rows = stmt
+        .query_map([], row_to_lock)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+/// The lock of a `YYYY-MM` period, if any.
+pub fn get_lock(conn: &Connection, period: &str) -> Result<Option<LockedPeriod>> {
+    let mut stmt = conn.prepare(&format!("{} WHERE period = ?1", SELECT_LOCK))?;
+    Ok(stmt.query_row([period], row_to_lock).optional()?)
+}
+
+/// The lock covering `date`, if any.
+pub fn lock_for_date(conn: &Connection, date: &str) -> Result<Option<LockedPeriod>> {
+    if !table_exists(conn)? {
+        // Connections whose schema predates the locked_periods migration
+        return Ok(None);
+    }
+    let mut stmt = conn.prepare_cached(&format!(
+        "{} WHERE ?1 BETWEEN start_date AND end_date LIMIT 1",
+        SELECT_LOCK
+    ))?;
+    Ok(stmt.query_row([date], row_to_lock).optional()?)
+}
+
+/// Compare the stored checksum of a locked period with its current events.
+pub fn verify(conn: &Connection, period: &str) -> Result<Verification> {
+    let period = period.trim();
+    month_range(period)?;
+    let locked = get_lock(conn, period)?
+        .ok_or_else(|| Error::invalid(format!("Period {} is not locked", period)))?;
+    let (actual, actual_events) = checksum(conn, &locked.start, &locked.end)?;
+    Ok(Verification {
+        period: locked.period,
+        expected: locked.checksum,
+        actual,
+        expected_events: locked.events,
+        actual_events,
+    })
+}
+
+/// Fail with [`ValidationError::Locked`] if `date` is locked and was not cleared by a forced
+/// [`guard`] in the current transaction. Used by the low-level `db` setters.
+pub fn ensure_unlocked(conn: &Connection, date: &str) -> Result<()> {
+    match lock_for_date(conn, date)? {
+        Some(lock) if !is_cleared(conn, date)? => Err(ValidationError::Locked {
+            date: date.to_string(),
+            period: lock.period,
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Same as [`ensure_unlocked`] for the date of an existing event (unknown ids pass).
+pub fn ensure_event_unlocked(conn: &Connection, event_id: i32) -> Result<()> {
+    let date: Option<String> = conn
+        .query_row("SELECT date FROM events WHERE id = ?1", [event_id], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    match date {
+        Some(d) => ensure_unlocked(conn, &d),
+        None => Ok(()),
+    }
+}
+
+/// Locked dates a forced operation is changing, returned by [`guard`].
+#[derive(Debug, Default)]
+#[must_use = "record the overrides once the change succeeded"]
+pub struct Overrides {
+    /// `(date, period)` pairs
+    locks: Vec<(String, String)>,
+}
+
+impl Overrides {
+    pub fn is_empty(&self) -> bool {
+        self.locks.is_empty()
+    }
+
+    /// Log one `lock_override` row per overridden date and withdraw the clearance given by
+    /// [`guard`]. Call it after the change succeeded, in the same transaction.
+    pub fn record(self, conn: &Connection, clock: &dyn Clock, operation: &str) -> Result<()> {
+        for (date, period) in &self.locks {
+            db::ttlog_with_clock(
+                conn,
+                clock,
+                OVERRIDE_OPERATION,
+                period,
+                &format!(
+                    "Forced {} on {} in locked period {}{}",
+                    operation,
+                    date,
+                    period,
+                    utils::current_user()
+                        .map(|u| format!(" by {}", u))
+                        .unwrap_or_default()
+                ),
+            )?;
+            conn.execute("DELETE FROM temp.lock_overrides WHERE date = ?1", [date])?;
+        }
+        Ok(())
+    }
+}
+
+/// Check `dates` before an operation changes them: refuse locked dates unless `force` is set.
+/// Forced dates are cleared for the `db` setters until [`Overrides::record`] is called; run both
+/// in one transaction so a failed change rolls the clearance back.
+pub fn guard(conn: &Connection, dates: &[&str], force: bool) -> Result<Overrides> {
+    let mut overrides = Overrides::default();
+    for date in dates {
+        let Some(lock) = lock_for_date(conn, date)? else {
+            continue;
+        };
+        if !force {
+            return Err(ValidationError::Locked {
+                date: date.to_string(),
+                period: lock.period,
+            }
+            .into());
+        }
+        conn.execute_batch(
+            "CREATE TEMP TABLE IF NOT EXISTS lock_overrides (date TEXT PRIMARY KEY)",
+        )?;
+        conn.execute(
+            "INSERT OR IGNORE INTO temp.lock_overrides (date) VALUES (?1)",
+            [date],
+        )?;
+        overrides.locks.push((date.to_string(), lock.period));
+    }
+    Ok(overrides)
+}
+
+/// True when a forced [`guard`] cleared `date` in the current transaction.
+fn is_cleared(conn: &Connection, date: &str) -> Result<bool> {
+    let exists = conn
+        .prepare_cached(
+            "SELECT 1 FROM sqlite_temp_master WHERE type = 'table' AND name = 'lock_overrides'",
+        )?
+        .exists([])?;
+    if !exists {
+        return Ok(false);
+    }
+    Ok(conn
+        .prepare_cached("SELECT 1 FROM temp.lock_overrides WHERE date = ?1")?
+        .exists([date])?)
+}
+
+const SELECT_LOCK: &str = "SELECT period, start_date, end_date, checksum, events, locked_at, locked_by FROM locked_periods";
+
+fn row_to_lock(row: &rusqlite::Row) -> rusqlite::Result<LockedPeriod> {
+    Ok(LockedPeriod {
+        period: row.get(0)?,
+        start: row.get(1)?,
+        end: row.get(2)?,
+        checksum: row.get(3)?,
+        events: row.get::<_, i64>(4)? as usize,
+        locked_at: row.get(5)?,
+        locked_by: row.get(6)?,
+    })
+}
+
+fn table_exists(conn: &Connection) -> Result<bool> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'locked_periods'",
+    )?;
+    Ok(stmt.exists([])?)
+}
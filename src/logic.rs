@@ -1,5 +1,7 @@
 use crate::config::Config;
+use crate::db;
 use chrono::{Duration, NaiveTime};
+use serde::Serialize;
 
 pub fn month_name(month: &str) -> &'static str {
     match month {
@@ -103,3 +105,167 @@ pub fn effective_lunch_minutes(
         _ => lunch.clamp(0, config.max_duration_lunch_break),
     }
 }
+
+/// Support struct to enrich JSON output and compute pair/unmatched
+#[derive(Serialize, Clone, Debug)]
+pub struct EventWithPair {
+    #[serde(flatten)]
+    pub event: db::Event,
+    pub pair: usize,
+    pub unmatched: bool,
+}
+
+/// Compute pair ids (per-date sequence) and unmatched flag for a slice of events.
+/// Rules:
+///  - Every 'in' event opens a new pair with an incremented pair id (per date) and unmatched=true
+///  - The first subsequent 'out' closes the earliest open pair (FIFO) and uses the same pair id,
+///    setting unmatched=false for both the 'in' and the 'out'
+///  - An 'out' without a preceding 'in' creates a new pair id with unmatched=true
+pub fn compute_event_pairs(events: &[db::Event]) -> Vec<EventWithPair> {
+    use std::collections::VecDeque;
+    let mut result: Vec<EventWithPair> = Vec::with_capacity(events.len());
+    let mut current_date = String::new();
+    let mut open_in_queue: VecDeque<usize> = VecDeque::new();
+    let mut pair_counter: usize = 0;
+    for ev in events {
+        if ev.date != current_date {
+            // reset for a new date
+            current_date = ev.date.clone();
+            open_in_queue.clear();
+            pair_counter = 0;
+        }
+        match ev.kind.as_str() {
+            "in" => {
+                pair_counter += 1;
+                result.push(EventWithPair {
+                    event: ev.clone(),
+                    pair: pair_counter,
+                    unmatched: true,
+                });
+                open_in_queue.push_back(result.len() - 1);
+            }
+            "out" => {
+                if let Some(in_idx) = open_in_queue.pop_front() {
+                    let pair_id = result[in_idx].pair;
+                    result[in_idx].unmatched = false; // closed match
+                    result.push(EventWithPair {
+                        event: ev.clone(),
+                        pair: pair_id,
+                        unmatched: false,
+                    });
+                } else {
+                    pair_counter += 1; // orphan out
+                    result.push(EventWithPair {
+                        event: ev.clone(),
+                        pair: pair_counter,
+                        unmatched: true,
+                    });
+                }
+            }
+            _ => {
+                pair_counter += 1;
+                result.push(EventWithPair {
+                    event: ev.clone(),
+                    pair: pair_counter,
+                    unmatched: true,
+                });
+            }
+        }
+    }
+    result
+}
+
+/// One in/out pair of a day, aggregated (start/end are "-" when missing).
+#[derive(Serialize, Clone, Debug)]
+pub struct PairSummary {
+    pub date: String,
+    pub pair: usize,
+    pub position: String,
+    pub start: String,
+    pub end: String,
+    pub lunch_minutes: i32,
+    pub duration_minutes: i32,
+    pub unmatched: bool,
+}
+
+/// Aggregate paired events (see [`compute_event_pairs`]) into one row per (date, pair).
+pub fn compute_event_summaries(enriched: &[EventWithPair]) -> Vec<PairSummary> {
+    use std::collections::BTreeMap;
+    #[derive(Default)]
+    struct Accum {
+        date: String,
+        pair: usize,
+        position: String,
+        start: Option<(String, i32)>,
+        end: Option<(String, i32)>,
+        lunch: i32,
+        unmatched_in: bool,
+        unmatched_out: bool,
+    }
+    let mut map: BTreeMap<(String, usize), Accum> = BTreeMap::new();
+    for e in enriched {
+        let key = (e.event.date.clone(), e.pair);
+        let acc = map.entry(key.clone()).or_insert_with(|| Accum {
+            date: key.0.clone(),
+            pair: key.1,
+            position: String::new(),
+            start: None,
+            end: None,
+            lunch: 0,
+            unmatched_in: false,
+            unmatched_out: false,
+        });
+        if e.event.kind == "in" {
+            if acc.start.is_none() {
+                acc.start = Some((e.event.time.clone(), e.event.minutes));
+            }
+            if acc.position.is_empty() {
+                acc.position = e.event.position.clone();
+            }
+            if e.unmatched {
+                acc.unmatched_in = true;
+            }
+        } else if e.event.kind == "out" {
+            if acc.end.is_none() {
+                acc.end = Some((e.event.time.clone(), e.event.minutes));
+            }
+            if acc.position.is_empty() {
+                acc.position = e.event.position.clone();
+            }
+            if e.event.lunch_break > 0 {
+                acc.lunch = e.event.lunch_break;
+            }
+            if e.unmatched {
+                acc.unmatched_out = true;
+            }
+        }
+    }
+    let mut rows: Vec<PairSummary> = Vec::new();
+    for (_, acc) in map.into_iter() {
+        let unmatched = (acc.start.is_some() && acc.end.is_none())
+            || (acc.start.is_none() && acc.end.is_some());
+        // Compute duration
+        let mut duration_minutes = 0;
+        if let (Some((_, st)), Some((_, et))) = (acc.start.as_ref(), acc.end.as_ref()) {
+            let mut diff = et - st;
+            if acc.lunch > 0 {
+                diff -= acc.lunch;
+            }
+            if diff < 0 {
+                diff = 0;
+            }
+            duration_minutes = diff;
+        }
+        rows.push(PairSummary {
+            date: acc.date,
+            pair: acc.pair,
+            position: acc.position,
+            start: acc.start.map(|(t, _)| t).unwrap_or_else(|| "-".to_string()),
+            end: acc.end.map(|(t, _)| t).unwrap_or_else(|| "-".to_string()),
+            lunch_minutes: acc.lunch,
+            duration_minutes,
+            unmatched,
+        });
+    }
+    rows
+}
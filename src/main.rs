@@ -1,14 +1,30 @@
 use clap::Parser;
+use rtimelogger::clock::FixedClock;
 use rtimelogger::config::Config;
-use rtimelogger::{db, export};
+use rtimelogger::utils::Confirm;
+use rtimelogger::{Error, Result, Timelog, export};
 use rusqlite::Connection;
+use std::process::ExitCode;
 
 mod commands;
 use rtimelogger::cli::{Cli, Commands};
 
-fn main() -> rusqlite::Result<()> {
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<()> {
+    rtimelogger::utils::set_quiet(
+        cli.quiet || !cli.output.is_table() || cli.command.writes_data_to_stdout(),
+    );
     // Ensure filesystem migration ran early (before any DB open). This moves old "%APPDATA%/rtimelog" or
     // "$HOME/.rtimelog" to the new location and renames config/db references if needed.
     if let Err(e) = rtimelogger::config::migrate::run_fs_migration() {
@@ -17,11 +33,10 @@ fn main() -> rusqlite::Result<()> {
 
     // Ensure config dir exists so Connection::open can create the DB file inside it.
     if let Err(e) = std::fs::create_dir_all(Config::config_dir()) {
-        eprintln!("❌ Failed to create config directory: {}", e);
-        return Err(rusqlite::Error::SqliteFailure(
-            rusqlite::ffi::Error::new(1),
-            Some(format!("Failed to create config dir: {}", e)),
-        ));
+        return Err(Error::Io(std::io::Error::new(
+            e.kind(),
+            format!("Failed to create config directory: {}", e),
+        )));
     }
 
     // Determine DB path without loading the full config (Config::load may read files under
@@ -46,7 +61,7 @@ fn main() -> rusqlite::Result<()> {
             .to_string()
     } else {
         // Production: load the configuration and use the database path from it
-        let cfg = Config::load();
+        let cfg = Config::load()?;
         let path = cfg.database.clone();
         maybe_loaded_config = Some(cfg);
         path
@@ -65,24 +80,31 @@ fn main() -> rusqlite::Result<()> {
             max_duration_lunch_break: 90,
             separator_char: "-".to_string(),
             show_weekday: "None".to_string(),
+            colors: Default::default(),
+            employee: Default::default(),
         }
     } else {
         // For production, prefer to reuse an already-loaded Config when available
-        maybe_loaded_config.unwrap_or_else(Config::load)
+        match maybe_loaded_config {
+            Some(cfg) => cfg,
+            None => Config::load()?,
+        }
     };
 
-    println!();
+    rtimelogger::style::init(cli.color, &config.colors);
+
+    rtimelogger::progress!();
 
     // Handle `init` separately because it may need to create config/db files first
     if let Commands::Init = &cli.command {
-        return commands::handle_init(&cli, &db_path);
+        return commands::handle_init(cli, &db_path);
     }
 
     // For other commands, open a single shared connection, set useful PRAGMA and ensure DB is initialized (creates
     // base tables and runs pending migrations).
     // Try to open the DB; if opening fails (e.g. CannotOpen), attempt remediation once: run FS migration,
     // create parent directories and try to touch the DB file, then retry.
-    let mut conn = match Connection::open(&db_path) {
+    let conn = match Connection::open(&db_path) {
         Ok(c) => c,
         Err(e) => {
             eprintln!(
@@ -154,7 +176,7 @@ fn main() -> rusqlite::Result<()> {
                             }
                         }
                     }
-                    return Err(e_final);
+                    return Err(e_final.into());
                 }
             }
         }
@@ -162,11 +184,18 @@ fn main() -> rusqlite::Result<()> {
 
     conn.pragma_update(None, "journal_mode", "WAL")?;
     conn.pragma_update(None, "foreign_keys", "ON")?;
-    db::init_db(&conn)?;
+    // Wrapping the connection runs pending migrations
+    let mut tl = Timelog::new(conn, config)?;
+    if let Some(now) = &cli.now_override {
+        tl.set_clock(FixedClock::parse(now)?);
+    }
+    // `--force` lets add/del/import/undo/redo change locked periods (each override is logged)
+    tl.set_force(cli.command.forces_locked());
+    let confirm = Confirm::from_flags(cli.yes, cli.no_input);
 
     match &cli.command {
-        Commands::Add { .. } => commands::handle_add(&cli.command, &mut conn, &config)?,
-        Commands::Del { .. } => commands::handle_del(&cli.command, &mut conn)?,
+        Commands::Add { .. } => commands::handle_add(&cli.command, &mut tl)?,
+        Commands::Del { .. } => commands::handle_del(&cli.command, &mut tl, confirm)?,
         Commands::List {
             period,
             pos,
@@ -177,31 +206,53 @@ fn main() -> rusqlite::Result<()> {
             summary,
         } => {
             let args = commands::HandleListArgs {
-                period: period.clone(),
+                period: period
+                    .as_deref()
+                    .map(|p| commands::resolve_period(p, tl.clock()))
+                    .transpose()?,
                 pos: pos.clone(),
                 now: *now,
                 details: *details,
                 events: *events,
                 pairs: *pairs,
                 summary: *summary,
+                output: cli.output,
             };
-            commands::handle_list(&args, &conn, &config)?
+            commands::handle_list(&args, tl.connection(), tl.config(), tl.clock())?
         }
         Commands::Config { .. } => commands::handle_config(&cli.command)?,
-        Commands::Log { .. } => commands::handle_log(&cli.command, &conn)?,
+        Commands::Log { .. } => commands::handle_log(
+            &cli.command,
+            tl.connection(),
+            tl.clock(),
+            cli.output,
+            confirm,
+        )?,
         Commands::Init => {
             // Already handled, but included for exhaustiveness
         }
         Commands::Backup { file, compress } => {
-            if let Err(e) = commands::handle_backup(&config, file, compress) {
-                eprintln!("❌ Backup failed: {}", e);
-            }
-        }
-        Commands::Export { .. } => {
-            if let Err(e) = export::handle_export(&cli.command, &conn) {
-                eprintln!("❌ Export failed: {}", e);
-            };
+            commands::handle_backup(tl.config(), file, compress)?
         }
+        Commands::Export { .. } => export::handle_export(
+            &cli.command,
+            tl.connection(),
+            tl.config(),
+            tl.clock(),
+            confirm,
+        )?,
+        Commands::Timesheet { .. } => rtimelogger::timesheet::handle_timesheet(
+            &cli.command,
+            tl.connection(),
+            tl.config(),
+            tl.clock(),
+            confirm,
+        )?,
+        Commands::Import { .. } => commands::handle_import(&cli.command, &mut tl, cli.output)?,
+        Commands::Tui => rtimelogger::tui::run(&mut tl)?,
+        Commands::Undo { steps, .. } => commands::handle_undo(&mut tl, *steps, false)?,
+        Commands::Redo { steps, .. } => commands::handle_undo(&mut tl, *steps, true)?,
+        Commands::Period { action } => commands::handle_period(action, &tl, cli.output)?,
     }
 
     Ok(())
@@ -0,0 +1,197 @@
+//! OpenDocument spreadsheet writer (`export --format ods`).
+//!
+//! The package is written directly with the `zip` crate: the uncompressed `mimetype` entry first
+//! (as ODF requires), then the manifest, `meta.xml` and `content.xml` holding a single table.
+//! Cells are typed from their column and content: dates, times, durations (`work_duration`) and
+//! integers get their native ODF value types, everything else is a string.
+
+use crate::error::Result;
+use crate::utils::parse_work_duration_to_minutes;
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+pub const MIME_TYPE: &str = "application/vnd.oasis.opendocument.spreadsheet";
+
+const NS: &str = concat!(
+    r#"xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" "#,
+    r#"xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" "#,
+    r#"xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" "#,
+    r#"xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" "#,
+    r#"xmlns:number="urn:oasis:names:tc:opendocument:xmlns:datastyle:1.0" "#,
+    r#"xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" "#,
+    r#"xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0" "#,
+    r#"office:version="1.3""#
+);
+
+/// Data styles and cell styles shared by every table.
+const STYLES: &str = r##"<number:date-style style:name="N_date"><number:year number:style="long"/><number:text>-</number:text><number:month number:style="long"/><number:text>-</number:text><number:day number:style="long"/></number:date-style><number:time-style style:name="N_time"><number:hours number:style="long"/><number:text>:</number:text><number:minutes number:style="long"/></number:time-style><number:time-style style:name="N_duration" number:truncate-on-overflow="false"><number:hours/><number:text>:</number:text><number:minutes number:style="long"/></number:time-style><style:style style:name="ce_header" style:family="table-cell"><style:table-cell-properties fo:background-color="#2f75b5" fo:border="0.5pt solid #000000"/><style:text-properties fo:font-weight="bold" fo:color="#ffffff"/></style:style><style:style style:name="ce_date" style:family="table-cell" style:data-style-name="N_date"/><style:style style:name="ce_time" style:family="table-cell" style:data-style-name="N_time"/><style:style style:name="ce_duration" style:family="table-cell" style:data-style-name="N_duration"/>"##;
+
+/// A typed spreadsheet cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OdsCell {
+    Empty,
+    Text(String),
+    Float(f64),
+    Date(NaiveDate),
+    /// Time of day, in minutes since midnight
+    Time(u32),
+    /// Duration in minutes (shown as `[HH]:MM`)
+    Duration(i64),
+}
+
+impl OdsCell {
+    /// Type a value of the column `header`.
+    pub fn parse(header: &str, value: &str) -> Self {
+        let value = value.trim();
+        if value.is_empty() {
+            return OdsCell::Empty;
+        }
+        if header == "work_duration" {
+            return OdsCell::Duration(parse_work_duration_to_minutes(value));
+        }
+        if let Ok(d) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            return OdsCell::Date(d);
+        }
+        if let Ok(t) = NaiveTime::parse_from_str(value, "%H:%M") {
+            return OdsCell::Time(t.hour() * 60 + t.minute());
+        }
+        if let Ok(n) = value.parse::<i64>() {
+            return OdsCell::Float(n as f64);
+        }
+        OdsCell::Text(value.to_string())
+    }
+
+    fn to_xml(&self, display: &str) -> String {
+        let text = format!("<text:p>{}</text:p>", xml_escape(display));
+        match self {
+            OdsCell::Empty => "<table:table-cell/>".to_string(),
+            OdsCell::Text(_) => {
+                format!(r#"<table:table-cell office:value-type="string">{text}</table:table-cell>"#)
+            }
+            OdsCell::Float(n) => format!(
+                r#"<table:table-cell office:value-type="float" office:value="{n}">{text}</table:table-cell>"#
+            ),
+            OdsCell::Date(d) => format!(
+                r#"<table:table-cell table:style-name="ce_date" office:value-type="date" office:date-value="{}">{text}</table:table-cell>"#,
+                d.format("%Y-%m-%d")
+            ),
+            OdsCell::Time(minutes) => format!(
+                r#"<table:table-cell table:style-name="ce_time" office:value-type="time" office:time-value="{}">{text}</table:table-cell>"#,
+                iso_duration(*minutes as i64)
+            ),
+            OdsCell::Duration(minutes) => format!(
+                r#"<table:table-cell table:style-name="ce_duration" office:value-type="time" office:time-value="{}">{text}</table:table-cell>"#,
+                iso_duration(*minutes)
+            ),
+        }
+    }
+}
+
+/// Write `headers` and `rows` as the single table `sheet` of an `.ods` file.
+pub fn write_ods(path: &Path, sheet: &str, headers: &[&str], rows: &[Vec<String>]) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(MIME_TYPE.as_bytes())?;
+    zip.start_file("META-INF/manifest.xml", deflated)?;
+    zip.write_all(manifest_xml().as_bytes())?;
+    zip.start_file("meta.xml", deflated)?;
+    zip.write_all(meta_xml().as_bytes())?;
+    zip.start_file("content.xml", deflated)?;
+    zip.write_all(content_xml(sheet, headers, rows).as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
+
+/// `content.xml` of a spreadsheet with one table.
+pub fn content_xml(sheet: &str, headers: &[&str], rows: &[Vec<String>]) -> String {
+    // Column widths from the longest value (about 0.22 cm per character)
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(&format!("<office:document-content {NS}>"));
+    xml.push_str("<office:automatic-styles>");
+    xml.push_str(STYLES);
+    for (i, w) in widths.iter().enumerate() {
+        xml.push_str(&format!(
+            r#"<style:style style:name="co{i}" style:family="table-column"><style:table-column-properties style:column-width="{:.2}cm"/></style:style>"#,
+            *w as f64 * 0.22 + 0.4
+        ));
+    }
+    xml.push_str("</office:automatic-styles>");
+
+    xml.push_str("<office:body><office:spreadsheet>");
+    xml.push_str(&format!(
+        r#"<table:table table:name="{}">"#,
+        xml_escape(sheet)
+    ));
+    for i in 0..widths.len() {
+        xml.push_str(&format!(
+            r#"<table:table-column table:style-name="co{i}"/>"#
+        ));
+    }
+    xml.push_str("<table:table-header-rows><table:table-row>");
+    for h in headers {
+        xml.push_str(&format!(
+            r#"<table:table-cell table:style-name="ce_header" office:value-type="string"><text:p>{}</text:p></table:table-cell>"#,
+            xml_escape(h)
+        ));
+    }
+    xml.push_str("</table:table-row></table:table-header-rows>");
+    for row in rows {
+        xml.push_str("<table:table-row>");
+        for (header, value) in headers.iter().zip(row) {
+            xml.push_str(&OdsCell::parse(header, value).to_xml(value));
+        }
+        xml.push_str("</table:table-row>");
+    }
+    xml.push_str("</table:table></office:spreadsheet></office:body></office:document-content>");
+    xml
+}
+
+fn manifest_xml() -> String {
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            r#"<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.3">"#,
+            r#"<manifest:file-entry manifest:full-path="/" manifest:version="1.3" manifest:media-type="{}"/>"#,
+            r#"<manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>"#,
+            r#"<manifest:file-entry manifest:full-path="meta.xml" manifest:media-type="text/xml"/>"#,
+            r#"</manifest:manifest>"#
+        ),
+        MIME_TYPE
+    )
+}
+
+fn meta_xml() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><office:document-meta {NS}><office:meta><meta:generator>rTimelogger/{}</meta:generator></office:meta></office:document-meta>"#,
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// ISO 8601 duration (`PT07H30M00S`) used by ODF time values.
+fn iso_duration(minutes: i64) -> String {
+    let sign = if minutes < 0 { "-" } else { "" };
+    let minutes = minutes.abs();
+    format!("{}PT{:02}H{:02}M00S", sign, minutes / 60, minutes % 60)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,137 @@
+//! Machine-readable output for the list, status and log views (global `--output` flag).
+//!
+//! `json` writes one JSON object per line (JSON lines), `csv` writes a header row followed by one
+//! row per record (nothing at all when there are no records). Records are flat structs whose field
+//! names are the documented, stable schema; optional values are `null` in JSON and empty cells in CSV.
+
+use crate::error::Result;
+use crate::logic::EventWithPair;
+use crate::timelog::SessionReport;
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// Output format selected with `--output`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable (colored) tables
+    #[default]
+    Table,
+    /// One JSON object per line
+    Json,
+    /// CSV with a header row
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn is_table(self) -> bool {
+        self == OutputFormat::Table
+    }
+}
+
+/// A legacy work session (`list`, `list --now`).
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionRecord {
+    pub id: i32,
+    pub date: String,
+    pub position: String,
+    pub start: Option<String>,
+    pub lunch: i32,
+    pub end: Option<String>,
+    pub expected_exit: Option<String>,
+    pub worked_minutes: Option<i64>,
+    pub surplus_minutes: Option<i64>,
+}
+
+impl From<&SessionReport> for SessionRecord {
+    fn from(r: &SessionReport) -> Self {
+        let non_empty = |s: &str| (!s.trim().is_empty()).then(|| s.to_string());
+        Self {
+            id: r.session.id,
+            date: r.session.date.clone(),
+            position: r.session.position.clone(),
+            start: non_empty(&r.session.start),
+            lunch: r.effective_lunch,
+            end: non_empty(&r.session.end),
+            expected_exit: r.expected_exit.clone(),
+            worked_minutes: r.worked_minutes,
+            surplus_minutes: r.surplus_minutes,
+        }
+    }
+}
+
+/// A punch event with its computed pair (`list --events`, `list --now --details`).
+#[derive(Debug, Clone, Serialize)]
+pub struct EventRecord {
+    pub id: i32,
+    pub date: String,
+    pub time: String,
+    pub kind: String,
+    pub position: String,
+    pub lunch_break: i32,
+    pub source: String,
+    pub pair: usize,
+    pub unmatched: bool,
+}
+
+impl From<&EventWithPair> for EventRecord {
+    fn from(e: &EventWithPair) -> Self {
+        Self {
+            id: e.event.id,
+            date: e.event.date.clone(),
+            time: e.event.time.clone(),
+            kind: e.event.kind.clone(),
+            position: e.event.position.clone(),
+            lunch_break: e.event.lunch_break,
+            source: e.event.source.clone(),
+            pair: e.pair,
+            unmatched: e.unmatched,
+        }
+    }
+}
+
+/// A row of the internal `log` table (`log --print`, `log --export`).
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    pub id: i32,
+    pub date: String,
+    pub operation: String,
+    pub target: String,
+    pub message: String,
+    /// OS user, for operations recorded with a snapshot
+    pub user: Option<String>,
+    /// `done` / `undone` / `discarded` for undoable operations
+    pub state: Option<String>,
+    /// Field-level changes (`#id date kind field: old → new`), separated by `; `
+    pub changes: String,
+}
+
+/// Write `rows` to `w` in the given format. `Table` writes nothing: tables are rendered by the caller.
+pub fn write_rows<T: Serialize, W: Write>(
+    mut w: W,
+    format: OutputFormat,
+    rows: &[T],
+) -> Result<()> {
+    match format {
+        OutputFormat::Table => {}
+        OutputFormat::Json => {
+            for row in rows {
+                serde_json::to_writer(&mut w, row)?;
+                w.write_all(b"\n")?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(&mut w);
+            for row in rows {
+                wtr.serialize(row)?;
+            }
+            wtr.flush()?;
+        }
+    }
+    w.flush()?;
+    Ok(())
+}
+
+/// Write `rows` to stdout in the given format.
+pub fn print_rows<T: Serialize>(format: OutputFormat, rows: &[T]) -> Result<()> {
+    write_rows(io::stdout().lock(), format, rows)
+}
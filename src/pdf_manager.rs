@@ -1,11 +1,58 @@
-use pdf_writer::{Content, Name, Pdf, Rect, Ref};
+//! PDF rendering of exported tables.
+//!
+//! Tables are split across as many A4 pages as needed, with the optional title and the header
+//! row repeated on every page and a `Page N of M` footer. Text is set in DejaVu Sans, embedded as
+//! a subset containing only the glyphs actually used, so any Unicode text (accented names,
+//! `→`, ...) is shown and can be copied out of the document.
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, Write};
 use std::path::Path;
+use subsetter::GlyphRemapper;
+use ttf_parser::{Face, GlyphId};
+
+/// DejaVu Sans (see `assets/fonts/LICENSE-DejaVu.txt`).
+static FONT_DATA: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+/// PostScript name of the embedded subset (six-letter tag + `+` + font name).
+const BASE_FONT: &[u8] = b"RTLOGR+DejaVuSans";
+const SYSTEM_INFO: SystemInfo = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
+
+const TITLE_SIZE: f32 = 14.0;
+const HEADER_SIZE: f32 = 10.0;
+const BODY_SIZE: f32 = 9.0;
+const FOOTER_SIZE: f32 = 8.0;
+/// Smallest font size used when shrinking a wide table to the page width
+const MIN_BODY_SIZE: f32 = 5.0;
+const CELL_PAD: f32 = 4.0;
+/// Space reserved at the bottom of every page for the footer
+const FOOTER_H: f32 = 20.0;
+
+/// Column geometry of a table, computed once from its content.
+struct Columns {
+    widths: Vec<f32>,
+    header_size: f32,
+    body_size: f32,
+}
 
 pub struct PdfManager {
-    pdf: Pdf,
-    contents_id: Ref,
+    face: Face<'static>,
+    /// Glyphs used so far, remapped to the ids of the embedded subset
+    glyphs: GlyphRemapper,
+    /// Character behind each subset glyph, for the ToUnicode map
+    chars: BTreeMap<u16, char>,
+    title: Option<String>,
+    pages: Vec<Content>,
+    /// Baseline of the last written row on the current page
+    y: f32,
     page_w: f32,
     page_h: f32,
     margin: f32,
@@ -20,121 +67,415 @@ impl Default for PdfManager {
 
 impl PdfManager {
     pub fn new() -> Self {
-        let mut pdf = Pdf::new();
-
-        let catalog_id = Ref::new(1);
-        let pages_id = Ref::new(2);
-        let page_id = Ref::new(3);
-        let font_id = Ref::new(4);
-        let contents_id = Ref::new(5);
-
-        // Catalogo / albero pagine
-        pdf.catalog(catalog_id).pages(pages_id);
-        pdf.pages(pages_id).kids([page_id]).count(1);
-
+        let face = Face::parse(FONT_DATA, 0).expect("bundled font is a valid TrueType file");
         // A4
         let (page_w, page_h) = (595.0, 842.0);
-
-        // Pagina
-        {
-            let mut page = pdf.page(page_id);
-            page.parent(pages_id)
-                .media_box(Rect::new(0.0, 0.0, page_w, page_h))
-                .contents(contents_id);
-            page.resources().fonts().pair(Name(b"F1"), font_id);
-        }
-
-        // Font
-        pdf.type1_font(font_id).base_font(Name(b"Helvetica"));
-
         Self {
-            pdf,
-            contents_id,
+            face,
+            glyphs: GlyphRemapper::new(),
+            chars: BTreeMap::new(),
+            title: None,
+            pages: Vec::new(),
+            y: page_h,
             page_w,
             page_h,
-            margin: 50.0,
-            row_h: 18.0,
+            margin: 40.0,
+            row_h: 16.0,
         }
     }
 
-    /// Scrive una riga di testo in tabella alle coordinate (x, y)
-    fn write_row(
-        &self,
-        content: &mut Content,
-        y: f32,
-        col_w: f32,
-        table_x: f32,
-        row: &[String],
-        bold: bool,
-    ) {
-        content.begin_text();
-        content.set_font(Name(b"F1"), if bold { 11.0 } else { 10.0 });
+    /// Title printed at the top of every page (and stored in the document info).
+    /// Must be set before writing content.
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = Some(title.into());
+    }
 
-        for (i, text) in row.iter().enumerate() {
-            let cell_x = table_x + (i as f32) * col_w + 4.0;
-            // baseline a 5 pt dal bordo inferiore della cella
-            content.set_text_matrix([1.0, 0.0, 0.0, 1.0, cell_x, y + 5.0]);
-            content.show(pdf_writer::Str(text.as_bytes()));
-        }
+    /// Row height of tables and label/value blocks (16 pt by default).
+    pub fn set_row_height(&mut self, row_h: f32) {
+        self.row_h = row_h;
+    }
 
-        content.end_text();
+    /// Number of pages written so far.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Width of `text` in points at the given font size.
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let units: f32 = text
+            .chars()
+            .map(|c| {
+                let glyph = self.face.glyph_index(c).unwrap_or(GlyphId(0));
+                self.face.glyph_hor_advance(glyph).unwrap_or(0) as f32
+            })
+            .sum();
+        units * size / self.face.units_per_em() as f32
     }
 
-    /// Scrive una tabella semplice con header e righe di dati
+    /// Write a table with a header row and data rows, continuing on new pages as needed.
+    ///
+    /// Columns are sized to their content; tables wider than the page are set in a smaller
+    /// font and cells that still don't fit are truncated with `…`.
     pub fn write_table(&mut self, headers: &[&str], rows: &[Vec<String>]) {
-        let table_x = self.margin;
-        let mut y = self.page_h - self.margin; // partiamo dall’alto
-        let cols = headers.len().max(1) as f32;
-        let table_w = self.page_w - 2.0 * self.margin;
-        let col_w = table_w / cols;
+        // zebra stripe on even rows
+        self.table(headers, rows, |r| (r % 2 == 0).then_some(0.96));
+    }
 
-        let mut content = Content::new();
+    /// Same as [`write_table`](Self::write_table), with a gray background on the rows whose
+    /// `shaded` flag is set (e.g. weekends) instead of zebra stripes.
+    pub fn write_table_shaded(&mut self, headers: &[&str], rows: &[Vec<String>], shaded: &[bool]) {
+        self.table(headers, rows, |r| {
+            shaded.get(r).copied().unwrap_or(false).then_some(0.90)
+        });
+    }
 
-        // --- Header background ---
-        y -= self.row_h;
-        content.save_state();
-        content.set_fill_rgb(0.90, 0.90, 0.90);
-        content.rect(table_x, y, table_w, self.row_h).fill_nonzero();
-        content.restore_state();
+    /// Start a new page; the title, if set, is printed at its top.
+    pub fn start_page(&mut self) {
+        self.pages.push(Content::new());
+        self.y = self.page_h - self.margin;
+        if let Some(title) = self.title.clone() {
+            self.y -= TITLE_SIZE;
+            self.text(self.margin, self.y, TITLE_SIZE, &title);
+            self.y -= self.row_h / 2.0;
+        }
+    }
 
-        // --- Header text (bold “simulato” aumentando la size) ---
-        content.begin_text();
-        content.set_font(Name(b"F1"), 11.0);
+    /// Write a line of large text (e.g. the heading of a page section).
+    pub fn write_heading(&mut self, text: &str) {
+        self.ensure_space(TITLE_SIZE + self.row_h);
+        self.y -= TITLE_SIZE;
+        let (x, y) = (self.margin, self.y);
+        self.text(x, y, TITLE_SIZE, text);
+        self.y -= self.row_h / 2.0;
+    }
+
+    fn table(
+        &mut self,
+        headers: &[&str],
+        rows: &[Vec<String>],
+        shade: impl Fn(usize) -> Option<f32>,
+    ) {
+        let columns = self.layout_columns(headers, rows);
         let header_row: Vec<String> = headers.iter().map(|s| s.to_string()).collect();
-        self.write_row(&mut content, y, col_w, table_x, &header_row, true);
-        content.end_text();
 
-        // --- Data rows ---
+        self.ensure_space(2.0 * self.row_h);
+        self.write_header(&columns, &header_row);
+
         for (r, row) in rows.iter().enumerate() {
-            // salto pagina minimale (solo una pagina in questo esempio)
-            if y - self.row_h < self.margin {
-                break;
+            if self.ensure_space(self.row_h) {
+                self.write_header(&columns, &header_row);
             }
+            self.y -= self.row_h;
 
-            y -= self.row_h;
-
-            // zebra stripe (righe pari grigio chiaro)
-            if r % 2 == 0 {
+            if let Some(gray) = shade(r) {
+                let (x, y, w, h) = (self.margin, self.y, self.table_width(), self.row_h);
+                let content = self.page();
                 content.save_state();
-                content.set_fill_rgb(0.96, 0.96, 0.96);
-                content.rect(table_x, y, table_w, self.row_h).fill_nonzero();
+                content.set_fill_rgb(gray, gray, gray);
+                content.rect(x, y, w, h).fill_nonzero();
                 content.restore_state();
             }
+            self.write_cells(&columns, row, columns.body_size);
+        }
+        self.y -= self.row_h / 2.0;
+    }
+
+    /// Append a `label  value` block (e.g. totals) below the current content.
+    /// The block is kept on a single page.
+    pub fn write_summary(&mut self, title: &str, lines: &[(String, String)]) {
+        let height = self.row_h * (lines.len() + 1) as f32 + self.row_h / 2.0;
+        self.ensure_space(height);
+
+        self.y -= self.row_h;
+        let (x, y, w) = (self.margin, self.y, self.table_width());
+        self.text(x, y + 4.0, HEADER_SIZE, title);
+        let content = self.page();
+        content.set_line_width(0.5);
+        content.move_to(x, y + 1.0);
+        content.line_to(x + w, y + 1.0);
+        content.stroke();
 
-            content.begin_text();
-            content.set_font(Name(b"F1"), 10.0);
-            self.write_row(&mut content, y, col_w, table_x, row, false);
-            content.end_text();
+        self.fields(lines);
+    }
+
+    /// Append `label  value` lines without a title (e.g. the header of a form).
+    pub fn write_fields(&mut self, lines: &[(String, String)]) {
+        let height = self.row_h * lines.len() as f32 + self.row_h / 2.0;
+        self.ensure_space(height);
+        self.fields(lines);
+    }
+
+    /// Append signature lines side by side, with their label underneath.
+    pub fn write_signatures(&mut self, labels: &[String]) {
+        let gap = 2.5 * self.row_h;
+        self.ensure_space(gap + self.row_h);
+        self.y -= gap;
+
+        let slot = self.table_width() / labels.len().max(1) as f32;
+        let y = self.y;
+        for (i, label) in labels.iter().enumerate() {
+            let x = self.margin + i as f32 * slot;
+            let content = self.page();
+            content.set_line_width(0.5);
+            content.move_to(x + CELL_PAD, y);
+            content.line_to(x + slot - 4.0 * CELL_PAD, y);
+            content.stroke();
+            self.text(x + CELL_PAD, y - 10.0, FOOTER_SIZE, label);
         }
+        self.y -= self.row_h;
+    }
 
-        // Scrivi lo stream di contenuto
-        self.pdf.stream(self.contents_id, &content.finish());
+    fn fields(&mut self, lines: &[(String, String)]) {
+        let x = self.margin;
+        let label_w = lines
+            .iter()
+            .map(|(label, _)| self.text_width(label, BODY_SIZE))
+            .fold(0.0, f32::max)
+            + 4.0 * CELL_PAD;
+        for (label, value) in lines {
+            self.y -= self.row_h;
+            self.text(x + CELL_PAD, self.y + 4.0, BODY_SIZE, label);
+            self.text(x + CELL_PAD + label_w, self.y + 4.0, BODY_SIZE, value);
+        }
+        self.y -= self.row_h / 2.0;
     }
 
-    pub fn save(self, path: &Path) -> std::io::Result<()> {
-        let bytes = self.pdf.finish();
+    /// Serialize the document: page footers, embedded font subset and page tree.
+    pub fn finish(mut self) -> io::Result<Vec<u8>> {
+        if self.pages.is_empty() {
+            self.start_page();
+        }
+        let count = self.pages.len();
+        for index in 0..count {
+            let label = format!("Page {} of {}", index + 1, count);
+            let x = self.page_w - self.margin - self.text_width(&label, FOOTER_SIZE);
+            self.text_on(index, x, self.margin / 2.0, FOOTER_SIZE, &label);
+        }
+
+        let mut alloc = Ref::new(1);
+        let catalog_id = alloc.bump();
+        let pages_id = alloc.bump();
+        let info_id = alloc.bump();
+        let font_id = alloc.bump();
+        let cid_font_id = alloc.bump();
+        let descriptor_id = alloc.bump();
+        let cmap_id = alloc.bump();
+        let font_file_id = alloc.bump();
+        let page_ids: Vec<(Ref, Ref)> = (0..count).map(|_| (alloc.bump(), alloc.bump())).collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(pages_id);
+        pdf.pages(pages_id)
+            .kids(page_ids.iter().map(|(page, _)| *page))
+            .count(count as i32);
+        {
+            let mut info = pdf.document_info(info_id);
+            info.producer(TextStr("rTimelogger"));
+            if let Some(title) = &self.title {
+                info.title(TextStr(title));
+            }
+        }
+
+        for ((page_id, content_id), content) in page_ids.iter().zip(std::mem::take(&mut self.pages))
+        {
+            let mut page = pdf.page(*page_id);
+            page.parent(pages_id)
+                .media_box(Rect::new(0.0, 0.0, self.page_w, self.page_h))
+                .contents(*content_id);
+            page.resources().fonts().pair(Name(b"F1"), font_id);
+            page.finish();
+            pdf.stream(*content_id, &deflate(&content.finish())?)
+                .filter(Filter::FlateDecode);
+        }
+
+        self.write_font(
+            &mut pdf,
+            font_id,
+            cid_font_id,
+            descriptor_id,
+            cmap_id,
+            font_file_id,
+        )?;
+        Ok(pdf.finish())
+    }
+
+    pub fn save(self, path: &Path) -> io::Result<()> {
+        let bytes = self.finish()?;
         let mut f = File::create(path)?;
         f.write_all(&bytes)?;
         Ok(())
     }
+
+    fn table_width(&self) -> f32 {
+        self.page_w - 2.0 * self.margin
+    }
+
+    /// Natural column widths (widest cell + padding), stretched or shrunk to the page width.
+    fn layout_columns(&self, headers: &[&str], rows: &[Vec<String>]) -> Columns {
+        let mut natural: Vec<f32> = headers
+            .iter()
+            .map(|h| self.text_width(h, HEADER_SIZE))
+            .collect();
+        for row in rows {
+            for (i, cell) in row.iter().enumerate().take(natural.len()) {
+                natural[i] = natural[i].max(self.text_width(cell, BODY_SIZE));
+            }
+        }
+        let padded: Vec<f32> = natural.iter().map(|w| w + 2.0 * CELL_PAD).collect();
+        let total: f32 = padded.iter().sum::<f32>().max(1.0);
+        let ratio = self.table_width() / total;
+        let scale = ratio.clamp(MIN_BODY_SIZE / BODY_SIZE, 1.0);
+
+        Columns {
+            widths: padded.iter().map(|w| w * ratio).collect(),
+            header_size: HEADER_SIZE * scale,
+            body_size: BODY_SIZE * scale,
+        }
+    }
+
+    fn write_header(&mut self, columns: &Columns, header_row: &[String]) {
+        self.y -= self.row_h;
+        let (x, y, w, h) = (self.margin, self.y, self.table_width(), self.row_h);
+        let content = self.page();
+        content.save_state();
+        content.set_fill_rgb(0.90, 0.90, 0.90);
+        content.rect(x, y, w, h).fill_nonzero();
+        content.restore_state();
+        self.write_cells(columns, header_row, columns.header_size);
+    }
+
+    fn write_cells(&mut self, columns: &Columns, row: &[String], size: f32) {
+        let mut x = self.margin;
+        for (text, width) in row.iter().zip(&columns.widths) {
+            let fitted = self.fit(text, width - 2.0 * CELL_PAD, size);
+            // baseline 5 pt above the bottom of the cell
+            self.text(x + CELL_PAD, self.y + 5.0, size, &fitted);
+            x += width;
+        }
+    }
+
+    /// Truncate `text` with `…` so that it is at most `max_w` points wide.
+    fn fit(&self, text: &str, max_w: f32, size: f32) -> String {
+        if self.text_width(text, size) <= max_w {
+            return text.to_string();
+        }
+        let mut fitted: String = text.to_string();
+        while !fitted.is_empty() && self.text_width(&format!("{}…", fitted), size) > max_w {
+            fitted.pop();
+        }
+        format!("{}…", fitted)
+    }
+
+    /// Start a new page if fewer than `height` points are left above the footer.
+    /// Returns true when a page was started.
+    fn ensure_space(&mut self, height: f32) -> bool {
+        if self.pages.is_empty() || self.y - height < self.margin + FOOTER_H {
+            self.start_page();
+            return true;
+        }
+        false
+    }
+
+    fn page(&mut self) -> &mut Content {
+        self.pages.last_mut().expect("a page has been started")
+    }
+
+    fn text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        let index = self.pages.len() - 1;
+        self.text_on(index, x, y, size, text);
+    }
+
+    fn text_on(&mut self, page: usize, x: f32, y: f32, size: f32, text: &str) {
+        let encoded = self.encode(text);
+        let content = &mut self.pages[page];
+        content.begin_text();
+        content.set_font(Name(b"F1"), size);
+        content.set_text_matrix([1.0, 0.0, 0.0, 1.0, x, y]);
+        content.show(Str(&encoded));
+        content.end_text();
+    }
+
+    /// Encode `text` as 2-byte glyph ids of the subset (Identity-H encoding).
+    fn encode(&mut self, text: &str) -> Vec<u8> {
+        let mut out = Vec::with_capacity(text.len() * 2);
+        for c in text.chars() {
+            let old = self.face.glyph_index(c).map_or(0, |g| g.0);
+            let new = self.glyphs.remap(old);
+            if old != 0 {
+                self.chars.entry(new).or_insert(c);
+            }
+            out.extend_from_slice(&new.to_be_bytes());
+        }
+        out
+    }
+
+    fn write_font(
+        &self,
+        pdf: &mut Pdf,
+        font_id: Ref,
+        cid_font_id: Ref,
+        descriptor_id: Ref,
+        cmap_id: Ref,
+        font_file_id: Ref,
+    ) -> io::Result<()> {
+        let face = &self.face;
+        let scale = 1000.0 / face.units_per_em() as f32;
+        let to_pdf = |v: i16| v as f32 * scale;
+
+        pdf.type0_font(font_id)
+            .base_font(Name(BASE_FONT))
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid_font_id)
+            .to_unicode(cmap_id);
+
+        let widths: Vec<f32> = self
+            .glyphs
+            .remapped_gids()
+            .map(|old| face.glyph_hor_advance(GlyphId(old)).unwrap_or(0) as f32 * scale)
+            .collect();
+        let mut cid = pdf.cid_font(cid_font_id);
+        cid.subtype(CidFontType::Type2)
+            .base_font(Name(BASE_FONT))
+            .system_info(SYSTEM_INFO)
+            .font_descriptor(descriptor_id)
+            .default_width(0.0)
+            .cid_to_gid_map_predefined(Name(b"Identity"));
+        cid.widths().consecutive(0, widths);
+        cid.finish();
+
+        let bbox = face.global_bounding_box();
+        pdf.font_descriptor(descriptor_id)
+            .name(Name(BASE_FONT))
+            .flags(FontFlags::NON_SYMBOLIC)
+            .bbox(Rect::new(
+                to_pdf(bbox.x_min),
+                to_pdf(bbox.y_min),
+                to_pdf(bbox.x_max),
+                to_pdf(bbox.y_max),
+            ))
+            .italic_angle(face.italic_angle())
+            .ascent(to_pdf(face.ascender()))
+            .descent(to_pdf(face.descender()))
+            .cap_height(to_pdf(face.capital_height().unwrap_or(face.ascender())))
+            .stem_v(80.0)
+            .font_file2(font_file_id);
+
+        let mut cmap = UnicodeCmap::new(Name(b"Custom"), SYSTEM_INFO);
+        for (glyph, c) in &self.chars {
+            cmap.pair(*glyph, *c);
+        }
+        pdf.cmap(cmap_id, &cmap.finish());
+
+        let subset = subsetter::subset(FONT_DATA, 0, &self.glyphs)
+            .map_err(|e| io::Error::other(format!("font subsetting failed: {:?}", e)))?;
+        pdf.stream(font_file_id, &deflate(&subset)?)
+            .filter(Filter::FlateDecode);
+        Ok(())
+    }
+}
+
+fn deflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
 }
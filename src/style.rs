@@ -0,0 +1,216 @@
+//! Terminal styling.
+//!
+//! All ANSI escapes used by the CLI go through this module. Colors are enabled according to
+//! `--color auto|always|never` (auto = stdout is a terminal and `NO_COLOR` is not set) and the
+//! palette for positions and surplus comes from the `colors:` section of the configuration.
+
+use crate::config::ColorTheme;
+use std::io::IsTerminal;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// When to emit colors (global `--color` flag).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Colors only when stdout is a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Neutral gray used for missing values.
+pub const DIM: &str = "90";
+/// Cyan used for informational values (worked time without surplus).
+pub const INFO: &str = "36";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PALETTE: RwLock<Option<Palette>> = RwLock::new(None);
+
+/// SGR parameters (e.g. `"34"`, `"45;97;1"`) resolved from a [`ColorTheme`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    pub office: String,
+    pub remote: String,
+    pub client: String,
+    pub holiday: String,
+    pub mixed: String,
+    pub surplus_positive: String,
+    pub surplus_negative: String,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::from_theme(&ColorTheme::default())
+    }
+}
+
+impl Palette {
+    /// Resolve a theme; invalid entries fall back to the default color with a warning.
+    pub fn from_theme(theme: &ColorTheme) -> Self {
+        let defaults = ColorTheme::default();
+        let pick = |name: &str, spec: &str, default: &str| match parse_color_spec(spec) {
+            Some(sgr) => sgr,
+            None => {
+                eprintln!(
+                    "⚠️  Invalid color '{}' for colors.{} in config, using '{}'",
+                    spec, name, default
+                );
+                parse_color_spec(default).unwrap_or_default()
+            }
+        };
+        Self {
+            office: pick("office", &theme.office, &defaults.office),
+            remote: pick("remote", &theme.remote, &defaults.remote),
+            client: pick("client", &theme.client, &defaults.client),
+            holiday: pick("holiday", &theme.holiday, &defaults.holiday),
+            mixed: pick("mixed", &theme.mixed, &defaults.mixed),
+            surplus_positive: pick(
+                "surplus_positive",
+                &theme.surplus_positive,
+                &defaults.surplus_positive,
+            ),
+            surplus_negative: pick(
+                "surplus_negative",
+                &theme.surplus_negative,
+                &defaults.surplus_negative,
+            ),
+        }
+    }
+
+    /// SGR parameters for a position code (O, R, C, H, M); empty for unknown codes.
+    pub fn position(&self, pos: &str) -> &str {
+        match pos {
+            "O" => &self.office,
+            "R" => &self.remote,
+            "C" => &self.client,
+            "H" => &self.holiday,
+            "M" => &self.mixed,
+            _ => "",
+        }
+    }
+
+    /// SGR parameters for a surplus value (no color for zero).
+    pub fn surplus(&self, minutes: i64) -> &str {
+        match minutes {
+            m if m > 0 => &self.surplus_positive,
+            m if m < 0 => &self.surplus_negative,
+            _ => "",
+        }
+    }
+}
+
+/// Decide whether colors are enabled and install the palette. Called once by the CLI.
+pub fn init(choice: ColorChoice, theme: &ColorTheme) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+            !no_color && std::io::stdout().is_terminal()
+        }
+    };
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if let Ok(mut p) = PALETTE.write() {
+        *p = Some(Palette::from_theme(theme));
+    }
+}
+
+/// Force colors on or off (tests, embedding).
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn colors_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// The active palette (default one if [`init`] was never called).
+pub fn palette() -> Palette {
+    PALETTE
+        .read()
+        .ok()
+        .and_then(|p| p.clone())
+        .unwrap_or_default()
+}
+
+/// Wrap `text` in the given SGR parameters when colors are enabled.
+pub fn paint(sgr: &str, text: &str) -> String {
+    if colors_enabled() && !sgr.is_empty() {
+        format!("\x1b[{}m{}\x1b[0m", sgr, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Paint text with the color of a position code.
+pub fn paint_position(pos: &str, text: &str) -> String {
+    paint(palette().position(pos), text)
+}
+
+/// Paint text with the surplus color (green/red by default) of `minutes`.
+pub fn paint_surplus(minutes: i64, text: &str) -> String {
+    paint(palette().surplus(minutes), text)
+}
+
+/// Parse a color spec into SGR parameters.
+///
+/// Accepted forms: raw SGR parameters (`"34"`, `"45;97;1"`) or space separated words:
+/// a foreground color (`red`, `bright_blue`, `gray`, ...), an optional background introduced by
+/// `on` (`on magenta`) and the modifiers `bold`, `dim`, `italic`, `underline`.
+/// `none` or an empty string disables the color.
+pub fn parse_color_spec(spec: &str) -> Option<String> {
+    let spec = spec.trim().to_lowercase();
+    if spec.is_empty() || spec == "none" {
+        return Some(String::new());
+    }
+    if spec.chars().all(|c| c.is_ascii_digit() || c == ';') {
+        return Some(spec);
+    }
+
+    let mut codes: Vec<String> = Vec::new();
+    let mut background = false;
+    for word in spec.split_whitespace() {
+        let code = match word {
+            "on" => {
+                background = true;
+                continue;
+            }
+            "bold" => 1,
+            "dim" => 2,
+            "italic" => 3,
+            "underline" => 4,
+            _ => {
+                let base = color_index(word)?;
+                let code = if background { base + 10 } else { base };
+                background = false;
+                code
+            }
+        };
+        codes.push(code.to_string());
+    }
+    if background {
+        return None; // dangling "on"
+    }
+    Some(codes.join(";"))
+}
+
+fn color_index(name: &str) -> Option<u8> {
+    let (bright, name) = match name.strip_prefix("bright_") {
+        Some(rest) => (true, rest),
+        None => (false, name),
+    };
+    let base = match name {
+        "black" => 30,
+        "red" => 31,
+        "green" => 32,
+        "yellow" => 33,
+        "blue" => 34,
+        "magenta" | "purple" => 35,
+        "cyan" => 36,
+        "white" => 37,
+        "gray" | "grey" if !bright => return Some(90),
+        _ => return None,
+    };
+    Some(if bright { base + 60 } else { base })
+}
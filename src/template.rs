@@ -0,0 +1,123 @@
+//! User-defined export templates (`export --template`).
+//!
+//! Templates use the Jinja syntax of [minijinja](https://docs.rs/minijinja) and see:
+//! - `title`, `dataset`, `range` (or none) and `generated` (local date-time of the export)
+//! - `employee`: the `employee:` config section (`name`, `id`, `company`, `manager`)
+//! - `columns`: the selected column names, and `rows`: one object per row keyed by column
+//! - `totals`: `days`, `worked_minutes`, `expected_minutes`, `surplus_minutes` (rules of `balance`)
+//!
+//! The helpers `mins2hhmm`, `weekday_str` and `describe_position` are available both as filters
+//! and as functions. Templates whose name ends in `.html` are HTML-escaped.
+
+use crate::config::EmployeeInfo;
+use crate::error::{Error, Result};
+use crate::timelog::Balance;
+use crate::utils;
+use minijinja::{Environment, Value, context};
+use serde::Serialize;
+use std::path::Path;
+
+/// Built-in templates: `(name on the command line, template name, source)`.
+pub const BUILTIN: [(&str, &str, &str); 2] = [
+    (
+        "markdown",
+        "report.md",
+        include_str!("../assets/templates/report.md"),
+    ),
+    (
+        "html",
+        "report.html",
+        include_str!("../assets/templates/report.html"),
+    ),
+];
+
+/// Everything a template sees besides the columns and rows.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportInfo {
+    pub title: String,
+    pub dataset: String,
+    pub range: Option<String>,
+    pub generated: String,
+    pub employee: EmployeeInfo,
+    pub totals: Balance,
+}
+
+/// A loaded template: its name (used in error messages and for HTML escaping) and source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub name: String,
+    pub source: String,
+}
+
+impl Template {
+    /// The built-in template `spec` (`markdown`, `html`), or else the template file at `spec`.
+    pub fn load(spec: &str) -> Result<Self> {
+        if let Some((_, name, source)) = BUILTIN.iter().find(|(b, _, _)| *b == spec) {
+            return Ok(Template {
+                name: name.to_string(),
+                source: source.to_string(),
+            });
+        }
+        let path = Path::new(spec);
+        if !path.is_file() {
+            return Err(Error::invalid(format!(
+                "Template not found: {} (use a file or one of: markdown, html)",
+                spec
+            )));
+        }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| spec.to_string());
+        Ok(Template {
+            name,
+            source: std::fs::read_to_string(path)?,
+        })
+    }
+
+    /// Render the rows (serialized objects keyed by column) with `info`.
+    pub fn render<R: Serialize>(
+        &self,
+        info: &ReportInfo,
+        columns: &[&str],
+        rows: &[R],
+    ) -> Result<String> {
+        let ctx = context! {
+            columns => columns,
+            rows => rows,
+            ..Value::from_serialize(info)
+        };
+        Ok(environment().render_named_str(&self.name, &self.source, ctx)?)
+    }
+}
+
+/// Template environment with the rTimelogger helpers registered.
+pub fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_keep_trailing_newline(true);
+    env.add_filter("mins2hhmm", mins2hhmm);
+    env.add_function("mins2hhmm", mins2hhmm);
+    env.add_filter("weekday_str", weekday_str);
+    env.add_function("weekday_str", weekday_str);
+    env.add_filter("describe_position", describe_position);
+    env.add_function("describe_position", describe_position);
+    env
+}
+
+/// Minutes as `HH:MM` (`-HH:MM` when negative); empty for none.
+fn mins2hhmm(minutes: Option<i64>) -> String {
+    minutes.map(utils::signed_hhmm).unwrap_or_default()
+}
+
+/// Weekday of a `YYYY-MM-DD` date; `style` is `s` (Mo), `m` (Mon, default) or `l` (Monday).
+fn weekday_str(date: Option<String>, style: Option<String>) -> String {
+    let style = style.and_then(|s| s.chars().next()).unwrap_or('m');
+    date.map(|d| utils::weekday_str(&d, style))
+        .unwrap_or_default()
+}
+
+/// Label of a position code (`O` → Office); empty for none.
+fn describe_position(pos: Option<String>) -> String {
+    pos.map(|p| utils::describe_position(&p).0)
+        .unwrap_or_default()
+}
@@ -0,0 +1,808 @@
+//! High-level facade over the database and business rules.
+//!
+//! [`Timelog`] wraps a SQLite [`Connection`] and the [`Config`] and exposes the same operations
+//! as the CLI (`add`, `add --edit`, `del`, `list`) as typed methods returning structured results,
+//! so other front-ends (GUI, chat bots, ...) can reuse them without printing anything.
+//!
+//! ```no_run
+//! use rtimelogger::Timelog;
+//! use rtimelogger::config::Config;
+//!
+//! let mut tl = Timelog::open(Config::load()?)?;
+//! tl.punch_in("2025-10-03", "09:00", Some("O"))?;
+//! tl.punch_out("2025-10-03", "17:30", Some(30))?;
+//! let day = tl.day_summary("2025-10-03")?;
+//! println!("worked {} min", day.worked_minutes);
+//! # Ok::<(), rtimelogger::Error>(())
+//! ```
+
+use crate::clock::{Clock, SystemClock};
+use crate::config::Config;
+use crate::db::{self, Event, WorkSession};
+use crate::error::{Error, Result, ValidationError};
+use crate::events::create_missing_event;
+use crate::lock;
+use crate::logic::{
+    self, EventWithPair, PairSummary, compute_event_pairs, compute_event_summaries,
+};
+use crate::tracker::TrackerPair;
+use crate::undo::{self, DayState, Reverted, Snapshot};
+use crate::utils;
+use chrono::NaiveTime;
+use rusqlite::Connection;
+use serde::Serialize;
+
+/// Values for [`Timelog::add`] and [`Timelog::edit_pair`]; unset fields are left untouched.
+#[derive(Debug, Clone, Default)]
+pub struct DayEntry {
+    /// Working position (O, R, H, C; M is also accepted when editing a pair)
+    pub position: Option<String>,
+    /// Start time (HH:MM)
+    pub start: Option<String>,
+    /// Lunch break in minutes (0..=90)
+    pub lunch: Option<i32>,
+    /// End time (HH:MM)
+    pub end: Option<String>,
+}
+
+impl DayEntry {
+    fn is_empty(&self) -> bool {
+        self.position.is_none()
+            && self.start.is_none()
+            && self.lunch.is_none()
+            && self.end.is_none()
+    }
+}
+
+/// Result of [`Timelog::add`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AddOutcome {
+    pub date: String,
+    /// Normalized position requested by the caller (if any)
+    pub position: Option<String>,
+    /// `in` event created (or already present) for the start time
+    pub in_event: Option<Event>,
+    /// `out` event created (or already present) for the end time
+    pub out_event: Option<Event>,
+    pub lunch: Option<i32>,
+    /// Human-readable list of applied changes (also written to the internal log)
+    pub changes: Vec<String>,
+}
+
+/// Result of [`Timelog::edit_pair`].
+#[derive(Debug, Clone, Serialize)]
+pub struct EditedPair {
+    pub date: String,
+    pub pair: usize,
+    /// Aggregated day position after a position change
+    pub position: Option<String>,
+    pub in_event: Option<Event>,
+    pub out_event: Option<Event>,
+    pub changes: Vec<String>,
+}
+
+/// Legacy work session enriched with the computed expected exit and surplus.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionReport {
+    #[serde(flatten)]
+    pub session: WorkSession,
+    /// Expected exit time (HH:MM) when a start time is known
+    pub expected_exit: Option<String>,
+    /// Lunch minutes actually applied by the rules (see [`logic::effective_lunch_minutes`])
+    pub effective_lunch: i32,
+    /// Net worked minutes when both start and end are known
+    pub worked_minutes: Option<i64>,
+    /// Surplus (positive) or deficit (negative) in minutes; None when not applicable
+    pub surplus_minutes: Option<i64>,
+}
+
+/// Everything known about a single day.
+#[derive(Debug, Clone, Serialize)]
+pub struct DaySummary {
+    pub date: String,
+    pub events: Vec<EventWithPair>,
+    pub pairs: Vec<PairSummary>,
+    /// Sum of the pair durations (lunch excluded)
+    pub worked_minutes: i32,
+    pub session: Option<SessionReport>,
+}
+
+/// Sessions of a period (year, month or everything) with totals.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodReport {
+    pub period: Option<String>,
+    pub sessions: Vec<SessionReport>,
+    pub total_worked_minutes: i64,
+    pub total_surplus_minutes: i64,
+}
+
+/// Hours balance of a period.
+#[derive(Debug, Clone, Serialize)]
+pub struct Balance {
+    pub period: Option<String>,
+    /// Number of completed days taken into account
+    pub days: usize,
+    pub worked_minutes: i64,
+    pub expected_minutes: i64,
+    pub surplus_minutes: i64,
+}
+
+/// Library entry point wrapping a database connection, the configuration and a [`Clock`].
+pub struct Timelog {
+    conn: Connection,
+    config: Config,
+    clock: Box<dyn Clock>,
+    force: bool,
+}
+
+impl Timelog {
+    /// Open the database referenced by `config.database` and apply pending migrations.
+    pub fn open(config: Config) -> Result<Self> {
+        let conn = Connection::open(&config.database)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        Self::new(conn, config)
+    }
+
+    /// Wrap an already opened connection (e.g. in-memory) and apply pending migrations.
+    /// The system clock is used until [`Timelog::with_clock`] is called.
+    pub fn new(conn: Connection, config: Config) -> Result<Self> {
+        db::init_db(&conn)?;
+        Ok(Self {
+            conn,
+            config,
+            clock: Box::new(SystemClock),
+            force: false,
+        })
+    }
+
+    /// Replace the clock (e.g. with a [`crate::clock::FixedClock`] in tests).
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Allow changes to dates inside locked periods (the CLI's `--force`); every forced change is
+    /// logged as `lock_override`, see [`lock::guard`].
+    pub fn set_force(&mut self, force: bool) {
+        self.force = force;
+    }
+
+    pub fn force(&self) -> bool {
+        self.force
+    }
+
+    /// Today's date (`YYYY-MM-DD`) according to the clock.
+    pub fn today(&self) -> String {
+        utils::date2iso(&self.clock.today())
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    pub fn connection_mut(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn into_inner(self) -> (Connection, Config) {
+        (self.conn, self.config)
+    }
+
+    /// Register a start time (creates the `in` event and updates the legacy session).
+    pub fn punch_in(&mut self, date: &str, time: &str, position: Option<&str>) -> Result<Event> {
+        let entry = DayEntry {
+            position: position.map(str::to_string),
+            start: Some(time.to_string()),
+            ..Default::default()
+        };
+        let outcome = self.add(date, &entry)?;
+        outcome
+            .in_event
+            .ok_or_else(|| Error::Database(rusqlite::Error::QueryReturnedNoRows))
+    }
+
+    /// Register an end time (creates the `out` event, optionally with its lunch break).
+    pub fn punch_out(&mut self, date: &str, time: &str, lunch: Option<i32>) -> Result<Event> {
+        let entry = DayEntry {
+            end: Some(time.to_string()),
+            lunch,
+            ..Default::default()
+        };
+        let outcome = self.add(date, &entry)?;
+        outcome
+            .out_event
+            .ok_or_else(|| Error::Database(rusqlite::Error::QueryReturnedNoRows))
+    }
+
+    /// Add or update a day: same semantics as `rtimelogger add <date> [pos] [start] [lunch] [end]`.
+    /// All values are validated before anything is written.
+    pub fn add(&mut self, date: &str, entry: &DayEntry) -> Result<AddOutcome> {
+        self.atomically(|tl| tl.do_add(date, entry))
+    }
+
+    fn do_add(&mut self, date: &str, entry: &DayEntry) -> Result<AddOutcome> {
+        validate_date(date)?;
+        if entry.is_empty() {
+            return Err(Error::invalid(
+                "Please provide at least one of: position, start, lunch, end (or use --edit --pair)",
+            ));
+        }
+        let pos = normalize_position(entry.position.as_deref(), &["O", "R", "H", "C"])?;
+        let start = entry.start.as_deref().map(validate_time).transpose()?;
+        let end = entry.end.as_deref().map(validate_time).transpose()?;
+        if let Some(l) = entry.lunch {
+            self.validate_lunch(l)?;
+        }
+
+        let overrides = lock::guard(&self.conn, &[date], self.force)?;
+        let before = DayState::capture(&self.conn, &[date])?;
+        let mut changes: Vec<String> = Vec::new();
+        let mut in_event = None;
+        let mut out_event = None;
+
+        if let Some(p) = pos.as_deref() {
+            db::upsert_position(&self.conn, date, p)?;
+            changes.push(format!("position={}", p));
+        }
+
+        if let Some(sv) = start.as_deref() {
+            db::upsert_start(&self.conn, date, sv)?;
+            changes.push(format!("start={}", sv));
+            let args = db::AddEventArgs {
+                date,
+                time: sv,
+                kind: "in",
+                position: pos.as_deref(),
+                source: "cli",
+                meta: None,
+            };
+            let id =
+                db::add_event_with_clock(&mut self.conn, &args, &self.config, self.clock.as_ref())?;
+            self.sync_position(date)?;
+            in_event = db::get_event(&self.conn, id)?;
+        }
+
+        if let Some(l) = entry.lunch {
+            db::upsert_lunch(&self.conn, date, l)?;
+            changes.push(format!("lunch={}", l));
+            // If there is an out event present, set its lunch_break for compatibility
+            if let Some(out_ev) = db::last_out_before(&self.conn, date, "23:59")?
+                && out_ev.lunch_break == 0
+            {
+                db::set_event_lunch(&self.conn, out_ev.id, l)?;
+            }
+        }
+
+        if let Some(ev_t) = end.as_deref() {
+            db::upsert_end(&self.conn, date, ev_t)?;
+            changes.push(format!("end={}", ev_t));
+            let args = db::AddEventArgs {
+                date,
+                time: ev_t,
+                kind: "out",
+                position: pos.as_deref(),
+                source: "cli",
+                meta: None,
+            };
+            let id =
+                db::add_event_with_clock(&mut self.conn, &args, &self.config, self.clock.as_ref())?;
+            if let Some(l) = entry.lunch
+                && l > 0
+            {
+                db::set_event_lunch(&self.conn, id as i32, l)?;
+            }
+            self.sync_position(date)?;
+            out_event = db::get_event(&self.conn, id)?;
+        }
+
+        let msg = format!("date={} | {}", date, changes.join(", "));
+        self.log_change("add", "Add record on events", &msg, before)?;
+
+        overrides.record(&self.conn, self.clock.as_ref(), "add")?;
+        Ok(AddOutcome {
+            date: date.to_string(),
+            position: pos,
+            in_event,
+            out_event,
+            lunch: entry.lunch,
+            changes,
+        })
+    }
+
+    /// Record a pair imported from another time tracker (see [`crate::tracker`]): both events
+    /// carry `source` and the pair's projects and tags as `meta`; the lunch goes on the `out`.
+    pub fn import_pair(
+        &mut self,
+        pair: &TrackerPair,
+        position: Option<&str>,
+        source: &str,
+    ) -> Result<AddOutcome> {
+        self.atomically(|tl| tl.do_import_pair(pair, position, source))
+    }
+
+    fn do_import_pair(
+        &mut self,
+        pair: &TrackerPair,
+        position: Option<&str>,
+        source: &str,
+    ) -> Result<AddOutcome> {
+        validate_date(&pair.date)?;
+        let date = pair.date.as_str();
+        let pos = normalize_position(position, &["O", "R", "H", "C"])?;
+        let start = validate_time(&pair.start)?;
+        let end = validate_time(&pair.end)?;
+        if end <= start {
+            return Err(Error::invalid(format!(
+                "End time must be after start time ({} >= {})",
+                end, start
+            )));
+        }
+        self.validate_lunch(pair.lunch)?;
+
+        let overrides = lock::guard(&self.conn, &[date], self.force)?;
+        let before = DayState::capture(&self.conn, &[date])?;
+        if let Some(p) = pos.as_deref() {
+            db::upsert_position(&self.conn, date, p)?;
+        }
+        let meta = pair.meta.to_meta();
+        let mut ids = Vec::with_capacity(2);
+        for (time, kind) in [(start.as_str(), "in"), (end.as_str(), "out")] {
+            let args = db::AddEventArgs {
+                date,
+                time,
+                kind,
+                position: pos.as_deref(),
+                source,
+                meta: Some(&meta),
+            };
+            ids.push(db::add_event_with_clock(
+                &mut self.conn,
+                &args,
+                &self.config,
+                self.clock.as_ref(),
+            )?);
+        }
+        if pair.lunch > 0 {
+            db::set_event_lunch(&self.conn, ids[1] as i32, pair.lunch)?;
+            db::upsert_lunch(&self.conn, date, pair.lunch)?;
+        }
+        self.sync_position(date)?;
+
+        let changes = vec![
+            format!("start={}", start),
+            format!("lunch={}", pair.lunch),
+            format!("end={}", end),
+        ];
+        let msg = format!("date={} | {} | {}", date, source, changes.join(", "));
+        self.log_change("import", "Import pair from another tracker", &msg, before)?;
+
+        overrides.record(&self.conn, self.clock.as_ref(), "import")?;
+        Ok(AddOutcome {
+            date: date.to_string(),
+            position: pos,
+            in_event: db::get_event(&self.conn, ids[0])?,
+            out_event: db::get_event(&self.conn, ids[1])?,
+            lunch: Some(pair.lunch),
+            changes,
+        })
+    }
+
+    /// Edit an existing pair of a day (same semantics as `rtimelogger add --edit --pair N`).
+    /// Missing `in`/`out` events of the pair are created when a start/end is provided.
+    pub fn edit_pair(&mut self, date: &str, pair: usize, edit: &DayEntry) -> Result<EditedPair> {
+        self.atomically(|tl| tl.do_edit_pair(date, pair, edit))
+    }
+
+    fn do_edit_pair(&mut self, date: &str, pair: usize, edit: &DayEntry) -> Result<EditedPair> {
+        validate_date(date)?;
+        let pos = normalize_position(edit.position.as_deref(), &["O", "R", "H", "C", "M"])?;
+        let start = edit.start.as_deref().map(validate_time).transpose()?;
+        let end = edit.end.as_deref().map(validate_time).transpose()?;
+        if let (Some(s), Some(e)) = (start.as_deref(), end.as_deref())
+            && e <= s
+        {
+            return Err(Error::invalid(format!(
+                "End time must be after start time ({} >= {})",
+                e, s
+            )));
+        }
+        if let Some(l) = edit.lunch {
+            self.validate_lunch(l)?;
+        }
+
+        let events = db::list_events_by_date(&self.conn, date)?;
+        if events.is_empty() {
+            return Err(Error::invalid(format!(
+                "No events for date {} to edit",
+                date
+            )));
+        }
+        let (mut in_event, mut out_event) = pair_events(&events, pair);
+        if in_event.is_none() && out_event.is_none() {
+            return Err(pair_not_found(date, pair));
+        }
+        let overrides = lock::guard(&self.conn, &[date], self.force)?;
+        let before = DayState::capture(&self.conn, &[date])?;
+
+        // Create missing events if the user tries to complete the pair
+        if let Some(sv) = start.as_deref()
+            && in_event.is_none()
+        {
+            in_event = create_missing_event(
+                &mut self.conn,
+                date,
+                sv,
+                "in",
+                &pos,
+                out_event.as_ref(),
+                &self.config,
+                self.clock.as_ref(),
+            )?;
+        }
+        if let Some(ev_t) = end.as_deref()
+            && out_event.is_none()
+        {
+            out_event = create_missing_event(
+                &mut self.conn,
+                date,
+                ev_t,
+                "out",
+                &pos,
+                in_event.as_ref(),
+                &self.config,
+                self.clock.as_ref(),
+            )?;
+        }
+
+        let mut changes: Vec<String> = Vec::new();
+        let mut position = None;
+
+        if let Some(p) = pos.as_deref() {
+            for ev in in_event.iter().chain(out_event.iter()) {
+                db::set_event_position(&self.conn, ev.id, p)?;
+            }
+            // After updating event positions, compute the aggregate across all events of the day
+            let agg = db::aggregate_position_from_events(&self.conn, date)?
+                .unwrap_or_else(|| p.to_string());
+            db::force_set_position(&self.conn, date, &agg)?;
+            position = Some(agg);
+            changes.push(format!("pos={}", p));
+        }
+
+        if let (Some(sv), Some(ie)) = (start.as_deref(), in_event.as_ref()) {
+            db::set_event_time(&self.conn, ie.id, sv)?;
+            db::force_set_start(&self.conn, date, sv)?;
+            changes.push(format!("start={}", sv));
+        }
+
+        if let (Some(ev_t), Some(oe)) = (end.as_deref(), out_event.as_ref()) {
+            db::set_event_time(&self.conn, oe.id, ev_t)?;
+            db::force_set_end(&self.conn, date, ev_t)?;
+            changes.push(format!("end={}", ev_t));
+        }
+
+        if let (Some(lv), Some(oe)) = (edit.lunch, out_event.as_ref()) {
+            db::set_event_lunch(&self.conn, oe.id, lv)?;
+            db::force_set_lunch(&self.conn, date, lv)?;
+            changes.push(format!("lunch={}", lv));
+        }
+
+        if changes.is_empty() {
+            return Err(Error::invalid(
+                "No fields provided to edit (use --pos/--in/--out/--lunch)",
+            ));
+        }
+        self.log_change(
+            "edit",
+            "Edit existing pair events",
+            &format!("date={} pair={} | {}", date, pair, changes.join(", ")),
+            before,
+        )?;
+
+        // Reload the pair to return the stored values
+        let in_event = match in_event {
+            Some(e) => db::get_event(&self.conn, e.id as i64)?,
+            None => None,
+        };
+        let out_event = match out_event {
+            Some(e) => db::get_event(&self.conn, e.id as i64)?,
+            None => None,
+        };
+
+        overrides.record(&self.conn, self.clock.as_ref(), "edit")?;
+        Ok(EditedPair {
+            date: date.to_string(),
+            pair,
+            position,
+            in_event,
+            out_event,
+            changes,
+        })
+    }
+
+    /// Delete the events of one pair and recompute the legacy session of the day.
+    /// Returns the number of deleted events.
+    pub fn delete_pair(&mut self, date: &str, pair: usize) -> Result<usize> {
+        self.atomically(|tl| tl.do_delete_pair(date, pair))
+    }
+
+    fn do_delete_pair(&mut self, date: &str, pair: usize) -> Result<usize> {
+        validate_date(date)?;
+        let events = db::list_events_by_date(&self.conn, date)?;
+        let ids: Vec<i32> = compute_event_pairs(&events)
+            .iter()
+            .filter(|e| e.pair == pair)
+            .map(|e| e.event.id)
+            .collect();
+        if ids.is_empty() {
+            return Err(pair_not_found(date, pair));
+        }
+
+        let overrides = lock::guard(&self.conn, &[date], self.force)?;
+        let before = DayState::capture(&self.conn, &[date])?;
+        let rows = db::delete_events_by_ids_and_recompute_sessions(&mut self.conn, &ids, date)?;
+        self.log_change(
+            "del",
+            "Delete pair events on date",
+            &format!("Deleted {} events for date={} pair={}", rows, date, pair),
+            before,
+        )?;
+        overrides.record(&self.conn, self.clock.as_ref(), "del")?;
+        Ok(rows)
+    }
+
+    /// Delete every event and work session of a day.
+    /// Returns `(deleted events, deleted work_sessions)`.
+    pub fn delete_day(&mut self, date: &str) -> Result<(usize, usize)> {
+        self.atomically(|tl| tl.do_delete_day(date))
+    }
+
+    fn do_delete_day(&mut self, date: &str) -> Result<(usize, usize)> {
+        validate_date(date)?;
+        let overrides = lock::guard(&self.conn, &[date], self.force)?;
+        let before = DayState::capture(&self.conn, &[date])?;
+        let ev_rows = db::delete_events_by_date(&self.conn, date)?;
+        let ws_rows = db::delete_sessions_by_date(&self.conn, date)?;
+        self.log_change(
+            "del",
+            "Delete all events and sessions for date",
+            &format!(
+                "Deleted date={} events={} work_sessions={}",
+                date, ev_rows, ws_rows
+            ),
+            before,
+        )?;
+        overrides.record(&self.conn, self.clock.as_ref(), "del")?;
+        Ok((ev_rows, ws_rows))
+    }
+
+    /// Revert the last `steps` add/edit/del operations (see [`crate::undo`]).
+    pub fn undo(&mut self, steps: usize) -> Result<Vec<Reverted>> {
+        undo::undo(&mut self.conn, self.clock.as_ref(), steps, self.force)
+    }
+
+    /// Re-apply the last `steps` undone operations.
+    pub fn redo(&mut self, steps: usize) -> Result<Vec<Reverted>> {
+        undo::redo(&mut self.conn, self.clock.as_ref(), steps, self.force)
+    }
+
+    /// Events, pairs and computed session figures of a single day.
+    pub fn day_summary(&self, date: &str) -> Result<DaySummary> {
+        validate_date(date)?;
+        let events = compute_event_pairs(&db::list_events_by_date(&self.conn, date)?);
+        let pairs = compute_event_summaries(&events);
+        let worked_minutes = pairs.iter().map(|p| p.duration_minutes).sum();
+        let session = db::list_sessions_by_date(&self.conn, date)?
+            .into_iter()
+            .next()
+            .map(|s| session_report(s, &self.config));
+
+        Ok(DaySummary {
+            date: date.to_string(),
+            events,
+            pairs,
+            worked_minutes,
+            session,
+        })
+    }
+
+    /// Sessions of a period (`YYYY`, `YYYY-MM` or None for everything), optionally filtered by position.
+    pub fn period_report(&self, period: Option<&str>, pos: Option<&str>) -> Result<PeriodReport> {
+        let pos = pos.map(|p| p.trim().to_uppercase());
+        let sessions: Vec<SessionReport> = db::list_sessions(&self.conn, period, pos.as_deref())?
+            .into_iter()
+            .map(|s| session_report(s, &self.config))
+            .collect();
+        let total_worked_minutes = sessions.iter().filter_map(|s| s.worked_minutes).sum();
+        let total_surplus_minutes = sessions.iter().filter_map(|s| s.surplus_minutes).sum();
+
+        Ok(PeriodReport {
+            period: period.map(str::to_string),
+            sessions,
+            total_worked_minutes,
+            total_surplus_minutes,
+        })
+    }
+
+    /// Worked vs expected minutes of a period; only days with a computed surplus are counted.
+    pub fn balance(&self, period: Option<&str>) -> Result<Balance> {
+        let report = self.period_report(period, None)?;
+        let work_minutes = utils::parse_work_duration_to_minutes(&self.config.min_work_duration);
+        let counted: Vec<&SessionReport> = report
+            .sessions
+            .iter()
+            .filter(|s| s.surplus_minutes.is_some())
+            .collect();
+
+        Ok(Balance {
+            period: report.period.clone(),
+            days: counted.len(),
+            worked_minutes: counted.iter().filter_map(|s| s.worked_minutes).sum(),
+            expected_minutes: work_minutes * counted.len() as i64,
+            surplus_minutes: counted.iter().filter_map(|s| s.surplus_minutes).sum(),
+        })
+    }
+
+    /// Write the audit row of a mutation with the before/after snapshot of the touched date.
+    fn log_change(
+        &self,
+        operation: &str,
+        target: &str,
+        message: &str,
+        before: DayState,
+    ) -> Result<()> {
+        let dates: Vec<&str> = before.dates.iter().map(String::as_str).collect();
+        let after = DayState::capture(&self.conn, &dates)?;
+        let snapshot = Snapshot::between(before, after);
+        undo::record(
+            &self.conn,
+            self.clock.as_ref(),
+            operation,
+            target,
+            message,
+            &snapshot,
+        )
+    }
+
+    /// Run `change` in a savepoint: when it fails, the database (including the lock clearances
+    /// taken by [`lock::guard`]) is left as it was.
+    fn atomically<T>(&mut self, change: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.conn.execute_batch("SAVEPOINT timelog_change")?;
+        match change(self) {
+            Ok(value) => {
+                self.conn.execute_batch("RELEASE timelog_change")?;
+                Ok(value)
+            }
+            Err(e) => {
+                self.conn
+                    .execute_batch("ROLLBACK TO timelog_change; RELEASE timelog_change")?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Recompute the aggregated position of a day from its events and store it in work_sessions.
+    fn sync_position(&self, date: &str) -> Result<()> {
+        if let Some(agg) = db::aggregate_position_from_events(&self.conn, date)? {
+            db::force_set_position(&self.conn, date, &agg)?;
+        }
+        Ok(())
+    }
+
+    /// A lunch break is either 0 (none) or within the configured `min_duration_lunch_break` and
+    /// `max_duration_lunch_break`.
+    fn validate_lunch(&self, lunch: i32) -> Result<()> {
+        let min = self.config.min_duration_lunch_break;
+        let max = self.config.max_duration_lunch_break;
+        if lunch == 0 || (min..=max).contains(&lunch) {
+            Ok(())
+        } else {
+            Err(ValidationError::Lunch { lunch, min, max }.into())
+        }
+    }
+}
+
+/// Compute expected exit, effective lunch, worked minutes and surplus of a legacy session,
+/// using the same rules as `rtimelogger list`.
+pub fn session_report(session: WorkSession, config: &Config) -> SessionReport {
+    let work_minutes = utils::parse_work_duration_to_minutes(&config.min_work_duration);
+    let has_start = !session.start.trim().is_empty();
+    let has_end = !session.end.trim().is_empty();
+
+    let mut report = SessionReport {
+        expected_exit: None,
+        effective_lunch: session.lunch,
+        worked_minutes: None,
+        surplus_minutes: None,
+        session,
+    };
+    if !has_start {
+        return report;
+    }
+    let s = &report.session;
+    if !has_end {
+        let expected = logic::calculate_expected_exit(&s.start, work_minutes, s.lunch, config);
+        report.expected_exit = Some(expected.format("%H:%M").to_string());
+        return report;
+    }
+
+    let pos_char = s.position.chars().next().unwrap_or('O');
+    let effective_lunch =
+        logic::effective_lunch_minutes(s.lunch, &s.start, &s.end, pos_char, config);
+    let (Ok(start_t), Ok(end_t)) = (
+        NaiveTime::parse_from_str(&s.start, "%H:%M"),
+        NaiveTime::parse_from_str(&s.end, "%H:%M"),
+    ) else {
+        return report;
+    };
+
+    if logic::crosses_lunch_window(&s.start, &s.end) && effective_lunch > 0 {
+        let expected =
+            logic::calculate_expected_exit(&s.start, work_minutes, effective_lunch, config);
+        let surplus =
+            logic::calculate_surplus(&s.start, effective_lunch, &s.end, work_minutes, config);
+        report.expected_exit = Some(expected.format("%H:%M").to_string());
+        report.effective_lunch = effective_lunch;
+        report.worked_minutes = Some((end_t - start_t).num_minutes() - effective_lunch as i64);
+        report.surplus_minutes = Some(surplus.num_minutes());
+    } else {
+        report.effective_lunch = 0;
+        report.worked_minutes = Some((end_t - start_t).num_minutes());
+    }
+    report
+}
+
+/// Find the `in` and `out` events of a given pair.
+fn pair_events(events: &[Event], pair: usize) -> (Option<Event>, Option<Event>) {
+    let mut in_event = None;
+    let mut out_event = None;
+    for ew in compute_event_pairs(events)
+        .into_iter()
+        .filter(|e| e.pair == pair)
+    {
+        if ew.event.is_in() {
+            in_event = Some(ew.event);
+        } else if ew.event.is_out() {
+            out_event = Some(ew.event);
+        }
+    }
+    (in_event, out_event)
+}
+
+/// Error of a pair missing from `date` (validation, exit code 2).
+pub fn pair_not_found(date: &str, pair: usize) -> Error {
+    Error::invalid(format!("Pair {} not found for date {}", pair, date))
+}
+
+fn validate_date(date: &str) -> Result<()> {
+    utils::iso2date(date)
+        .map(|_| ())
+        .map_err(|_| ValidationError::Date(date.to_string()).into())
+}
+
+/// Validate a time and return it normalized as `HH:MM`.
+fn validate_time(time: &str) -> Result<String> {
+    db::normalize_time(time)
+        .map(|(t, _)| t)
+        .ok_or_else(|| ValidationError::Time(time.to_string()).into())
+}
+
+fn normalize_position(pos: Option<&str>, allowed: &[&str]) -> Result<Option<String>> {
+    match pos.map(|p| p.trim().to_uppercase()) {
+        Some(p) if !allowed.contains(&p.as_str()) => Err(ValidationError::Position(p).into()),
+        other => Ok(other),
+    }
+}
@@ -0,0 +1,216 @@
+//! Printable monthly timesheet (`rtimelogger timesheet`).
+//!
+//! One A4 page per month: the employee details from the `employee:` config section, a row for
+//! every calendar day (weekends and holidays shaded), the monthly totals and signature lines for
+//! the employee and the manager. Worked time and surplus follow the same rules as `list`.
+
+use crate::cli::Commands;
+use crate::clock::Clock;
+use crate::config::{Config, EmployeeInfo};
+use crate::db;
+use crate::error::{Error, Result, ValidationError};
+use crate::export::{ExportFilter, ensure_writable, format_minutes, pdf_summary};
+use crate::lock::month_range;
+use crate::logic::month_name;
+use crate::pdf_manager::PdfManager;
+use crate::timelog::{SessionReport, session_report};
+use crate::utils::{Confirm, date2iso, describe_position, weekday_str};
+use chrono::{Datelike, NaiveDate, Weekday};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::Path;
+
+const HEADERS: [&str; 8] = [
+    "Date", "Day", "Position", "In", "Out", "Lunch", "Worked", "Surplus",
+];
+
+/// One calendar day of a timesheet; days without a session have empty fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimesheetDay {
+    pub date: String,
+    pub weekday: String,
+    /// Saturday or Sunday
+    pub weekend: bool,
+    pub position: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    /// Lunch break counted for the day (minutes)
+    pub lunch: Option<i32>,
+    pub worked_minutes: Option<i64>,
+    pub surplus_minutes: Option<i64>,
+}
+
+/// Months covered by `period`: `YYYY-MM`, or `YYYY` for all twelve months.
+pub fn months(period: &str) -> Result<Vec<String>> {
+    let period = period.trim();
+    if period.len() == 4 && period.chars().all(|c| c.is_ascii_digit()) {
+        return Ok((1..=12).map(|m| format!("{}-{:02}", period, m)).collect());
+    }
+    month_range(period)?;
+    Ok(vec![period.to_string()])
+}
+
+/// Every calendar day of a `YYYY-MM` month, with its session when there is one.
+pub fn month_days(conn: &Connection, config: &Config, month: &str) -> Result<Vec<TimesheetDay>> {
+    let (start, end) = month_range(month)?;
+    let parse = |d: &str| {
+        NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|e| Error::invalid(format!("Invalid date {}: {}", d, e)))
+    };
+    let (first, last) = (parse(&start)?, parse(&end)?);
+    let reports: Vec<SessionReport> = db::list_sessions(conn, Some(month), None)?
+        .into_iter()
+        .map(|s| session_report(s, config))
+        .collect();
+
+    let non_empty = |s: &str| (!s.trim().is_empty()).then(|| s.to_string());
+    let days = first
+        .iter_days()
+        .take_while(|d| *d <= last)
+        .map(|d| {
+            let date = date2iso(&d);
+            let report = reports.iter().find(|r| r.session.date == date);
+            TimesheetDay {
+                weekday: weekday_str(&date, 'm'),
+                weekend: matches!(d.weekday(), Weekday::Sat | Weekday::Sun),
+                position: report.map(|r| r.session.position.clone()),
+                start: report.and_then(|r| non_empty(&r.session.start)),
+                end: report.and_then(|r| non_empty(&r.session.end)),
+                lunch: report
+                    .filter(|r| r.worked_minutes.is_some())
+                    .map(|r| r.effective_lunch),
+                worked_minutes: report.and_then(|r| r.worked_minutes),
+                surplus_minutes: report.and_then(|r| r.surplus_minutes),
+                date,
+            }
+        })
+        .collect();
+    Ok(days)
+}
+
+/// Lay out the timesheets of `months`, one page each.
+pub fn render(conn: &Connection, config: &Config, months: &[String]) -> Result<PdfManager> {
+    let mut pdf = PdfManager::new();
+    // 31 days, the header block, the totals and the signatures fit on one A4 page
+    pdf.set_row_height(14.0);
+
+    for month in months {
+        let days = month_days(conn, config, month)?;
+        let rows: Vec<Vec<String>> = days.iter().map(day_row).collect();
+        let shaded: Vec<bool> = days
+            .iter()
+            .map(|d| d.weekend || d.position.as_deref() == Some("H"))
+            .collect();
+
+        pdf.start_page();
+        pdf.write_heading(&format!("Timesheet – {}", month_label(month)));
+        pdf.write_fields(&header_fields(&config.employee, month));
+        pdf.write_table_shaded(&HEADERS, &rows, &shaded);
+        let totals = pdf_summary(
+            conn,
+            config,
+            &ExportFilter::range(Some(month_range(month)?)),
+        )?;
+        pdf.write_summary("Monthly totals", &totals);
+        pdf.write_signatures(&signature_labels(&config.employee));
+    }
+    Ok(pdf)
+}
+
+/// Handle `timesheet <PERIOD> --file <FILE> [--force]`.
+pub fn handle_timesheet(
+    cmd: &Commands,
+    conn: &Connection,
+    config: &Config,
+    clock: &dyn Clock,
+    mode: Confirm,
+) -> Result<()> {
+    if let Commands::Timesheet {
+        period,
+        file,
+        force,
+    } = cmd
+    {
+        // "this-month", "last-year", ... resolve to a YYYY-MM or YYYY period
+        let resolved = crate::utils::parse_date_range(period, clock.today())
+            .map(|r| r.period())
+            .ok()
+            .filter(|p| p.len() == 4 || p.len() == 7)
+            .ok_or_else(|| ValidationError::Period(period.trim().to_string()))?;
+        let months = months(&resolved)?;
+        let path = Path::new(file);
+        if !path.is_absolute() {
+            return Err(Error::invalid(format!(
+                "Output file path must be absolute: {}",
+                file
+            )));
+        }
+        ensure_writable(path, *force, mode)?;
+
+        let pdf = render(conn, config, &months)?;
+        let pages = pdf.page_count();
+        pdf.save(path)?;
+        crate::progress!(
+            "✅ Timesheet written to {} ({} page(s))",
+            path.display(),
+            pages
+        );
+    }
+    Ok(())
+}
+
+fn month_label(month: &str) -> String {
+    format!("{} {}", month_name(&month[5..7]), &month[..4])
+}
+
+fn header_fields(employee: &EmployeeInfo, month: &str) -> Vec<(String, String)> {
+    let mut fields = vec![
+        ("Employee".to_string(), employee.name.clone()),
+        ("Employee ID".to_string(), employee.id.clone()),
+    ];
+    if !employee.company.is_empty() {
+        fields.push(("Company".to_string(), employee.company.clone()));
+    }
+    fields.push(("Month".to_string(), month_label(month)));
+    fields
+}
+
+fn signature_labels(employee: &EmployeeInfo) -> Vec<String> {
+    let label = |role: &str, name: &str| {
+        if name.is_empty() {
+            format!("{} signature", role)
+        } else {
+            format!("{} signature ({})", role, name)
+        }
+    };
+    vec![
+        label("Employee", &employee.name),
+        label("Manager", &employee.manager),
+    ]
+}
+
+fn day_row(day: &TimesheetDay) -> Vec<String> {
+    let position = day
+        .position
+        .as_deref()
+        .map(|p| describe_position(p).0)
+        .unwrap_or_default();
+    let surplus = day.surplus_minutes.map(|m| {
+        let formatted = format_minutes(m);
+        if m > 0 {
+            format!("+{}", formatted)
+        } else {
+            formatted
+        }
+    });
+    vec![
+        day.date.clone(),
+        day.weekday.clone(),
+        position,
+        day.start.clone().unwrap_or_default(),
+        day.end.clone().unwrap_or_default(),
+        day.lunch.map(|l| l.to_string()).unwrap_or_default(),
+        day.worked_minutes.map(format_minutes).unwrap_or_default(),
+        surplus.unwrap_or_default(),
+    ]
+}
@@ -0,0 +1,381 @@
+//! Import of worked time from other time trackers (`rtimelogger import --format timewarrior|toggl|clockify`)
+//! and export to Timewarrior (`rtimelogger export --format timew`).
+//!
+//! Supported inputs are the JSON written by `timew export` and the detailed CSV reports of Toggl
+//! Track and Clockify. Each tracked interval becomes an `in`/`out` pair whose events carry the tool
+//! name as `source` and the projects and tags as `meta` (a small JSON object).
+//!
+//! Intervals are first cut at midnight (the part before it ends at 23:59), then the intervals of a
+//! day are turned into pairs: overlapping or touching intervals are joined, and a break that starts
+//! after 12:00 and ends by 14:30 (within the minimum and maximum lunch break) joins the two
+//! intervals into a single pair with that break as its lunch, as if it had been entered with `add`.
+//!
+//! The export writes the lines of a Timewarrior data file (`~/.timewarrior/data/YYYY-MM.data`): one
+//! `inc <start> - <end> # <tags>` line per matched pair, in UTC, tagged `rtimelogger`, `pos:<code>`
+//! and `lunch:<minutes>` when the pair has a lunch break. Holidays and unmatched pairs are skipped, and so
+//! are events imported from Timewarrior, which it already has.
+
+use crate::db::{self, Event};
+use crate::error::{Error, Result};
+use crate::ics::ImportAction;
+use crate::lock;
+use crate::logic::{compute_event_pairs, compute_event_summaries};
+use chrono::{Days, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+
+/// Break window in which a gap between two intervals is taken as the lunch break
+const LUNCH_FROM: u32 = 12 * 60;
+const LUNCH_UNTIL: u32 = 14 * 60 + 30;
+/// Last minute of a day, where intervals crossing midnight are cut
+const END_OF_DAY: u32 = 23 * 60 + 59;
+
+/// A time tracker whose exports can be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackerFormat {
+    /// `timew export` (JSON, UTC timestamps)
+    Timewarrior,
+    /// Toggl Track detailed report (CSV)
+    Toggl,
+    /// Clockify detailed report (CSV)
+    Clockify,
+}
+
+impl TrackerFormat {
+    /// The format named on the command line (`timewarrior`/`timew`, `toggl`, `clockify`).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "timewarrior" | "timew" => Some(TrackerFormat::Timewarrior),
+            "toggl" => Some(TrackerFormat::Toggl),
+            "clockify" => Some(TrackerFormat::Clockify),
+            _ => None,
+        }
+    }
+
+    /// Value of the `source` column of the imported events.
+    pub fn source(&self) -> &'static str {
+        match self {
+            TrackerFormat::Timewarrior => "timewarrior",
+            TrackerFormat::Toggl => "toggl",
+            TrackerFormat::Clockify => "clockify",
+        }
+    }
+}
+
+/// A tracked interval, in local time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub projects: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+/// Intervals read from an export, and the open ones left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedIntervals {
+    pub intervals: Vec<Interval>,
+    /// Start (as exported) of each interval still being tracked, skipped
+    pub open: Vec<String>,
+}
+
+/// Parse an export of `format`. Timewarrior stores UTC, converted to the time zone `tz`.
+pub fn parse_intervals<Tz: TimeZone>(
+    format: TrackerFormat,
+    text: &str,
+    tz: &Tz,
+) -> Result<ParsedIntervals> {
+    match format {
+        TrackerFormat::Timewarrior => parse_timewarrior(text, tz),
+        TrackerFormat::Toggl | TrackerFormat::Clockify => Ok(ParsedIntervals {
+            intervals: parse_report_csv(text)?,
+            open: Vec::new(),
+        }),
+    }
+}
+
+#[derive(Deserialize)]
+struct TimewInterval {
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Intervals of a `timew export`; the open interval (still tracking) is skipped and reported in
+/// `ParsedIntervals::open`.
+pub fn parse_timewarrior<Tz: TimeZone>(json: &str, tz: &Tz) -> Result<ParsedIntervals> {
+    let entries: Vec<TimewInterval> = serde_json::from_str(json)
+        .map_err(|e| Error::invalid(format!("invalid Timewarrior export: {e}")))?;
+    let local = |value: &str| {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ")
+            .map(|utc| Utc.from_utc_datetime(&utc).with_timezone(tz).naive_local())
+            .map_err(|_| Error::invalid(format!("invalid Timewarrior timestamp: {value}")))
+    };
+    let mut parsed = ParsedIntervals::default();
+    for entry in entries {
+        let Some(end) = entry.end.as_deref() else {
+            parsed.open.push(entry.start);
+            continue;
+        };
+        parsed.intervals.push(Interval {
+            start: local(&entry.start)?,
+            end: local(end)?,
+            projects: Vec::new(),
+            tags: entry.tags,
+        });
+    }
+    Ok(parsed)
+}
+
+/// Intervals of a Toggl Track or Clockify detailed report: the `Start date`, `Start time`,
+/// `End date` and `End time` columns (any case) are required, `Project` and `Tags` are optional.
+pub fn parse_report_csv(text: &str) -> Result<Vec<Interval>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(text.trim_start_matches('\u{feff}').as_bytes());
+    let headers: Vec<String> = rdr
+        .headers()
+        .map_err(|e| Error::invalid(format!("invalid CSV report: {e}")))?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (Some(start_date), Some(start_time), Some(end_date), Some(end_time)) = (
+        column("start date"),
+        column("start time"),
+        column("end date"),
+        column("end time"),
+    ) else {
+        return Err(Error::invalid(
+            "CSV report needs the columns: Start date, Start time, End date, End time",
+        ));
+    };
+    let (project, tags) = (column("project"), column("tags"));
+
+    let mut intervals = Vec::new();
+    for (i, record) in rdr.records().enumerate() {
+        let record = record.map_err(|e| Error::invalid(format!("invalid CSV report: {e}")))?;
+        let field = |idx: usize| record.get(idx).unwrap_or("").trim();
+        let at = |date_idx: usize, time_idx: usize| {
+            parse_date(field(date_idx))
+                .zip(parse_time(field(time_idx)))
+                .map(|(d, t)| d.and_time(t))
+                .ok_or_else(|| {
+                    Error::invalid(format!(
+                        "CSV report line {}: invalid date/time '{} {}'",
+                        i + 2,
+                        field(date_idx),
+                        field(time_idx)
+                    ))
+                })
+        };
+        intervals.push(Interval {
+            start: at(start_date, start_time)?,
+            end: at(end_date, end_time)?,
+            projects: project
+                .map(field)
+                .filter(|p| !p.is_empty())
+                .map(str::to_string)
+                .into_iter()
+                .collect(),
+            tags: tags
+                .map(field)
+                .unwrap_or("")
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect(),
+        });
+    }
+    Ok(intervals)
+}
+
+/// Dates as written by the trackers depending on the user's settings.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    ["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y", "%Y/%m/%d"]
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(value, f).ok())
+}
+
+/// Times in 24-hour or 12-hour (`AM`/`PM`) notation, with or without seconds.
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    ["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"]
+        .iter()
+        .find_map(|f| NaiveTime::parse_from_str(value, f).ok())
+}
+
+/// Projects and tags stored in the `meta` column of imported events.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PairMeta {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl PairMeta {
+    fn merge(&mut self, projects: &[String], tags: &[String]) {
+        for (list, values) in [(&mut self.projects, projects), (&mut self.tags, tags)] {
+            for v in values {
+                if !list.contains(v) {
+                    list.push(v.clone());
+                }
+            }
+        }
+    }
+
+    /// JSON text of the `meta` column (empty when there is nothing to record).
+    pub fn to_meta(&self) -> String {
+        if self.projects.is_empty() && self.tags.is_empty() {
+            String::new()
+        } else {
+            serde_json::to_string(self).unwrap_or_default()
+        }
+    }
+}
+
+/// One pair to import.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrackerPair {
+    pub date: String,
+    pub start: String,
+    pub end: String,
+    pub lunch: i32,
+    pub meta: PairMeta,
+    pub action: ImportAction,
+}
+
+/// Pairs of `intervals` (see the module docs), ordered by date and start.
+/// `lunch` is the range of breaks taken as a lunch break.
+pub fn day_pairs(intervals: &[Interval], lunch: RangeInclusive<i32>) -> Vec<TrackerPair> {
+    let mut pieces: Vec<(NaiveDate, u32, u32, &Interval)> = Vec::new();
+    for iv in intervals {
+        let mut start = iv.start;
+        while start.date() < iv.end.date() {
+            let from = minutes_of(start);
+            if from < END_OF_DAY {
+                pieces.push((start.date(), from, END_OF_DAY, iv));
+            }
+            start = start
+                .date()
+                .checked_add_days(Days::new(1))
+                .expect("date in range")
+                .and_time(NaiveTime::MIN);
+        }
+        if iv.end > start {
+            pieces.push((start.date(), minutes_of(start), minutes_of(iv.end), iv));
+        }
+    }
+    pieces.sort_by_key(|(date, from, to, _)| (*date, *from, *to));
+
+    let mut pairs: Vec<(NaiveDate, u32, u32, i32, PairMeta)> = Vec::new();
+    for (date, from, to, iv) in pieces {
+        if from >= to {
+            continue;
+        }
+        if let Some(last) = pairs.last_mut().filter(|p| p.0 == date) {
+            let gap = from as i64 - last.2 as i64;
+            let lunch_break = last.3 == 0
+                && last.2 >= LUNCH_FROM
+                && from <= LUNCH_UNTIL
+                && lunch.contains(&(gap as i32));
+            if gap <= 0 || lunch_break {
+                if gap > 0 {
+                    last.3 = gap as i32;
+                }
+                last.2 = last.2.max(to);
+                last.4.merge(&iv.projects, &iv.tags);
+                continue;
+            }
+        }
+        let mut meta = PairMeta::default();
+        meta.merge(&iv.projects, &iv.tags);
+        pairs.push((date, from, to, 0, meta));
+    }
+
+    pairs
+        .into_iter()
+        .map(|(date, from, to, lunch, meta)| TrackerPair {
+            date: date.format("%Y-%m-%d").to_string(),
+            start: hhmm(from),
+            end: hhmm(to),
+            lunch,
+            meta,
+            action: ImportAction::Create,
+        })
+        .collect()
+}
+
+/// Pairs that importing `intervals` would create, restricted to `range` (inclusive dates), with
+/// what happens to each: already recorded (an `in` at the start or an `out` at the end exists),
+/// locked (unless `force` is set), or created.
+pub fn plan_import(
+    conn: &Connection,
+    intervals: &[Interval],
+    range: Option<&(String, String)>,
+    lunch: RangeInclusive<i32>,
+    force: bool,
+) -> Result<Vec<TrackerPair>> {
+    let mut plan = Vec::new();
+    for mut pair in day_pairs(intervals, lunch) {
+        if let Some((from, to)) = range
+            && (pair.date.as_str() < from.as_str() || pair.date.as_str() > to.as_str())
+        {
+            continue;
+        }
+        let events = db::list_events_by_date(conn, &pair.date)?;
+        pair.action = if events.iter().any(|e| {
+            (e.kind == "in" && e.time == pair.start) || (e.kind == "out" && e.time == pair.end)
+        }) {
+            ImportAction::Exists
+        } else if !force && lock::lock_for_date(conn, &pair.date)?.is_some() {
+            ImportAction::Locked
+        } else {
+            ImportAction::Create
+        };
+        plan.push(pair);
+    }
+    Ok(plan)
+}
+
+fn minutes_of(at: NaiveDateTime) -> u32 {
+    at.hour() * 60 + at.minute()
+}
+
+fn hhmm(minutes: u32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Timewarrior data file lines for the matched pairs of `events` (local times in `tz`).
+pub fn timew_data<Tz: TimeZone>(events: &[Event], tz: &Tz) -> String {
+    let utc = |date: &str, time: &str| {
+        let local =
+            NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y-%m-%d %H:%M").ok()?;
+        let at = tz.from_local_datetime(&local).earliest()?;
+        Some(at.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string())
+    };
+    let own: Vec<Event> = events
+        .iter()
+        .filter(|e| e.source != TrackerFormat::Timewarrior.source())
+        .cloned()
+        .collect();
+    let mut out = String::new();
+    for pair in compute_event_summaries(&compute_event_pairs(&own)) {
+        if pair.unmatched || pair.position == "H" {
+            continue;
+        }
+        let (Some(start), Some(end)) = (utc(&pair.date, &pair.start), utc(&pair.date, &pair.end))
+        else {
+            continue;
+        };
+        let mut tags = vec!["rtimelogger".to_string(), format!("pos:{}", pair.position)];
+        if pair.lunch_minutes > 0 {
+            tags.push(format!("lunch:{}", pair.lunch_minutes));
+        }
+        out.push_str(&format!("inc {} - {} # {}\n", start, end, tags.join(" ")));
+    }
+    out
+}
@@ -0,0 +1,341 @@
+//! Interactive full-screen interface (`rtimelogger tui`).
+//!
+//! A month calendar on the left, the pairs of the selected day on the right and a surplus footer.
+//! Pairs can be edited (in/out time, lunch, position) and deleted in place; every change goes
+//! through [`Timelog`] and therefore through the same `db` functions used by `add --edit` and `del`.
+//!
+//! [`App`] holds the state and reacts to key events without touching the terminal, so it can be
+//! driven from tests; [`run`] owns the terminal and the event loop.
+
+mod view;
+
+use crate::error::{Error, Result};
+use crate::logic::PairSummary;
+use crate::timelog::{DayEntry, DaySummary, PeriodReport, Timelog};
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::io::IsTerminal;
+
+pub use view::render;
+
+/// Pane receiving navigation keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Calendar,
+    Day,
+}
+
+/// Labels of the edit form fields, in display order.
+pub const EDIT_FIELDS: [&str; 4] = ["In", "Out", "Lunch", "Position"];
+
+/// Inline edit of one pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditForm {
+    pub pair: usize,
+    /// Current values, indexed like [`EDIT_FIELDS`]
+    pub values: [String; 4],
+    /// Index of the field being typed into
+    pub field: usize,
+    original: [String; 4],
+}
+
+impl EditForm {
+    fn new(p: &PairSummary) -> Self {
+        let values = [
+            p.start.clone(),
+            p.end.clone(),
+            p.lunch_minutes.to_string(),
+            p.position.clone(),
+        ];
+        Self {
+            pair: p.pair,
+            original: values.clone(),
+            values,
+            field: 0,
+        }
+    }
+
+    /// Only the fields that were changed (and are not blank) end up in the entry.
+    fn to_entry(&self) -> Result<DayEntry> {
+        let changed = |i: usize| {
+            let v = self.values[i].trim();
+            (!v.is_empty() && v != self.original[i].trim()).then(|| v.to_string())
+        };
+        let lunch = match changed(2) {
+            Some(l) => Some(
+                l.parse::<i32>()
+                    .map_err(|_| Error::invalid(format!("Invalid lunch: {} (minutes)", l)))?,
+            ),
+            None => None,
+        };
+        Ok(DayEntry {
+            start: changed(0),
+            end: changed(1),
+            lunch,
+            position: changed(3),
+        })
+    }
+}
+
+/// What the keyboard currently drives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Browse,
+    Edit(EditForm),
+    /// Waiting for y/n before deleting the given pair
+    ConfirmDelete(usize),
+}
+
+/// State of the TUI.
+pub struct App {
+    pub selected: NaiveDate,
+    pub today: NaiveDate,
+    pub focus: Focus,
+    /// Index into `day.pairs` of the highlighted pair
+    pub pair_index: usize,
+    pub mode: Mode,
+    /// Last message (result of an action or validation error)
+    pub status: Option<String>,
+    /// Sessions of the selected month
+    pub month: PeriodReport,
+    /// Events and pairs of the selected day
+    pub day: DaySummary,
+    quit: bool,
+}
+
+impl App {
+    /// Start on today's date (as given by the clock of `tl`).
+    pub fn new(tl: &Timelog) -> Result<Self> {
+        let today = tl.clock().today();
+        let ds = today.format("%Y-%m-%d").to_string();
+        Ok(Self {
+            selected: today,
+            today,
+            focus: Focus::Calendar,
+            pair_index: 0,
+            mode: Mode::Browse,
+            status: None,
+            month: tl.period_report(Some(&today.format("%Y-%m").to_string()), None)?,
+            day: tl.day_summary(&ds)?,
+            quit: false,
+        })
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    pub fn selected_date(&self) -> String {
+        self.selected.format("%Y-%m-%d").to_string()
+    }
+
+    /// `YYYY-MM` of the selected date.
+    pub fn selected_month(&self) -> String {
+        self.selected.format("%Y-%m").to_string()
+    }
+
+    /// The highlighted pair of the selected day, if any.
+    pub fn selected_pair(&self) -> Option<&PairSummary> {
+        self.day.pairs.get(self.pair_index)
+    }
+
+    /// Surplus of the selected day, when the day is complete.
+    pub fn day_surplus(&self) -> Option<i64> {
+        self.day.session.as_ref().and_then(|s| s.surplus_minutes)
+    }
+
+    /// Re-read the selected month and day from the database.
+    pub fn reload(&mut self, tl: &Timelog) -> Result<()> {
+        self.month = tl.period_report(Some(&self.selected_month()), None)?;
+        self.day = tl.day_summary(&self.selected_date())?;
+        if self.pair_index >= self.day.pairs.len() {
+            self.pair_index = self.day.pairs.len().saturating_sub(1);
+        }
+        Ok(())
+    }
+
+    /// React to a key press. Validation errors are shown in the status line; other errors
+    /// (database, I/O) are returned.
+    pub fn handle_key(&mut self, tl: &mut Timelog, key: KeyEvent) -> Result<()> {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return Ok(());
+        }
+        let result = match self.mode.clone() {
+            Mode::Browse => self.handle_browse_key(tl, key),
+            Mode::Edit(form) => self.handle_edit_key(tl, form, key),
+            Mode::ConfirmDelete(pair) => self.handle_confirm_key(tl, pair, key),
+        };
+        match result {
+            Err(e @ Error::Validation(_)) => {
+                self.status = Some(format!("❌ {}", e));
+                Ok(())
+            }
+            other => other,
+        }
+    }
+
+    fn handle_browse_key(&mut self, tl: &mut Timelog, key: KeyEvent) -> Result<()> {
+        match (self.focus, key.code) {
+            (_, KeyCode::Char('q')) => self.quit = true,
+            (_, KeyCode::Tab) | (_, KeyCode::BackTab) => {
+                self.focus = match self.focus {
+                    Focus::Calendar => Focus::Day,
+                    Focus::Day => Focus::Calendar,
+                }
+            }
+            (_, KeyCode::PageUp) | (_, KeyCode::Char('[')) => {
+                self.select(tl, self.selected - Months::new(1))?
+            }
+            (_, KeyCode::PageDown) | (_, KeyCode::Char(']')) => {
+                self.select(tl, self.selected + Months::new(1))?
+            }
+            (_, KeyCode::Char('t')) => self.select(tl, self.today)?,
+
+            (Focus::Calendar, KeyCode::Esc) => self.quit = true,
+            (Focus::Calendar, KeyCode::Left | KeyCode::Char('h')) => {
+                self.select(tl, self.selected - Duration::days(1))?
+            }
+            (Focus::Calendar, KeyCode::Right | KeyCode::Char('l')) => {
+                self.select(tl, self.selected + Duration::days(1))?
+            }
+            (Focus::Calendar, KeyCode::Up | KeyCode::Char('k')) => {
+                self.select(tl, self.selected - Duration::days(7))?
+            }
+            (Focus::Calendar, KeyCode::Down | KeyCode::Char('j')) => {
+                self.select(tl, self.selected + Duration::days(7))?
+            }
+            (Focus::Calendar, KeyCode::Enter) => self.focus = Focus::Day,
+
+            (Focus::Day, KeyCode::Esc) => self.focus = Focus::Calendar,
+            (Focus::Day, KeyCode::Up | KeyCode::Char('k')) => {
+                self.pair_index = self.pair_index.saturating_sub(1)
+            }
+            (Focus::Day, KeyCode::Down | KeyCode::Char('j')) => {
+                self.pair_index = (self.pair_index + 1).min(self.day.pairs.len().saturating_sub(1))
+            }
+            (Focus::Day, KeyCode::Enter | KeyCode::Char('e')) => match self.selected_pair() {
+                Some(p) => self.mode = Mode::Edit(EditForm::new(p)),
+                None => self.status = Some("No pair to edit on this day".to_string()),
+            },
+            (Focus::Day, KeyCode::Delete | KeyCode::Char('d')) => match self.selected_pair() {
+                Some(p) => self.mode = Mode::ConfirmDelete(p.pair),
+                None => self.status = Some("No pair to delete on this day".to_string()),
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_edit_key(
+        &mut self,
+        tl: &mut Timelog,
+        mut form: EditForm,
+        key: KeyEvent,
+    ) -> Result<()> {
+        let last = EDIT_FIELDS.len() - 1;
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = Mode::Browse;
+                self.status = Some("Edit cancelled".to_string());
+                return Ok(());
+            }
+            KeyCode::Enter => {
+                // Leave the form open on errors so the user can fix the value
+                let entry = form.to_entry()?;
+                if entry.start.is_none()
+                    && entry.end.is_none()
+                    && entry.lunch.is_none()
+                    && entry.position.is_none()
+                {
+                    self.mode = Mode::Browse;
+                    self.status = Some("Nothing changed".to_string());
+                    return Ok(());
+                }
+                let edited = tl.edit_pair(&self.selected_date(), form.pair, &entry)?;
+                self.mode = Mode::Browse;
+                self.status = Some(format!(
+                    "✅ Pair {} updated: {}",
+                    edited.pair,
+                    edited.changes.join(", ")
+                ));
+                return self.reload(tl);
+            }
+            KeyCode::Tab | KeyCode::Down => form.field = (form.field + 1) % EDIT_FIELDS.len(),
+            KeyCode::BackTab | KeyCode::Up => {
+                form.field = if form.field == 0 {
+                    last
+                } else {
+                    form.field - 1
+                }
+            }
+            KeyCode::Backspace => {
+                form.values[form.field].pop();
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                let c = if form.field == 3 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                };
+                form.values[form.field].push(c);
+            }
+            _ => {}
+        }
+        self.mode = Mode::Edit(form);
+        Ok(())
+    }
+
+    fn handle_confirm_key(&mut self, tl: &mut Timelog, pair: usize, key: KeyEvent) -> Result<()> {
+        self.mode = Mode::Browse;
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                let rows = tl.delete_pair(&self.selected_date(), pair)?;
+                self.status = Some(format!("🗑️  Deleted pair {} ({} events)", pair, rows));
+                self.reload(tl)
+            }
+            _ => {
+                self.status = Some("Deletion cancelled".to_string());
+                Ok(())
+            }
+        }
+    }
+
+    fn select(&mut self, tl: &Timelog, date: NaiveDate) -> Result<()> {
+        let month_changed =
+            date.year() != self.selected.year() || date.month() != self.selected.month();
+        self.selected = date;
+        self.pair_index = 0;
+        self.status = None;
+        if month_changed {
+            self.reload(tl)
+        } else {
+            self.day = tl.day_summary(&self.selected_date())?;
+            Ok(())
+        }
+    }
+}
+
+/// Run the TUI until the user quits. The terminal is restored even when an error occurs.
+pub fn run(tl: &mut Timelog) -> Result<()> {
+    if !std::io::stdout().is_terminal() {
+        return Err(Error::invalid(
+            "The tui command requires an interactive terminal",
+        ));
+    }
+    let mut app = App::new(tl)?;
+    let mut terminal = ratatui::try_init()?;
+    let result = (|| -> Result<()> {
+        while !app.should_quit() {
+            terminal.draw(|f| render(f, &app))?;
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                app.handle_key(tl, key)?;
+            }
+        }
+        Ok(())
+    })();
+    ratatui::restore();
+    result
+}
@@ -0,0 +1,273 @@
+//! Rendering of the TUI (calendar, day pane, footer, popups).
+
+use super::{App, EDIT_FIELDS, Focus, Mode};
+use crate::utils::describe_position;
+use chrono::{Datelike, Duration};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState};
+use std::collections::HashMap;
+
+/// Draw the whole interface.
+pub fn render(f: &mut Frame, app: &App) {
+    let [main, footer] =
+        Layout::vertical([Constraint::Min(10), Constraint::Length(4)]).areas(f.area());
+    let [calendar, day] =
+        Layout::horizontal([Constraint::Length(32), Constraint::Min(40)]).areas(main);
+
+    render_calendar(f, app, calendar);
+    render_day(f, app, day);
+    render_footer(f, app, footer);
+
+    match &app.mode {
+        Mode::Browse => {}
+        Mode::Edit(form) => {
+            let area = centered(f.area(), 40, EDIT_FIELDS.len() as u16 + 4);
+            let lines: Vec<Line> = EDIT_FIELDS
+                .iter()
+                .zip(form.values.iter())
+                .enumerate()
+                .map(|(i, (label, value))| {
+                    let style = if i == form.field {
+                        Style::default().add_modifier(Modifier::REVERSED)
+                    } else {
+                        Style::default()
+                    };
+                    let cursor = if i == form.field { "_" } else { "" };
+                    Line::from(vec![
+                        Span::raw(format!("{:>9}: ", label)),
+                        Span::styled(format!("{}{}", value, cursor), style),
+                    ])
+                })
+                .chain(std::iter::once(Line::from(Span::styled(
+                    "Enter save · Tab next · Esc cancel",
+                    Style::default().fg(Color::DarkGray),
+                ))))
+                .collect();
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Paragraph::new(lines).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!(" Edit pair {} ", form.pair)),
+                ),
+                area,
+            );
+        }
+        Mode::ConfirmDelete(pair) => {
+            let area = centered(f.area(), 44, 3);
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Paragraph::new(format!(
+                    "Delete pair {} of {}? (y/n)",
+                    pair,
+                    app.selected_date()
+                ))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::Red))
+                        .title(" Confirm "),
+                ),
+                area,
+            );
+        }
+    }
+}
+
+fn render_calendar(f: &mut Frame, app: &App, area: Rect) {
+    let surplus_by_date: HashMap<&str, Option<i64>> = app
+        .month
+        .sessions
+        .iter()
+        .map(|s| (s.session.date.as_str(), s.surplus_minutes))
+        .collect();
+
+    let first = app.selected.with_day(1).unwrap_or(app.selected);
+    let offset = first.weekday().num_days_from_monday() as i64;
+    let mut day = first - Duration::days(offset);
+
+    let header = Row::new(["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let mut rows = Vec::new();
+    while day.month() == first.month() || day < first {
+        let cells: Vec<Cell> = (0..7)
+            .map(|i| {
+                let d = day + Duration::days(i);
+                if d.month() != first.month() {
+                    return Cell::from("");
+                }
+                let key = d.format("%Y-%m-%d").to_string();
+                let mut style = match surplus_by_date.get(key.as_str()) {
+                    Some(Some(m)) if *m < 0 => Style::default().fg(Color::Red),
+                    Some(Some(_)) => Style::default().fg(Color::Green),
+                    Some(None) => Style::default().fg(Color::Cyan),
+                    None => Style::default(),
+                };
+                if d == app.today {
+                    style = style.add_modifier(Modifier::UNDERLINED | Modifier::BOLD);
+                }
+                if d == app.selected {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Cell::from(format!("{:>2}", d.day())).style(style)
+            })
+            .collect();
+        rows.push(Row::new(cells));
+        day += Duration::days(7);
+    }
+
+    let table = Table::new(rows, [Constraint::Length(3); 7])
+        .header(header)
+        .block(pane_block(
+            format!(" {} ", app.selected.format("%B %Y")),
+            app.focus == Focus::Calendar,
+        ));
+    f.render_widget(table, area);
+}
+
+fn render_day(f: &mut Frame, app: &App, area: Rect) {
+    let header = Row::new(["#", "In", "Out", "Lunch", "Worked", "Position"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = app
+        .day
+        .pairs
+        .iter()
+        .map(|p| {
+            let dash = |s: &str| {
+                if s.is_empty() {
+                    "-".to_string()
+                } else {
+                    s.to_string()
+                }
+            };
+            let (pos_label, _) = describe_position(&p.position);
+            Row::new([
+                p.pair.to_string(),
+                dash(&p.start),
+                dash(&p.end),
+                p.lunch_minutes.to_string(),
+                format_minutes(p.duration_minutes as i64, false),
+                pos_label,
+            ])
+            .style(if p.unmatched {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            })
+        })
+        .collect();
+
+    let title = format!(" {} ({}) ", app.selected_date(), app.selected.format("%A"));
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(if app.focus == Focus::Day {
+        Style::default().add_modifier(Modifier::REVERSED)
+    } else {
+        Style::default()
+    })
+    .block(pane_block(title, app.focus == Focus::Day));
+
+    let mut state = TableState::default();
+    if !app.day.pairs.is_empty() {
+        state.select(Some(app.pair_index));
+    }
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+fn render_footer(f: &mut Frame, app: &App, area: Rect) {
+    let day_text = match (app.day_surplus(), &app.day.session) {
+        (Some(m), _) => surplus_span(m),
+        (None, Some(s)) => match &s.expected_exit {
+            Some(exit) => Span::raw(format!("expected exit {}", exit)),
+            None => Span::raw("-"),
+        },
+        (None, None) => Span::raw("-"),
+    };
+    let counted = app
+        .month
+        .sessions
+        .iter()
+        .filter(|s| s.surplus_minutes.is_some())
+        .count();
+
+    let lines = vec![
+        Line::from(vec![
+            Span::raw(format!(
+                "Day worked {} · surplus ",
+                format_minutes(app.day.worked_minutes as i64, false)
+            )),
+            day_text,
+            Span::raw(format!(
+                "   │   {} ({} days) surplus ",
+                app.selected_month(),
+                counted
+            )),
+            surplus_span(app.month.total_surplus_minutes),
+        ]),
+        Line::from(Span::raw(app.status.clone().unwrap_or_default())),
+        Line::from(Span::styled(
+            "←→↑↓ move · [ ] month · t today · Tab pane · e edit · d delete · q quit",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    f.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::TOP)),
+        area,
+    );
+}
+
+fn pane_block<'a>(title: String, focused: bool) -> Block<'a> {
+    let style = if focused {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    };
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(title)
+}
+
+fn surplus_span<'a>(minutes: i64) -> Span<'a> {
+    let color = if minutes < 0 {
+        Color::Red
+    } else {
+        Color::Green
+    };
+    Span::styled(format_minutes(minutes, true), Style::default().fg(color))
+}
+
+/// `7h 36m`, with an explicit sign when `signed`.
+fn format_minutes(minutes: i64, signed: bool) -> String {
+    let abs = minutes.abs();
+    let sign = match (signed, minutes) {
+        (true, m) if m < 0 => "-",
+        (true, m) if m > 0 => "+",
+        _ => "",
+    };
+    format!("{}{}h {:02}m", sign, abs / 60, abs % 60)
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
@@ -0,0 +1,303 @@
+//! Undo/redo of mutating operations.
+//!
+//! Every `add`, `edit` and `del` performed through [`crate::Timelog`] stores in its `log` row a
+//! JSON [`Snapshot`] of the affected `events` rows (before and after the change) together with the
+//! legacy `work_sessions` rows of the touched dates, and `state = 'done'`.
+//! [`undo`] restores the `before` side of the most recent `done` rows and marks them `undone`;
+//! [`redo`] re-applies the `after` side of the oldest `undone` rows. Recording a new operation
+//! discards the redo stack (`state = 'discarded'`). Each call runs in a single transaction.
+//! Operations touching a locked period (see [`crate::lock`]) are refused unless overridden.
+
+use crate::clock::Clock;
+use crate::db::{self, Event, WorkSession};
+use crate::error::{Error, Result};
+use crate::lock;
+use crate::utils;
+use rusqlite::{Connection, Transaction, params};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Events and legacy sessions of a set of dates at a point in time.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayState {
+    pub dates: Vec<String>,
+    pub events: Vec<Event>,
+    pub sessions: Vec<WorkSession>,
+}
+
+impl DayState {
+    /// Read the current events and work sessions of `dates`.
+    pub fn capture(conn: &Connection, dates: &[&str]) -> Result<Self> {
+        let mut state = DayState {
+            dates: dates.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        };
+        for d in dates {
+            state.events.extend(db::list_events_by_date(conn, d)?);
+            state.sessions.extend(db::list_sessions_by_date(conn, d)?);
+        }
+        Ok(state)
+    }
+}
+
+/// Structured before/after image stored in `log.snapshot`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub dates: Vec<String>,
+    /// Affected events as they were before the operation (absent = created by it)
+    pub before: Vec<Event>,
+    /// Affected events as they are after the operation (absent = deleted by it)
+    pub after: Vec<Event>,
+    pub sessions_before: Vec<WorkSession>,
+    pub sessions_after: Vec<WorkSession>,
+    /// OS user that performed the operation
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+impl Snapshot {
+    /// Keep only the events that differ between the two states; `user` is the current OS user.
+    pub fn between(before: DayState, after: DayState) -> Self {
+        let changed = |a: &[Event], b: &[Event]| -> Vec<Event> {
+            a.iter().filter(|e| !b.contains(e)).cloned().collect()
+        };
+        Self {
+            before: changed(&before.events, &after.events),
+            after: changed(&after.events, &before.events),
+            dates: before.dates,
+            sessions_before: before.sessions,
+            sessions_after: after.sessions,
+            user: utils::current_user(),
+        }
+    }
+
+    /// True when the operation did not change any event or session.
+    pub fn is_empty(&self) -> bool {
+        self.before.is_empty()
+            && self.after.is_empty()
+            && self.sessions_before == self.sessions_after
+    }
+}
+
+/// A log entry reverted by [`undo`] or re-applied by [`redo`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Reverted {
+    pub log_id: i32,
+    pub operation: String,
+    pub message: String,
+}
+
+/// Write an audit row carrying `snapshot` and drop the redo stack.
+/// Operations that changed nothing are logged without snapshot.
+pub fn record(
+    conn: &Connection,
+    clock: &dyn Clock,
+    operation: &str,
+    target: &str,
+    message: &str,
+    snapshot: &Snapshot,
+) -> Result<()> {
+    if snapshot.is_empty() {
+        // Nothing changed: plain audit row, not undoable
+        return db::ttlog_with_clock(conn, clock, operation, target, message);
+    }
+    conn.execute(
+        "UPDATE log SET state = 'discarded' WHERE state = 'undone'",
+        [],
+    )?;
+    conn.execute(
+        "INSERT INTO log (date, operation, target, message, snapshot, state) \
+         VALUES (?1, ?2, ?3, ?4, ?5, 'done')",
+        params![
+            clock.now_utc().to_rfc3339(),
+            operation,
+            target,
+            message,
+            serde_json::to_string(snapshot)?
+        ],
+    )?;
+    Ok(())
+}
+
+/// Revert the last `steps` operations (most recent first).
+/// Locked dates are refused unless `force` is set (see [`lock::guard`]).
+pub fn undo(
+    conn: &mut Connection,
+    clock: &dyn Clock,
+    steps: usize,
+    force: bool,
+) -> Result<Vec<Reverted>> {
+    apply(conn, clock, steps, force, Direction::Undo)
+}
+
+/// Re-apply the last `steps` undone operations (oldest first).
+pub fn redo(
+    conn: &mut Connection,
+    clock: &dyn Clock,
+    steps: usize,
+    force: bool,
+) -> Result<Vec<Reverted>> {
+    apply(conn, clock, steps, force, Direction::Redo)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Undo,
+    Redo,
+}
+
+fn apply(
+    conn: &mut Connection,
+    clock: &dyn Clock,
+    steps: usize,
+    force: bool,
+    dir: Direction,
+) -> Result<Vec<Reverted>> {
+    let (select, new_state, verb) = match dir {
+        Direction::Undo => (
+            "SELECT id, operation, message, snapshot FROM log WHERE state = 'done' ORDER BY id DESC LIMIT ?1",
+            "undone",
+            "undo",
+        ),
+        Direction::Redo => (
+            "SELECT id, operation, message, snapshot FROM log WHERE state = 'undone' ORDER BY id ASC LIMIT ?1",
+            "done",
+            "redo",
+        ),
+    };
+
+    let tx = conn.transaction()?;
+    let rows = {
+        let mut stmt = tx.prepare(select)?;
+        stmt.query_map([steps as i64], |row| {
+            Ok((
+                row.get::<_, i32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+    };
+    if rows.is_empty() {
+        return Err(Error::invalid(format!("Nothing to {}", verb)));
+    }
+
+    let mut reverted = Vec::with_capacity(rows.len());
+    for (id, operation, message, json) in rows {
+        let snap: Snapshot = serde_json::from_str(&json)?;
+        let (current, target, sessions) = match dir {
+            Direction::Undo => (&snap.after, &snap.before, &snap.sessions_before),
+            Direction::Redo => (&snap.before, &snap.after, &snap.sessions_after),
+        };
+        let dates: Vec<&str> = snap.dates.iter().map(String::as_str).collect();
+        let overrides = lock::guard(&tx, &dates, force)?;
+        ensure_unchanged(&tx, id, verb, current, target)?;
+        restore(&tx, &snap.dates, current, target, sessions)?;
+
+        tx.execute(
+            "UPDATE log SET state = ?1 WHERE id = ?2",
+            params![new_state, id],
+        )?;
+        db::ttlog_with_clock(
+            &tx,
+            clock,
+            verb,
+            &format!("log#{}", id),
+            &format!("{} #{} ({}: {})", verb, id, operation, message),
+        )?;
+        overrides.record(&tx, clock, verb)?;
+        reverted.push(Reverted {
+            log_id: id,
+            operation,
+            message,
+        });
+    }
+    tx.commit()?;
+    Ok(reverted)
+}
+
+/// Refuse to overwrite events that were modified after the operation was recorded.
+fn ensure_unchanged(
+    tx: &Transaction,
+    id: i32,
+    verb: &str,
+    current: &[Event],
+    target: &[Event],
+) -> Result<()> {
+    let current_ids: HashSet<i32> = current.iter().map(|e| e.id).collect();
+    for e in current {
+        if db::get_event(tx, e.id as i64)?.as_ref() != Some(e) {
+            return Err(conflict(id, verb, e));
+        }
+    }
+    for e in target.iter().filter(|e| !current_ids.contains(&e.id)) {
+        if db::get_event(tx, e.id as i64)?.is_some() {
+            return Err(conflict(id, verb, e));
+        }
+    }
+    Ok(())
+}
+
+fn conflict(id: i32, verb: &str, e: &Event) -> Error {
+    Error::invalid(format!(
+        "Cannot {} operation #{}: event {} ({} {} {}) was changed afterwards",
+        verb, id, e.id, e.date, e.time, e.kind
+    ))
+}
+
+/// Replace the `current` events with `target` and the sessions of `dates` with `sessions`.
+fn restore(
+    tx: &Transaction,
+    dates: &[String],
+    current: &[Event],
+    target: &[Event],
+    sessions: &[WorkSession],
+) -> Result<()> {
+    for e in current {
+        tx.execute("DELETE FROM events WHERE id = ?1", [e.id])?;
+    }
+    for e in target {
+        tx.execute(
+            "INSERT INTO events (id, date, time, minutes, kind, position, lunch_break, pair, source, meta, created_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                e.id,
+                e.date,
+                e.time,
+                e.minutes,
+                e.kind,
+                e.position,
+                e.lunch_break,
+                e.pair,
+                e.source,
+                e.meta,
+                e.created_at
+            ],
+        )?;
+    }
+    for d in dates {
+        tx.execute("DELETE FROM work_sessions WHERE date = ?1", [d])?;
+    }
+    for s in sessions {
+        tx.execute(
+            "INSERT INTO work_sessions (id, date, position, start_time, lunch_break, end_time) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![s.id, s.date, s.position, s.start, s.lunch, s.end],
+        )?;
+    }
+    Ok(())
+}
+
+/// Number of operations that can currently be undone and redone.
+pub fn pending(conn: &Connection) -> Result<(usize, usize)> {
+    let count = |state: &str| -> Result<usize> {
+        let n: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM log WHERE state = ?1",
+            [state],
+            |row| row.get(0),
+        )?;
+        Ok(n as usize)
+    };
+    Ok((count("done")?, count("undone")?))
+}
@@ -1,6 +1,80 @@
-use chrono::{Datelike, NaiveDate, NaiveDateTime, ParseError, Weekday};
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, ParseError, Weekday};
 use std::io;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable quiet mode (global `--quiet` flag).
+/// When enabled, progress lines printed through [`progress!`] are suppressed.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Return true when quiet mode is enabled.
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Print a progress/confirmation line (e.g. `✅ ...`) to stdout unless quiet mode is enabled.
+/// Warnings and errors must keep using `eprintln!` so they are never hidden.
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if !$crate::utils::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// How confirmation prompts are answered (global `--yes` / `--no-input`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confirm {
+    /// Ask on stdin
+    Ask,
+    /// Answer "yes" without prompting
+    Yes,
+    /// Never prompt: a needed confirmation is an error
+    NoInput,
+}
+
+impl Confirm {
+    pub fn from_flags(yes: bool, no_input: bool) -> Self {
+        match (yes, no_input) {
+            (true, _) => Confirm::Yes,
+            (false, true) => Confirm::NoInput,
+            (false, false) => Confirm::Ask,
+        }
+    }
+}
+
+/// Ask the user for a y/N confirmation on stdin, according to `mode`.
+pub fn confirm(prompt: &str, mode: Confirm) -> crate::Result<bool> {
+    match mode {
+        Confirm::Yes => return Ok(true),
+        Confirm::NoInput => {
+            return Err(crate::Error::invalid(format!(
+                "{}? Confirmation needed but --no-input is set (pass --yes to confirm)",
+                prompt
+            )));
+        }
+        Confirm::Ask => {}
+    }
+    print!("{} (N/y) ? ", prompt);
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap_or(0);
+    Ok(input.trim().eq_ignore_ascii_case("y"))
+}
+
+/// Name of the OS user running the process (`USER`, or `USERNAME` on Windows).
+pub fn current_user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|u| !u.trim().is_empty())
+}
 
 /// Convert a `NaiveDate` into an ISO 8601 string (YYYY-MM-DD)
 pub fn date2iso(date: &NaiveDate) -> String {
@@ -18,6 +92,247 @@ pub fn iso2date(s: &str) -> Result<NaiveDate, ParseError> {
     }
 }
 
+/// An inclusive range of days, see [`parse_date_range`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateRange {
+    pub fn day(date: NaiveDate) -> Self {
+        DateRange {
+            from: date,
+            to: date,
+        }
+    }
+
+    /// Bounds as ISO dates (`YYYY-MM-DD`), for SQL filters.
+    pub fn bounds(&self) -> (String, String) {
+        (date2iso(&self.from), date2iso(&self.to))
+    }
+
+    /// `YYYY-MM` when the range is exactly one calendar month.
+    pub fn month(&self) -> Option<String> {
+        let first = self.from.with_day(1)?;
+        (self.from == first && self.to == last_of_month(first))
+            .then(|| self.from.format("%Y-%m").to_string())
+    }
+
+    /// `YYYY` for a whole year, `YYYY-MM` for a whole month, else `YYYY-MM-DD..YYYY-MM-DD`:
+    /// the periods understood by `db::list_sessions`.
+    pub fn period(&self) -> String {
+        if self.from.ordinal() == 1
+            && self.to.year() == self.from.year()
+            && self.to.month() == 12
+            && self.to.day() == 31
+        {
+            return self.from.year().to_string();
+        }
+        self.month()
+            .unwrap_or_else(|| format!("{}..{}", date2iso(&self.from), date2iso(&self.to)))
+    }
+}
+
+/// Parse a day: `YYYY-MM-DD`, `today`, `yesterday`, `tomorrow`, a weekday (`mon`, `friday`:
+/// the latest one up to today), `last fri` / `next mon` (strictly before / after today) or an
+/// offset from today such as `-2d` or `+1w`. Words may be separated by spaces, `-` or `_`.
+pub fn parse_date_expr(expr: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let s = expr.trim().to_lowercase();
+    if let Ok(date) = iso2date(&s) {
+        return Ok(date);
+    }
+    if let Some(date) = parse_day_offset(&s, today) {
+        return Ok(date);
+    }
+    let words: Vec<&str> = s.split([' ', '-', '_']).filter(|w| !w.is_empty()).collect();
+    let date = match words.as_slice() {
+        ["today"] => Some(today),
+        ["yesterday"] => today.pred_opt(),
+        ["tomorrow"] => today.succ_opt(),
+        [day] => day
+            .parse::<Weekday>()
+            .ok()
+            .map(|wd| today - Duration::days(days_between(wd, today.weekday()) as i64)),
+        ["last", day] => day.parse::<Weekday>().ok().map(|wd| {
+            let back = days_between(wd, today.weekday());
+            today - Duration::days(if back == 0 { 7 } else { back } as i64)
+        }),
+        ["next", day] => day.parse::<Weekday>().ok().map(|wd| {
+            let ahead = days_between(today.weekday(), wd);
+            today + Duration::days(if ahead == 0 { 7 } else { ahead } as i64)
+        }),
+        _ => None,
+    };
+    date.ok_or_else(|| {
+        format!(
+            "invalid date '{}' (use YYYY-MM-DD, today, yesterday, mon, last fri, -2d)",
+            expr.trim()
+        )
+    })
+}
+
+/// Parse a range of days: `YYYY`, `YYYY-MM`, `YYYY-MM-{dd..dd}`, an ISO week (`2025-W41`),
+/// `this-week`, `last-month`, `next-year` (and the other combinations), any day accepted by
+/// [`parse_date_expr`], or `FROM..TO` where either side may be left empty for an open range.
+pub fn parse_date_range(expr: &str, today: NaiveDate) -> Result<DateRange, String> {
+    let s = expr.trim().to_lowercase();
+    if !s.ends_with('}')
+        && let Some((from, to)) = s.split_once("..")
+    {
+        if from.trim().is_empty() && to.trim().is_empty() {
+            return Err("empty range '..'".into());
+        }
+        let from = match from.trim() {
+            "" => NaiveDate::from_ymd_opt(1, 1, 1).expect("valid date"),
+            f => parse_date_range(f, today)?.from,
+        };
+        let to = match to.trim() {
+            "" => NaiveDate::from_ymd_opt(9999, 12, 31).expect("valid date"),
+            t => parse_date_range(t, today)?.to,
+        };
+        if from > to {
+            return Err(format!("range '{}' ends before it starts", expr.trim()));
+        }
+        return Ok(DateRange { from, to });
+    }
+    if let Some(range) = parse_calendar_period(&s, today)? {
+        return Ok(range);
+    }
+    parse_date_expr(&s, today).map(DateRange::day).map_err(|_| {
+        format!(
+            "invalid range '{}' (use YYYY, YYYY-MM, YYYY-Www, last-month, this-week, FROM..TO or a day such as today or -2d)",
+            expr.trim()
+        )
+    })
+}
+
+/// Years, months, day lists in a month, ISO weeks and `this`/`last`/`next` weeks, months, years.
+fn parse_calendar_period(s: &str, today: NaiveDate) -> Result<Option<DateRange>, String> {
+    let month = |y: i32, m: u32| -> Result<DateRange, String> {
+        let from =
+            NaiveDate::from_ymd_opt(y, m, 1).ok_or_else(|| format!("invalid month '{s}'"))?;
+        Ok(DateRange {
+            from,
+            to: last_of_month(from),
+        })
+    };
+    let number = |part: &str| part.chars().all(|c| c.is_ascii_digit()) && !part.is_empty();
+    if !s.is_ascii() {
+        return Ok(None);
+    }
+
+    // YYYY
+    if s.len() == 4 && number(s) {
+        let y: i32 = s.parse().map_err(|_| "invalid year")?;
+        return month(y, 1).map(|r| {
+            Some(DateRange {
+                from: r.from,
+                to: NaiveDate::from_ymd_opt(y, 12, 31).expect("valid date"),
+            })
+        });
+    }
+    // YYYY-MM
+    if s.len() == 7 && &s[4..5] == "-" && number(&s[..4]) && number(&s[5..]) {
+        return month(s[..4].parse().unwrap_or(0), s[5..].parse().unwrap_or(0)).map(Some);
+    }
+    // YYYY-MM-{dd..dd}
+    if s.len() >= 15 && &s[4..5] == "-" && s[7..].starts_with("-{") && s.ends_with('}') {
+        let whole = month(
+            s[..4].parse().map_err(|_| "invalid year")?,
+            s[5..7].parse().map_err(|_| "invalid month")?,
+        )?;
+        let (d1, d2) = s[9..s.len() - 1]
+            .split_once("..")
+            .ok_or("invalid day range syntax")?;
+        let d1: u32 = d1.parse().map_err(|_| "invalid start day")?;
+        let d2: u32 = d2.parse().map_err(|_| "invalid end day")?;
+        if d1 == 0 || d1 > d2 || d2 > whole.to.day() {
+            return Err("day range out of bounds".into());
+        }
+        return Ok(Some(DateRange {
+            from: whole.from.with_day(d1).expect("checked above"),
+            to: whole.from.with_day(d2).expect("checked above"),
+        }));
+    }
+    // YYYY-Www
+    if s.len() == 8 && &s[4..6] == "-w" && number(&s[..4]) && number(&s[6..]) {
+        let (y, w) = (s[..4].parse().unwrap_or(0), s[6..].parse().unwrap_or(0));
+        let from = NaiveDate::from_isoywd_opt(y, w, Weekday::Mon)
+            .ok_or_else(|| format!("invalid week '{s}'"))?;
+        return Ok(Some(DateRange {
+            from,
+            to: from + Duration::days(6),
+        }));
+    }
+
+    let words: Vec<&str> = s.split([' ', '-', '_']).filter(|w| !w.is_empty()).collect();
+    let [which, unit] = words.as_slice() else {
+        return Ok(None);
+    };
+    let shift: i32 = match *which {
+        "this" => 0,
+        "last" => -1,
+        "next" => 1,
+        _ => return Ok(None),
+    };
+    let range = match *unit {
+        "week" => {
+            let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64)
+                + Duration::weeks(shift as i64);
+            DateRange {
+                from: monday,
+                to: monday + Duration::days(6),
+            }
+        }
+        "month" => {
+            let index = today.year() * 12 + today.month0() as i32 + shift;
+            month(index.div_euclid(12), index.rem_euclid(12) as u32 + 1)?
+        }
+        "year" => {
+            let y = today.year() + shift;
+            DateRange {
+                from: NaiveDate::from_ymd_opt(y, 1, 1).ok_or("invalid year")?,
+                to: NaiveDate::from_ymd_opt(y, 12, 31).ok_or("invalid year")?,
+            }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(range))
+}
+
+/// `-2d`, `+1w`: days or weeks before or after today.
+fn parse_day_offset(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    if !s.is_ascii() {
+        return None;
+    }
+    let sign = match s.chars().next()? {
+        '-' => -1,
+        '+' => 1,
+        _ => return None,
+    };
+    let (n, unit) = s[1..].split_at(s.len().checked_sub(2)?);
+    let n = n.parse::<i64>().ok()?.checked_mul(sign)?;
+    let offset = match unit {
+        "d" => Duration::try_days(n)?,
+        "w" => Duration::try_weeks(n)?,
+        _ => return None,
+    };
+    today.checked_add_signed(offset)
+}
+
+/// Days from weekday `from` forward to weekday `to` (0..=6).
+fn days_between(from: Weekday, to: Weekday) -> u32 {
+    (to.num_days_from_monday() + 7 - from.num_days_from_monday()) % 7
+}
+
+fn last_of_month(first: NaiveDate) -> NaiveDate {
+    first
+        .checked_add_months(Months::new(1))
+        .and_then(|d| d.pred_opt())
+        .unwrap_or(first)
+}
+
 /// Convert a `NaiveDateTime` into an ISO 8601 string (YYYY-MM-DD HH:MM:SS)
 pub fn datetime2iso(dt: &NaiveDateTime) -> String {
     dt.format("%Y-%m-%d %H:%M:%S").to_string()
@@ -139,6 +454,12 @@ pub fn mins2readable(minutes: i32) -> (String, String) {
     }
 }
 
+/// Minutes as `HH:MM`, prefixed with `-` when negative (`-30` → `-00:30`).
+pub fn signed_hhmm(minutes: i64) -> String {
+    let (hh, mm) = mins2readable(minutes as i32);
+    format!("{}{}:{}", if minutes < 0 { "-" } else { "" }, hh, mm)
+}
+
 /// Generate a separator string with `width` repetitions of the given `ch`,
 /// aligned to the given column (`align`).
 pub fn make_separator(ch: char, width: usize, align: usize) -> String {
@@ -152,40 +473,24 @@ pub fn print_separator(ch: char, width: usize, align: usize) {
     println!("{}", make_separator(ch, width, align));
 }
 
-/// Return a tuple (label, colorized_label) for a given working position.
-/// O = Office (blue), R = Remote (cyan), F = On-site (yellow), H = Holiday (purple background).
+/// Return a tuple (label, ANSI color prefix) for a given working position, using the default
+/// palette of [`crate::style`]: O = Office (blue), R = Remote (cyan), C = On-site (yellow),
+/// H = Holiday (purple background), M = Mixed (magenta).
+///
+/// The prefix is unconditional; terminal output should go through [`crate::style::paint_position`],
+/// which honours `--color`, `NO_COLOR` and the configured theme.
 pub fn describe_position(pos: &str) -> (String, String) {
-    match pos {
-        "O" => {
-            let label = "Office".to_string();
-            let colored = "\x1b[34m".to_string();
-            (label, colored)
-        }
-        "R" => {
-            let label = "Remote".to_string();
-            let colored = "\x1b[36m".to_string();
-            (label, colored)
-        }
-        "C" => {
-            let label = "On-site (Client)".to_string();
-            let colored = "\x1b[33m".to_string();
-            (label, colored)
-        }
-        "H" => {
-            let label = "Holiday".to_string();
-            let colored = "\x1b[45;97;1m".to_string();
-            (label, colored)
-        }
-        "M" => {
-            let label = "Mixed".to_string();
-            let colored = "\x1b[35m".to_string(); // magenta
-            (label, colored)
-        }
-        _ => {
-            let label = pos.to_string();
-            (label.clone(), "\x1b[0m".to_string()) // fallback without color
-        }
-    }
+    let label = match pos {
+        "O" => "Office",
+        "R" => "Remote",
+        "C" => "On-site (Client)",
+        "H" => "Holiday",
+        "M" => "Mixed",
+        _ => pos,
+    };
+    let sgr = crate::style::Palette::default().position(pos).to_string();
+    let code = if sgr.is_empty() { "0".to_string() } else { sgr };
+    (label.to_string(), format!("\x1b[{}m", code))
 }
 
 /// Return true if the given date (YYYY-MM-DD) is the last day of its month.
@@ -229,7 +534,7 @@ pub fn compress_backup(dest: &Path) -> io::Result<PathBuf> {
     std::io::copy(&mut f, &mut zip)?;
     zip.finish()?;
 
-    println!("✅ Compressed backup: {}", zip_path.display());
+    crate::progress!("✅ Compressed backup: {}", zip_path.display());
     Ok(zip_path)
 }
 
@@ -247,6 +552,6 @@ pub fn compress_backup(dest: &Path) -> io::Result<PathBuf> {
     tar.append_path_with_name(dest, dest.file_name().unwrap())?;
     tar.finish()?;
 
-    println!("✅ Compressed backup: {}", tar_gz_path.display());
+    crate::progress!("✅ Compressed backup: {}", tar_gz_path.display());
     Ok(tar_gz_path)
 }
@@ -0,0 +1,205 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db, temp_out};
+use predicates::prelude::PredicateBooleanExt;
+use predicates::str::contains;
+use rtimelogger::audit::{LogFilter, query_log};
+use rtimelogger::clock::FixedClock;
+use rtimelogger::config::Config;
+use rtimelogger::timelog::DayEntry;
+use rtimelogger::{Error, Timelog};
+use rusqlite::Connection;
+
+fn open() -> Timelog {
+    let conn = Connection::open_in_memory().expect("open in-memory");
+    let mut tl = Timelog::new(conn, Config::default()).expect("timelog");
+    tl.set_clock(FixedClock::parse("2025-10-03T18:00").unwrap());
+    tl.punch_in("2025-10-03", "09:00", Some("O")).unwrap();
+    tl.punch_out("2025-10-03", "17:30", Some(30)).unwrap();
+    tl.set_clock(FixedClock::parse("2025-10-06T09:00").unwrap());
+    let edit = DayEntry {
+        end: Some("18:00".into()),
+        position: Some("R".into()),
+        ..Default::default()
+    };
+    tl.edit_pair("2025-10-03", 1, &edit).unwrap();
+    tl
+}
+
+#[test]
+fn test_filters_hide_migrations_and_select_rows() {
+    let tl = open();
+    let conn = tl.connection();
+
+    let all = query_log(conn, &LogFilter::default()).unwrap();
+    assert!(all.iter().all(|e| e.operation != "migration_applied"));
+    assert_eq!(all.len(), 3);
+
+    let with_migrations = LogFilter {
+        migrations: true,
+        ..Default::default()
+    };
+    assert!(query_log(conn, &with_migrations).unwrap().len() > all.len());
+
+    let edits = LogFilter {
+        operations: vec!["edit".into()],
+        ..Default::default()
+    };
+    assert_eq!(query_log(conn, &edits).unwrap().len(), 1);
+
+    let since = LogFilter {
+        since: Some("2025-10-04".into()),
+        ..Default::default()
+    };
+    let rows = query_log(conn, &since).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].operation, "edit");
+
+    let until = LogFilter {
+        until: Some("2025-10-03".into()),
+        tail: Some(1),
+        ..Default::default()
+    };
+    let rows = query_log(conn, &until).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert!(rows[0].message.contains("end=17:30"));
+
+    let target = LogFilter {
+        target: Some("pair=1".into()),
+        limit: Some(5),
+        ..Default::default()
+    };
+    assert_eq!(query_log(conn, &target).unwrap().len(), 1);
+
+    let bad = LogFilter {
+        since: Some("yesterday".into()),
+        ..Default::default()
+    };
+    assert!(matches!(query_log(conn, &bad), Err(Error::Validation(_))));
+}
+
+#[test]
+fn test_edit_shows_field_level_diff() {
+    let tl = open();
+    let edits = LogFilter {
+        operations: vec!["edit".into()],
+        ..Default::default()
+    };
+    let entry = query_log(tl.connection(), &edits).unwrap().remove(0);
+    let changed: Vec<String> = entry.changes.iter().map(|c| c.to_string()).collect();
+    assert!(
+        changed
+            .iter()
+            .any(|c| c.ends_with("2025-10-03 out time: 17:30 → 18:00")),
+        "{:?}",
+        changed
+    );
+    assert!(changed.iter().any(|c| c.contains("in position: O → R")));
+    assert!(!changed.iter().any(|c| c.contains("lunch_break")));
+}
+
+#[test]
+fn test_cli_log_days_are_local() {
+    let db_path = setup_test_db("audit_local_days");
+    init_db_with_data(&db_path);
+    let conn = Connection::open(&db_path).unwrap();
+    // 23:30 on 2025-10-03 and 00:30 on 2025-10-04 in Rome (UTC+2)
+    conn.execute_batch(
+        "INSERT INTO log (date, operation, target, message)
+         VALUES ('2025-10-03T21:30:00+00:00', 'add', '2025-10-03', 'late evening'),
+                ('2025-10-03T22:30:00.123456789+00:00', 'add', '2025-10-04', 'after midnight');",
+    )
+    .unwrap();
+
+    let log = |flag: &str, day: &str| {
+        let output = Command::cargo_bin("rtimelogger")
+            .unwrap()
+            .env("TZ", "Europe/Rome")
+            .args(["--db", &db_path, "log", flag, day])
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let since = log("--since", "2025-10-04");
+    assert!(since.contains("after midnight") && !since.contains("late evening"));
+    let until = log("--until", "2025-10-03");
+    assert!(until.contains("late evening") && !until.contains("after midnight"));
+}
+
+#[test]
+fn test_cli_log_filters_and_export() {
+    let db_path = setup_test_db("audit_cli");
+    init_db_with_data(&db_path);
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "add",
+            "2025-09-15",
+            "--edit",
+            "--pair",
+            "1",
+            "--out",
+            "17:45",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "log", "--operation", "edit"])
+        .assert()
+        .success()
+        .stdout(contains("time: 17:00 → 17:45"))
+        .stdout(contains("migration_applied").not());
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "log", "--print"])
+        .assert()
+        .success()
+        .stdout(contains("migration_applied").not());
+
+    let csv_path = temp_out("audit_log", "csv");
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "log", "--tail", "2", "--export", &csv_path,
+        ])
+        .assert()
+        .success();
+    let csv = std::fs::read_to_string(&csv_path).unwrap();
+    assert!(csv.starts_with("id,date,operation,target,message,user,state,changes"));
+    assert_eq!(csv.lines().count(), 3);
+
+    // `--force` is reserved for locked periods; replacing the file is `--overwrite`
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "log", "--tail", "1", "--export", &csv_path,
+        ])
+        .arg("--overwrite")
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read_to_string(&csv_path).unwrap().lines().count(),
+        2
+    );
+
+    let json_path = temp_out("audit_log", "json");
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "log", "--operation", "add,edit"])
+        .args(["--export", &json_path])
+        .assert()
+        .success();
+    let rows: Vec<serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+    assert!(rows.iter().all(|r| r["operation"] != "migration_applied"));
+    let edit = rows.last().unwrap();
+    assert_eq!(edit["operation"], "edit");
+    assert_eq!(edit["changes"][0]["field"], "time");
+    assert_eq!(edit["changes"][0]["new"], "17:45");
+}
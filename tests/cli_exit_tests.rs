@@ -0,0 +1,156 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db, temp_out};
+use predicates::prelude::*;
+use predicates::str::contains;
+
+#[test]
+fn test_add_invalid_date_exits_with_validation_code() {
+    let db_path = setup_test_db("exit_add_invalid_date");
+    init_db_with_data(&db_path);
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "add", "2025-13-01", "O", "09:00"])
+        .assert()
+        .code(2)
+        .stderr(contains("Invalid date format"));
+}
+
+#[test]
+fn test_add_invalid_lunch_exits_with_validation_code() {
+    let db_path = setup_test_db("exit_add_invalid_lunch");
+    init_db_with_data(&db_path);
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "add", "2025-09-02", "--lunch", "120"])
+        .assert()
+        .code(2)
+        .stderr(contains("Invalid lunch break"));
+}
+
+#[test]
+fn test_del_invalid_date_exits_with_validation_code() {
+    let db_path = setup_test_db("exit_del_invalid_date");
+    init_db_with_data(&db_path);
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "del", "2025/09/01"])
+        .assert()
+        .code(2)
+        .stderr(contains("Invalid date format"));
+}
+
+#[test]
+fn test_del_of_nothing_exits_with_validation_code() {
+    let db_path = setup_test_db("exit_del_nothing");
+    init_db_with_data(&db_path);
+
+    let del = |args: &[&str]| {
+        Command::cargo_bin("rtimelogger")
+            .unwrap()
+            .args(["--db", &db_path, "--yes", "del"])
+            .args(args)
+            .assert()
+    };
+    del(&["--pair", "99", "2025-09-01"])
+        .code(2)
+        .stdout(contains("not found").not())
+        .stderr(contains("Pair 99 not found for date 2025-09-01"));
+    del(&["2025-09-02"])
+        .code(2)
+        .stdout(contains("found").not())
+        .stderr(contains(
+            "No events or work_sessions found for date 2025-09-02",
+        ));
+}
+
+#[test]
+fn test_del_with_yes_does_not_prompt() {
+    let db_path = setup_test_db("exit_del_yes");
+    init_db_with_data(&db_path);
+
+    // No stdin provided: the deletion must go through thanks to --yes
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "--yes", "del", "2025-09-01"])
+        .assert()
+        .success()
+        .stdout(contains("Are you sure").not())
+        .stdout(contains("Deleted"));
+
+    // --no-input refuses to prompt instead of confirming
+    let del_pair = |flags: &[&str]| {
+        Command::cargo_bin("rtimelogger")
+            .unwrap()
+            .args(["--db", &db_path])
+            .args(flags)
+            .args(["del", "--pair", "1", "2025-09-15"])
+            .assert()
+    };
+    del_pair(&["--no-input"])
+        .code(2)
+        .stderr(contains("--no-input"))
+        .stdout(contains("Deleted").not());
+    del_pair(&["--no-input", "--yes"])
+        .success()
+        .stdout(contains("Deleted 2 event(s) for pair 1"));
+}
+
+#[test]
+fn test_quiet_suppresses_progress_lines() {
+    let db_path = setup_test_db("exit_quiet");
+    init_db_with_data(&db_path);
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "--quiet",
+            "add",
+            "2025-09-02",
+            "O",
+            "09:00",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "-q", "-y", "del", "2025-09-02"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_export_over_existing_file_follows_confirm_flags() {
+    let db_path = setup_test_db("exit_export_existing");
+    init_db_with_data(&db_path);
+    let out = temp_out("exit_export_existing", "csv");
+    std::fs::write(&out, "old").unwrap();
+
+    let export = |flags: &[&str]| {
+        Command::cargo_bin("rtimelogger")
+            .unwrap()
+            .args(["--db", &db_path])
+            .args(flags)
+            .args(["export", "--format", "csv", "--file", &out])
+            .assert()
+    };
+    export(&["--no-input"])
+        .code(2)
+        .stderr(contains("already exists").and(contains("--no-input")));
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), "old");
+
+    export(&["--no-input", "--yes"]).success();
+    assert!(
+        std::fs::read_to_string(&out)
+            .unwrap()
+            .contains("2025-09-01")
+    );
+}
@@ -0,0 +1,142 @@
+mod common;
+use assert_cmd::Command;
+use chrono::{Duration, NaiveDate};
+use common::{init_db_with_data, setup_test_db};
+use predicates::prelude::*;
+use predicates::str::contains;
+use rtimelogger::Timelog;
+use rtimelogger::clock::{Clock, FixedClock};
+use rtimelogger::config::Config;
+use rusqlite::Connection;
+
+#[test]
+fn test_fixed_clock_parse_and_advance() {
+    let mut clock = FixedClock::parse("2025-10-31T23:30").unwrap();
+    assert_eq!(
+        clock.today(),
+        NaiveDate::from_ymd_opt(2025, 10, 31).unwrap()
+    );
+    clock.advance(Duration::minutes(45));
+    assert_eq!(clock.today(), NaiveDate::from_ymd_opt(2025, 11, 1).unwrap());
+    assert_eq!(clock.now_local().format("%H:%M").to_string(), "00:15");
+
+    assert!(FixedClock::parse("2025-10-03 17:00").is_err());
+    assert_eq!(FixedClock::parse("nope").unwrap_err().exit_code(), 2);
+}
+
+#[test]
+fn test_timelog_uses_injected_clock_for_timestamps() {
+    let conn = Connection::open_in_memory().unwrap();
+    let clock = FixedClock::parse("2025-10-03T17:00").unwrap();
+    let mut tl = Timelog::new(conn, Config::default())
+        .unwrap()
+        .with_clock(clock);
+    assert_eq!(tl.today(), "2025-10-03");
+
+    let ev = tl.punch_in("2025-10-03", "09:00", None).unwrap();
+    let expected = clock.now_utc().to_rfc3339();
+    assert_eq!(ev.created_at, expected);
+
+    let logged: String = tl
+        .connection()
+        .query_row(
+            "SELECT date FROM log WHERE operation = 'add' ORDER BY id DESC LIMIT 1",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(logged, expected);
+}
+
+#[test]
+fn test_cli_now_override_selects_today() {
+    let db_path = setup_test_db("clock_now_override");
+    init_db_with_data(&db_path);
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "--now-override",
+            "2025-09-15T17:00",
+            "list",
+            "--now",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("2025-09-15"))
+        .stdout(contains("2025-09-01").not());
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "--now-override",
+            "15/09/2025",
+            "list",
+            "--now",
+        ])
+        .assert()
+        .code(2)
+        .stderr(contains("Invalid date-time"));
+}
+
+#[test]
+fn test_cli_relative_dates_follow_now_override() {
+    let db_path = setup_test_db("clock_relative_dates");
+    init_db_with_data(&db_path);
+    let run = |args: &[&str]| {
+        Command::cargo_bin("rtimelogger")
+            .unwrap()
+            .args(["--db", &db_path, "--now-override", "2025-10-15T10:00"])
+            .args(args)
+            .assert()
+    };
+
+    run(&[
+        "add",
+        "yesterday",
+        "--pos",
+        "R",
+        "--in",
+        "09:00",
+        "--out",
+        "17:00",
+    ])
+    .success();
+    run(&["add", "-2d", "--in", "09:00", "--out", "12:00"]).success();
+    run(&["list", "--period", "this-week"])
+        .success()
+        .stdout(contains("Saved sessions from 2025-10-13 to 2025-10-19"))
+        .stdout(contains("2025-10-14"))
+        .stdout(contains("2025-10-13"))
+        .stdout(contains("2025-09-").not());
+    run(&["list", "--period", "last-month"])
+        .success()
+        .stdout(contains("September 2025"))
+        .stdout(contains("2025-10-").not());
+    run(&[
+        "export",
+        "--format",
+        "csv",
+        "--file",
+        "-",
+        "--range",
+        "2025-10-14..",
+    ])
+    .success()
+    .stdout(contains("2025-10-14"))
+    .stdout(contains("2025-10-13").not());
+    run(&["period", "lock", "last-month"])
+        .success()
+        .stdout(contains("2025-09"));
+    run(&["list", "--period", "someday"])
+        .code(2)
+        .stderr(contains("Invalid period"));
+    // out of the calendar: an error, not a panic
+    run(&["list", "--period=-99999999999d"])
+        .code(2)
+        .stderr(contains("Invalid period"));
+}
@@ -0,0 +1,107 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db};
+use predicates::str::contains;
+use rtimelogger::error::ValidationError;
+use rtimelogger::{Error, db};
+use rusqlite::Connection;
+
+#[test]
+fn test_invalid_period_is_a_validation_error() {
+    let conn = Connection::open_in_memory().expect("open in-memory");
+    db::init_db(&conn).expect("init_db");
+
+    let err = db::list_events_filtered(&conn, Some("2025-9"), None).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Validation(ValidationError::Period(ref p)) if p == "2025-9"
+    ));
+    assert_eq!(err.exit_code(), 2);
+}
+
+#[test]
+fn test_lunch_error_reports_configured_bounds() {
+    let config = rtimelogger::config::Config {
+        min_duration_lunch_break: 20,
+        max_duration_lunch_break: 60,
+        ..Default::default()
+    };
+    let conn = Connection::open_in_memory().expect("open in-memory");
+    let mut tl = rtimelogger::Timelog::new(conn, config).expect("timelog");
+    let entry = rtimelogger::timelog::DayEntry {
+        lunch: Some(75),
+        ..Default::default()
+    };
+
+    let err = tl.add("2025-10-03", &entry).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Validation(ValidationError::Lunch {
+            lunch: 75,
+            min: 20,
+            max: 60
+        })
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Invalid lunch break: 75 (must be 0 or between 20 and 60 minutes)"
+    );
+
+    // below the configured minimum, while 0 still means no lunch
+    let short = rtimelogger::timelog::DayEntry {
+        lunch: Some(10),
+        ..Default::default()
+    };
+    let err = tl.add("2025-10-03", &short).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Validation(ValidationError::Lunch { lunch: 10, .. })
+    ));
+    let none = rtimelogger::timelog::DayEntry {
+        lunch: Some(0),
+        ..Default::default()
+    };
+    tl.add("2025-10-03", &none).unwrap();
+}
+
+#[test]
+fn test_exit_codes_are_distinct_per_class() {
+    let errors = [
+        Error::invalid("bad input"),
+        Error::Config("broken yaml".into()),
+        Error::Io(std::io::Error::other("disk full")),
+        Error::Database(rusqlite::Error::QueryReturnedNoRows),
+        Error::Migration {
+            version: "x".into(),
+            source: Box::new(Error::Config("boom".into())),
+        },
+        Error::Export("writer".into()),
+    ];
+    let mut codes: Vec<u8> = errors.iter().map(Error::exit_code).collect();
+    assert!(codes.iter().all(|c| *c > 1));
+    codes.sort();
+    codes.dedup();
+    assert_eq!(codes.len(), errors.len());
+}
+
+#[test]
+fn test_cli_unsupported_export_format_exits_with_validation_code() {
+    let db_path = setup_test_db("error_export_format");
+    init_db_with_data(&db_path);
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "export",
+            "--format",
+            "xml",
+            "--file",
+            "/tmp/x.xml",
+            "--events",
+        ])
+        .assert()
+        .code(2)
+        .stderr(contains("Unsupported format"));
+}
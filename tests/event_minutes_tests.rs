@@ -0,0 +1,160 @@
+use rtimelogger::config::Config;
+use rtimelogger::db;
+use rusqlite::Connection;
+
+fn open_db() -> Connection {
+    let conn = Connection::open_in_memory().expect("open in-memory");
+    db::init_db(&conn).expect("init_db");
+    conn
+}
+
+fn add(conn: &mut Connection, date: &str, time: &str, kind: &str) -> i64 {
+    let args = db::AddEventArgs {
+        date,
+        time,
+        kind,
+        position: Some("O"),
+        source: "test",
+        meta: None,
+    };
+    db::add_event(conn, &args, &Config::default()).expect("add_event")
+}
+
+#[test]
+fn test_add_event_normalizes_time_and_minutes() {
+    let mut conn = open_db();
+    add(&mut conn, "2025-10-03", "9:05", "in");
+
+    let events = db::list_events_by_date(&conn, "2025-10-03").expect("list");
+    assert_eq!(events.len(), 1);
+    let ev = &events[0];
+    assert_eq!(ev.time, "09:05");
+    assert_eq!(ev.minutes, 9 * 60 + 5);
+    assert!(ev.is_in());
+    assert_eq!(
+        ev.datetime().unwrap().format("%Y-%m-%d %H:%M").to_string(),
+        "2025-10-03 09:05"
+    );
+}
+
+#[test]
+fn test_duplicate_event_is_not_inserted_twice() {
+    let mut conn = open_db();
+    let first = add(&mut conn, "2025-10-03", "09:00", "in");
+    let second = add(&mut conn, "2025-10-03", "9:00", "in");
+    assert_eq!(first, second, "duplicate punch must return the existing id");
+    assert_eq!(db::count_events_by_date(&conn, "2025-10-03").unwrap(), 1);
+}
+
+#[test]
+fn test_events_are_ordered_by_minutes() {
+    let mut conn = open_db();
+    add(&mut conn, "2025-10-03", "10:00", "out");
+    add(&mut conn, "2025-10-03", "9:00", "in");
+
+    let events = db::list_events_by_date(&conn, "2025-10-03").expect("list");
+    let times: Vec<&str> = events.iter().map(|e| e.time.as_str()).collect();
+    assert_eq!(times, vec!["09:00", "10:00"]);
+}
+
+#[test]
+fn test_migration_backfills_minutes_and_removes_duplicates() {
+    let conn = open_db();
+    // Simulate a legacy database: drop the new column/indexes and the migration marker
+    conn.execute_batch(
+        "
+        DROP INDEX idx_events_uniq;
+        DROP INDEX idx_events_date_minutes;
+        ALTER TABLE events DROP COLUMN minutes;
+        DELETE FROM log WHERE target = '20251014_0013_add_minutes_and_unique_index_to_events';
+        INSERT INTO events (date, time, kind, position, lunch_break, pair, source, meta, created_at)
+        VALUES ('2025-10-03', '9:00', 'in', 'O', 0, 1, 'cli', '', '2025-10-03T09:00:00'),
+               ('2025-10-03', '09:00', 'in', 'O', 0, 1, 'cli', '', '2025-10-03T09:00:00'),
+               ('2025-10-03', '17:30', 'out', 'O', 0, 1, 'cli', '', '2025-10-03T17:30:00'),
+               ('2025-10-03', '17:30', 'out', 'O', 45, 1, 'cli', 'fixed by hand', '2025-10-03T17:30:00');
+        ",
+    )
+    .expect("simulate legacy schema");
+
+    db::init_db(&conn).expect("re-run migrations");
+
+    let events = db::list_events_by_date(&conn, "2025-10-03").expect("list");
+    assert_eq!(events.len(), 2, "duplicated punch should be removed");
+    assert_eq!(events[0].time, "09:00");
+    assert_eq!(events[0].minutes, 540);
+    assert_eq!(events[1].minutes, 17 * 60 + 30);
+    assert_eq!(
+        events[1].lunch_break, 45,
+        "lunch of the dropped duplicate is kept"
+    );
+    assert_eq!(events[1].meta, "fixed by hand");
+}
+
+#[test]
+fn test_migration_renumbers_pairs_of_merged_dates() {
+    let conn = open_db();
+    conn.execute_batch(
+        "
+        DROP INDEX idx_events_uniq;
+        DROP INDEX idx_events_date_minutes;
+        ALTER TABLE events DROP COLUMN minutes;
+        DELETE FROM log WHERE target = '20251014_0013_add_minutes_and_unique_index_to_events';
+        INSERT INTO events (date, time, kind, position, lunch_break, pair, source, meta, created_at)
+        VALUES ('2025-10-03', '09:00', 'in', 'O', 0, 1, 'cli', '', '2025-10-03T09:00:00'),
+               ('2025-10-03', '09:00', 'in', 'O', 0, 2, 'cli', '', '2025-10-03T09:00:00'),
+               ('2025-10-03', '12:00', 'out', 'O', 0, 2, 'cli', '', '2025-10-03T12:00:00'),
+               ('2025-10-03', '13:00', 'in', 'O', 0, 3, 'cli', '', '2025-10-03T13:00:00'),
+               ('2025-10-03', '17:00', 'out', 'O', 0, 3, 'cli', '', '2025-10-03T17:00:00');
+        ",
+    )
+    .expect("simulate legacy schema");
+
+    db::init_db(&conn).expect("re-run migrations");
+
+    let events = db::list_events_by_date(&conn, "2025-10-03").expect("list");
+    let pairs: Vec<i32> = events.iter().map(|e| e.pair).collect();
+    assert_eq!(pairs, vec![1, 1, 2, 2]);
+}
+
+#[test]
+fn test_migration_aborts_on_conflicting_duplicates() {
+    let conn = open_db();
+    conn.execute_batch(
+        "
+        DROP INDEX idx_events_uniq;
+        DROP INDEX idx_events_date_minutes;
+        ALTER TABLE events DROP COLUMN minutes;
+        DELETE FROM log WHERE target = '20251014_0013_add_minutes_and_unique_index_to_events';
+        INSERT INTO events (date, time, kind, position, lunch_break, pair, source, meta, created_at)
+        VALUES ('2025-10-03', '09:00', 'in', 'O', 0, 1, 'cli', '', '2025-10-03T09:00:00'),
+               ('2025-10-03', '09:00', 'in', 'R', 0, 1, 'cli', '', '2025-10-03T09:00:00');
+        ",
+    )
+    .expect("simulate legacy schema");
+
+    let err = db::init_db(&conn).unwrap_err().to_string();
+    assert!(err.contains("different positions"), "{err}");
+    assert!(err.contains("ids 1, 2"), "{err}");
+
+    // the whole migration is rolled back
+    let columns: Vec<String> = conn
+        .prepare("SELECT name FROM pragma_table_info('events')")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap();
+    assert!(!columns.iter().any(|c| c == "minutes"));
+}
+
+#[test]
+fn test_last_out_before_rejects_invalid_time() {
+    let mut conn = open_db();
+    add(&mut conn, "2025-10-03", "12:00", "out");
+    assert!(db::last_out_before(&conn, "2025-10-03", "25:00").is_err());
+    assert!(
+        db::last_out_before(&conn, "2025-10-03", "13:00")
+            .unwrap()
+            .is_some()
+    );
+}
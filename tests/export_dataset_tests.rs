@@ -0,0 +1,118 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db, temp_out};
+use predicates::str::contains;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::Read;
+
+fn export_json(name: &str, dataset: &str) -> Vec<Value> {
+    let db_path = setup_test_db(name);
+    init_db_with_data(&db_path);
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "add", "2025-10-01", "O", "08:00"])
+        .assert()
+        .success();
+
+    let out = temp_out(name, "json");
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "export", "--format", "json", "--file", &out, dataset,
+        ])
+        .assert()
+        .success();
+    serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap()
+}
+
+#[test]
+fn test_export_summary_pairs() {
+    let rows = export_json("export_summary", "--summary");
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0]["date"], "2025-09-01");
+    assert_eq!(rows[0]["pair"], 1);
+    assert_eq!(rows[0]["duration_minutes"], 450);
+    assert_eq!(rows[0]["unmatched"], false);
+    // the open punch of October has no out yet
+    assert_eq!(rows[2]["date"], "2025-10-01");
+    assert_eq!(rows[2]["unmatched"], true);
+}
+
+#[test]
+fn test_export_daily_and_monthly() {
+    let days = export_json("export_daily", "--daily");
+    assert_eq!(days.len(), 3);
+    assert_eq!(days[0]["worked_minutes"], 450);
+    assert_eq!(days[0]["expected_minutes"], 480);
+    assert_eq!(days[0]["surplus_minutes"], -30);
+    // open day: nothing computed yet
+    assert_eq!(days[2]["worked_minutes"], Value::Null);
+    assert_eq!(days[2]["expected_minutes"], Value::Null);
+
+    let months = export_json("export_monthly", "--monthly");
+    assert_eq!(months.len(), 2);
+    assert_eq!(months[0]["month"], "2025-09");
+    assert_eq!(months[0]["days"], 2);
+    assert_eq!(months[0]["worked_minutes"], 900);
+    assert_eq!(months[0]["expected_minutes"], 960);
+    assert_eq!(months[0]["surplus_minutes"], -60);
+    assert_eq!(months[1]["days"], 0);
+}
+
+#[test]
+fn test_export_daily_xlsx_sums_minutes() {
+    let db_path = setup_test_db("export_daily_xlsx");
+    init_db_with_data(&db_path);
+    let out = temp_out("export_daily_xlsx", "xlsx");
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "export", "--format", "xlsx", "--file", &out, "--daily",
+        ])
+        .assert()
+        .success();
+
+    let mut archive = zip::ZipArchive::new(File::open(&out).unwrap()).unwrap();
+    let mut sheet = String::new();
+    archive
+        .by_name("xl/worksheets/sheet2.xml")
+        .unwrap()
+        .read_to_string(&mut sheet)
+        .unwrap();
+    // surplus is a plain value here, not the work_duration formula column
+    assert!(!sheet.contains("ISNUMBER"));
+    assert!(sheet.contains("<f>SUM(C2:C3)</f><v>900</v>"));
+    assert!(sheet.contains("<f>SUM(E2:E3)</f><v>-60</v>"));
+}
+
+#[test]
+fn test_export_dataset_flags() {
+    let db_path = setup_test_db("export_dataset_flags");
+    init_db_with_data(&db_path);
+    let out = temp_out("export_dataset_flags", "ics");
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "export", "--format", "ics", "--file", &out, "--daily",
+        ])
+        .assert()
+        .code(2)
+        .stderr(contains("drop --daily"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "export",
+            "--file",
+            &out,
+            "--daily",
+            "--monthly",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("cannot be used with"));
+}
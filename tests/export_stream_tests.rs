@@ -0,0 +1,156 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db};
+use predicates::str::contains;
+use serde_json::Value;
+
+fn db(name: &str) -> String {
+    let db_path = setup_test_db(name);
+    init_db_with_data(&db_path);
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "add",
+            "2025-09-16",
+            "R",
+            "08:30",
+            "30",
+            "17:00",
+        ])
+        .assert()
+        .success();
+    db_path
+}
+
+fn export_stdout(db_path: &str, args: &[&str]) -> String {
+    let out = Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", db_path, "export", "--file", "-"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn test_csv_to_stdout_with_columns_and_filters() {
+    let db_path = db("export_stream_csv");
+
+    let csv = export_stdout(
+        &db_path,
+        &["--format", "csv", "--columns", "date,time,kind,position"],
+    );
+    let lines: Vec<&str> = csv.lines().collect();
+    // header only, no progress lines on stdout
+    assert_eq!(lines[0], "date,time,kind,position");
+    assert_eq!(lines[1], "2025-09-01,09:00,in,O");
+    assert_eq!(lines.len(), 7);
+
+    let csv = export_stdout(
+        &db_path,
+        &[
+            "--format",
+            "csv",
+            "--columns",
+            "time,date",
+            "--pos",
+            "r",
+            "--kind",
+            "out",
+        ],
+    );
+    assert_eq!(csv, "time,date\n17:00,2025-09-16\n");
+}
+
+#[test]
+fn test_json_to_stdout_keeps_types_and_order() {
+    let db_path = db("export_stream_json");
+
+    let json = export_stdout(
+        &db_path,
+        &[
+            "--format",
+            "json",
+            "--sessions",
+            "--columns",
+            "date,lunch_break,start_time",
+            "--range",
+            "2025-09-{15..16}",
+        ],
+    );
+    let rows: Vec<Value> = serde_json::from_str(&json).unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["lunch_break"], 30);
+    assert_eq!(rows[1]["start_time"], "08:30");
+    assert!(rows[0].get("id").is_none());
+    let date = json.find("\"date\"").unwrap();
+    let lunch = json.find("\"lunch_break\"").unwrap();
+    assert!(date < lunch);
+
+    let monthly = export_stdout(&db_path, &["--format", "json", "--monthly", "--pos", "O"]);
+    let rows: Vec<Value> = serde_json::from_str(&monthly).unwrap();
+    assert_eq!(rows[0]["days"], 2);
+}
+
+#[test]
+fn test_export_option_errors() {
+    let db_path = db("export_stream_errors");
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "export",
+            "--format",
+            "csv",
+            "--file",
+            "-",
+            "--columns",
+            "date,nope",
+        ])
+        .assert()
+        .code(2)
+        .stderr(contains("Unknown column 'nope' for events"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "export", "--format", "xlsx", "--file", "-",
+        ])
+        .assert()
+        .code(2)
+        .stderr(contains("cannot be written to stdout"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "export",
+            "--format",
+            "csv",
+            "--file",
+            "-",
+            "--sessions",
+            "--kind",
+            "in",
+        ])
+        .assert()
+        .code(2)
+        .stderr(contains("--kind only applies"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "export", "--file", "-", "--kind", "lunch"])
+        .assert()
+        .failure()
+        .stderr(contains("invalid value 'lunch'"));
+}
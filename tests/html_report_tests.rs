@@ -0,0 +1,130 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db};
+use predicates::str::contains;
+use rtimelogger::config::{Config, EmployeeInfo};
+use rtimelogger::html_report::{ReportDay, render, report_days, report_months};
+use rtimelogger::timelog::DayEntry;
+use rtimelogger::{Timelog, db};
+use rusqlite::Connection;
+
+fn open() -> Timelog {
+    let conn = Connection::open_in_memory().expect("open in-memory");
+    let mut tl = Timelog::new(conn, Config::default()).expect("timelog");
+    tl.punch_in("2025-10-03", "09:00", Some("O")).unwrap();
+    tl.punch_out("2025-10-03", "12:30", None).unwrap();
+    tl.punch_in("2025-10-03", "13:30", Some("R")).unwrap();
+    tl.punch_out("2025-10-03", "18:00", Some(30)).unwrap();
+    // unmatched in: no worked time, no surplus
+    tl.punch_in("2025-10-06", "09:00", None).unwrap();
+    tl.add(
+        "2025-10-07",
+        &DayEntry {
+            position: Some("H".into()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    tl.punch_in("2025-11-03", "08:00", Some("C")).unwrap();
+    tl.punch_out("2025-11-03", "15:00", Some(30)).unwrap();
+    tl
+}
+
+fn report(tl: &Timelog) -> Vec<ReportDay> {
+    let events = db::list_events(tl.connection()).unwrap();
+    let sessions = db::list_sessions(tl.connection(), None, None).unwrap();
+    report_days(sessions, &events, &Config::default())
+}
+
+#[test]
+fn test_report_days_and_months() {
+    let tl = open();
+    let days = report(&tl);
+
+    let summary: Vec<_> = days
+        .iter()
+        .map(|d| {
+            (
+                d.day.date.as_str(),
+                d.day.position.as_str(),
+                d.day.worked_minutes,
+                d.day.surplus_minutes,
+                d.unmatched,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            // as `list`: 09:00-18:00 with the 30 min lunch of the day
+            ("2025-10-03", "M", Some(510), Some(30), 0),
+            ("2025-10-06", "O", None, None, 1),
+            ("2025-10-07", "H", None, None, 0),
+            ("2025-11-03", "C", Some(390), Some(-90), 0),
+        ]
+    );
+
+    let months = report_months(&days);
+    assert_eq!(months.len(), 2);
+    assert_eq!(months[0].totals.month, "2025-10");
+    assert_eq!((months[0].totals.days, months[0].holidays), (1, 1));
+    assert_eq!(months[0].totals.surplus_minutes, 30);
+    assert_eq!(months[1].totals.expected_minutes, 480);
+    assert_eq!(months[1].totals.surplus_minutes, -90);
+}
+
+#[test]
+fn test_render_is_self_contained() {
+    let tl = open();
+    let employee = EmployeeInfo {
+        name: "Ada <Lovelace>".into(),
+        ..Default::default()
+    };
+    let html = render("Report", &employee, &report(&tl));
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Ada &lt;Lovelace&gt;"));
+    assert!(html.contains("<td>October 2025</td>"));
+    assert!(html.contains("<td class=\"num neg\">-01:30</td>"));
+    // bar chart, surplus line and pie
+    assert_eq!(html.matches("<svg").count(), 3);
+    assert_eq!(html.matches("<rect").count(), 4);
+    assert!(html.contains("<polyline"));
+    assert_eq!(html.matches("<path").count(), 4);
+    // nothing loaded from elsewhere
+    for external in ["<script", "<link", "src=", "href=", "url("] {
+        assert!(!html.contains(external), "{external}");
+    }
+
+    let empty = render("Report", &employee, &[]);
+    assert!(empty.contains("No data in this range."));
+    assert!(!empty.contains("<svg"));
+}
+
+#[test]
+fn test_export_html_cli() {
+    let db_path = setup_test_db("html_report_cli");
+    init_db_with_data(&db_path);
+
+    let out = Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "export", "--format", "html", "--file", "-", "--range", "2025-09",
+        ])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let html = String::from_utf8(out.stdout).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("rTimelogger – report 2025-09"));
+    assert!(html.contains("<td>September 2025</td>"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "export", "--format", "html", "--file", "-", "--daily",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("drop --daily"));
+}
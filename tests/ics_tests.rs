@@ -0,0 +1,338 @@
+mod common;
+use assert_cmd::Command;
+use chrono::{TimeZone, Utc};
+use common::{init_db_with_data, setup_test_db, temp_out};
+use predicates::prelude::PredicateBooleanExt;
+use predicates::str::contains;
+use regex::Regex;
+use rtimelogger::config::Config;
+use rtimelogger::ics::{
+    ImportAction, ImportFilter, build_calendar, escape_text, parse_calendar, plan_import,
+};
+use rtimelogger::lock;
+use rtimelogger::timelog::DayEntry;
+use rtimelogger::{Timelog, db};
+use rusqlite::Connection;
+use std::fs;
+
+fn open() -> Timelog {
+    let conn = Connection::open_in_memory().expect("open in-memory");
+    let mut tl = Timelog::new(conn, Config::default()).expect("timelog");
+    tl.punch_in("2025-10-03", "09:00", Some("O")).unwrap();
+    tl.punch_out("2025-10-03", "12:30", None).unwrap();
+    tl.punch_in("2025-10-03", "13:30", Some("R")).unwrap();
+    tl.punch_out("2025-10-03", "18:00", Some(30)).unwrap();
+    // unmatched in: not exported
+    tl.punch_in("2025-10-06", "09:00", None).unwrap();
+    tl.add(
+        "2025-10-07",
+        &DayEntry {
+            position: Some("H".into()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    tl
+}
+
+fn calendar(tl: &Timelog) -> String {
+    let events = db::list_events(tl.connection()).unwrap();
+    let sessions = db::list_sessions(tl.connection(), None, Some("H")).unwrap();
+    let stamp = Utc.with_ymd_and_hms(2025, 10, 8, 12, 0, 0).unwrap();
+    build_calendar(&events, &sessions, stamp)
+}
+
+#[test]
+fn test_pairs_and_absences_become_vevents() {
+    let tl = open();
+    // long lines are folded with CRLF + space
+    let ics = calendar(&tl).replace("\r\n ", "");
+
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+    assert!(ics.contains("DTSTART:20251003T090000\r\nDTEND:20251003T123000"));
+    assert!(ics.contains("DTSTART:20251003T133000\r\nDTEND:20251003T180000"));
+    assert!(ics.contains("SUMMARY:Work (Remote)"));
+    assert!(ics.contains("DESCRIPTION:Position: Remote\\nLunch: 30 min\\nWorked: 04h 00m"));
+    assert!(ics.contains("DTSTAMP:20251008T120000Z"));
+    assert!(ics.contains(
+        "UID:absence-2025-10-07@rtimelogger\r\nDTSTAMP:20251008T120000Z\r\nDTSTART;VALUE=DATE:20251007\r\nDTEND;VALUE=DATE:20251008"
+    ));
+    assert!(!ics.contains("20251006"));
+}
+
+#[test]
+fn test_uids_are_stable_across_edits() {
+    let mut tl = open();
+    let uids = |ics: &str| -> Vec<String> {
+        ics.lines()
+            .filter(|l| l.starts_with("UID:"))
+            .map(str::to_string)
+            .collect()
+    };
+    let before = calendar(&tl);
+    let edit = DayEntry {
+        end: Some("18:30".into()),
+        ..Default::default()
+    };
+    tl.edit_pair("2025-10-03", 2, &edit).unwrap();
+    let after = calendar(&tl);
+
+    assert_ne!(before, after);
+    assert!(after.contains("DTEND:20251003T183000"));
+    assert_eq!(uids(&before), uids(&after));
+}
+
+#[test]
+fn test_text_escaping_and_folding() {
+    assert_eq!(escape_text("a,b;c\\d\ne"), "a\\,b\\;c\\\\d\\ne");
+    let events = db::list_events(open().connection()).unwrap();
+    let ics = build_calendar(&events, &[], Utc::now());
+    assert!(ics.split("\r\n").all(|l| l.len() <= 75));
+}
+
+#[test]
+fn test_cli_export_ics() {
+    let db_path = setup_test_db("export_ics");
+    init_db_with_data(&db_path);
+    let out = temp_out("export_ics", "ics");
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "export", "--format", "ics", "--file", &out,
+        ])
+        .args(["--range", "2025-09"])
+        .assert()
+        .success()
+        .stdout(contains("Exported calendar"));
+    let ics = fs::read_to_string(&out).unwrap();
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+    assert!(ics.contains("DTSTART:20250915T090000"));
+
+    // DTSTAMP follows --now-override, so the calendar is reproducible
+    let export = || {
+        Command::cargo_bin("rtimelogger")
+            .unwrap()
+            .args(["--db", &db_path, "--now-override", "2025-10-08T14:00"])
+            .args([
+                "export", "--format", "ics", "--file", "-", "--range", "2025-09",
+            ])
+            .output()
+            .unwrap()
+            .stdout
+    };
+    let first = String::from_utf8(export()).unwrap();
+    assert!(first.contains("DTSTAMP:20251008T"));
+    assert_eq!(first.as_bytes(), export().as_slice());
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "export", "--format", "ics", "--file", &out,
+        ])
+        .args(["--sessions", "--force"])
+        .assert()
+        .code(2);
+}
+
+const CALENDAR: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+UID:vac-1\r
+SUMMARY:Summer vacation\r
+CATEGORIES:Vacation,Personal\r
+DTSTART;VALUE=DATE:20250801\r
+DTEND;VALUE=DATE:20250806\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:closure-1\r
+SUMMARY:Company closure\\, Ferragosto\r
+DTSTART;VALUE=DATE:20250815\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:meeting-1\r
+SUMMARY:Team meeting\r
+DTSTART:20250807T100000\r
+DTEND:20250807T110000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:doctor-1\r
+SUMMARY:Doctor (leave)\r
+CATEGORIES:Leave\r
+DTSTART:20250808T083000\r
+DTEND:20250808T120000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:cancelled-1\r
+SUMMARY:Cancelled trip\r
+STATUS:CANCELLED\r
+DTSTART;VALUE=DATE:20250820\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+fn plan_dates(plan: &[rtimelogger::ics::ImportDay], action: ImportAction) -> Vec<String> {
+    plan.iter()
+        .filter(|d| d.action == action)
+        .map(|d| d.date.clone())
+        .collect()
+}
+
+#[test]
+fn test_parse_calendar() {
+    let events = parse_calendar(CALENDAR).unwrap();
+    assert_eq!(events.len(), 5);
+    let vacation = &events[0];
+    assert!(vacation.all_day);
+    assert_eq!(vacation.categories, ["Vacation", "Personal"]);
+    // DTEND of all-day events is exclusive
+    assert_eq!(vacation.end.to_string(), "2025-08-05");
+    assert_eq!(vacation.days().count(), 5);
+    assert_eq!(events[1].summary, "Company closure, Ferragosto");
+    assert_eq!(events[1].start, events[1].end);
+    assert!(!events[2].all_day);
+    assert!(events[4].cancelled);
+
+    // folded lines are joined back
+    let folded = "BEGIN:VEVENT\r\nSUMMARY:Long\r\n  holiday\r\nDTSTART;VALUE=DATE:20250101\r\nEND:VEVENT\r\n";
+    assert_eq!(parse_calendar(folded).unwrap()[0].summary, "Long holiday");
+    assert!(parse_calendar("BEGIN:VEVENT\r\nSUMMARY:x\r\nEND:VEVENT\r\n").is_err());
+}
+
+#[test]
+fn test_plan_import_filters() {
+    let mut tl = open();
+    let events = parse_calendar(CALENDAR).unwrap();
+    tl.punch_in("2025-08-04", "09:00", None).unwrap();
+
+    // default: all-day events only, weekends skipped (2025-08-02/03)
+    let plan = plan_import(
+        tl.connection(),
+        &events,
+        &ImportFilter::default(),
+        "H",
+        false,
+    )
+    .unwrap();
+    assert_eq!(
+        plan_dates(&plan, ImportAction::Create),
+        ["2025-08-01", "2025-08-05", "2025-08-15"]
+    );
+    assert_eq!(plan_dates(&plan, ImportAction::HasPunches), ["2025-08-04"]);
+
+    // a category also selects timed events
+    let leave = ImportFilter {
+        categories: vec!["leave".into()],
+        ..Default::default()
+    };
+    let plan = plan_import(tl.connection(), &events, &leave, "H", false).unwrap();
+    assert_eq!(plan_dates(&plan, ImportAction::Create), ["2025-08-08"]);
+
+    let closures = ImportFilter {
+        summary: Some(Regex::new("(?i)closure").unwrap()),
+        range: Some(("2025-08-01".into(), "2025-08-31".into())),
+        include_weekends: true,
+        ..Default::default()
+    };
+    let plan = plan_import(tl.connection(), &events, &closures, "H", false).unwrap();
+    assert_eq!(plan_dates(&plan, ImportAction::Create), ["2025-08-15"]);
+
+    let weekends = ImportFilter {
+        include_weekends: true,
+        range: Some(("2025-08-02".into(), "2025-08-03".into())),
+        ..Default::default()
+    };
+    let plan = plan_import(tl.connection(), &events, &weekends, "H", false).unwrap();
+    assert_eq!(plan.len(), 2);
+
+    lock::lock_period(tl.connection(), tl.clock(), "2025-08").unwrap();
+    let plan = plan_import(
+        tl.connection(),
+        &events,
+        &ImportFilter::default(),
+        "H",
+        false,
+    )
+    .unwrap();
+    assert_eq!(plan_dates(&plan, ImportAction::Locked).len(), 3);
+    let plan = plan_import(
+        tl.connection(),
+        &events,
+        &ImportFilter::default(),
+        "H",
+        true,
+    )
+    .unwrap();
+    assert!(plan_dates(&plan, ImportAction::Locked).is_empty());
+}
+
+#[test]
+fn test_cli_import_ics() {
+    let db_path = setup_test_db("import_ics");
+    init_db_with_data(&db_path);
+    let file = temp_out("import_ics", "ics");
+    fs::write(&file, CALENDAR).unwrap();
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "import", "--file", &file, "--dry-run"])
+        .assert()
+        .success()
+        .stdout(contains("2025-08-01 Fri | Summer vacation | would create"))
+        .stdout(contains("4 day(s) would be imported"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "list", "--period", "2025-08"])
+        .assert()
+        .success()
+        .stdout(contains("2025-08-01").not());
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "import", "--format", "ics", "--file", &file,
+        ])
+        .args(["--range", "2025-08"])
+        .assert()
+        .success()
+        .stdout(contains("Imported 4 day(s)"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "--output", "csv", "list", "--period", "2025-08",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("2025-08-01,H"))
+        .stdout(contains("2025-08-15,H"));
+
+    // re-importing changes nothing
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "--output", "json", "import", "--file", &file,
+        ])
+        .assert()
+        .success()
+        .stdout(contains("\"action\":\"exists\""))
+        .stdout(contains("\"action\":\"create\"").not());
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "import",
+            "--file",
+            &file,
+            "--summary",
+            "(",
+        ])
+        .assert()
+        .code(2)
+        .stderr(contains("invalid --summary regex"));
+}
@@ -358,8 +358,8 @@ fn test_list_sessions_invalid_period() {
         .unwrap()
         .args(["--db", &db_path, "list", "--period", "2025-9"])
         .assert()
-        .failure()
-        .stderr(contains("InvalidQuery"));
+        .code(2)
+        .stderr(contains("Invalid period"));
 }
 
 #[test]
@@ -538,7 +538,9 @@ fn test_list_sessions_positions_with_colors() {
         // List filtrato per posizione → deve contenere label e colore
         Command::cargo_bin("rtimelogger")
             .unwrap()
-            .args(["--db", &db_path, "--test", "list", "--pos", pos])
+            .args([
+                "--db", &db_path, "--test", "--color", "always", "list", "--pos", pos,
+            ])
             .assert()
             .success()
             .stdout(contains(label))
@@ -611,14 +613,13 @@ fn test_delete_nonexistent_session() {
         .assert()
         .success();
 
-    // Try to delete a date that does not exist: confirm with 'y' and expect 0 rows deleted
+    // Try to delete a date that does not exist: a validation error, nothing asked
     Command::cargo_bin("rtimelogger")
         .unwrap()
         .args(["--db", &db_path, "--test", "del", "2099-01-01"])
-        .write_stdin("y\n")
         .assert()
-        .success() // the command should not error
-        .stdout(contains("No events or work_sessions found for date"));
+        .code(2)
+        .stderr(contains("No events or work_sessions found for date"));
 }
 
 #[test]
@@ -901,8 +902,8 @@ fn test_delete_nonexistent_pair() {
             "2025-10-03",
         ])
         .assert()
-        .success()
-        .stdout(contains("Pair 5 not found for date 2025-10-03"));
+        .code(2)
+        .stderr(contains("Pair 5 not found for date 2025-10-03"));
 }
 
 #[test]
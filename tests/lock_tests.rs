@@ -0,0 +1,265 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db};
+use predicates::str::contains;
+use rtimelogger::audit::{LogFilter, query_log};
+use rtimelogger::clock::FixedClock;
+use rtimelogger::config::Config;
+use rtimelogger::error::ValidationError;
+use rtimelogger::lock;
+use rtimelogger::timelog::DayEntry;
+use rtimelogger::{Error, Timelog};
+use rusqlite::Connection;
+
+fn open() -> Timelog {
+    let conn = Connection::open_in_memory().expect("open in-memory");
+    let mut tl = Timelog::new(conn, Config::default()).expect("timelog");
+    tl.set_clock(FixedClock::parse("2025-10-03T18:00").unwrap());
+    tl.punch_in("2025-09-30", "09:00", Some("O")).unwrap();
+    tl.punch_out("2025-09-30", "17:30", Some(30)).unwrap();
+    tl.punch_in("2025-10-01", "09:00", Some("R")).unwrap();
+    tl
+}
+
+fn is_locked_error<T: std::fmt::Debug>(r: rtimelogger::Result<T>) -> bool {
+    matches!(r, Err(Error::Validation(ValidationError::Locked { .. })))
+}
+
+#[test]
+fn test_month_range() {
+    assert_eq!(
+        lock::month_range("2024-02").unwrap(),
+        ("2024-02-01".to_string(), "2024-02-29".to_string())
+    );
+    assert_eq!(lock::month_range("2025-12").unwrap().1, "2025-12-31");
+    assert!(lock::month_range("2025").is_err());
+    assert!(lock::month_range("2025-13").is_err());
+}
+
+#[test]
+fn test_locked_month_refuses_changes() {
+    let mut tl = open();
+    let locked = lock::lock_period(tl.connection(), tl.clock(), "2025-09").unwrap();
+    assert_eq!(locked.events, 2);
+    assert_eq!(locked.checksum.len(), 64);
+    assert!(matches!(
+        lock::lock_period(tl.connection(), tl.clock(), "2025-09"),
+        Err(Error::Validation(_))
+    ));
+
+    let edit = DayEntry {
+        end: Some("18:00".into()),
+        ..Default::default()
+    };
+    assert!(is_locked_error(tl.edit_pair("2025-09-30", 1, &edit)));
+    assert!(is_locked_error(tl.punch_in("2025-09-29", "09:00", None)));
+    assert!(is_locked_error(tl.delete_pair("2025-09-30", 1)));
+    assert!(is_locked_error(tl.delete_day("2025-09-30")));
+    let out_id = tl.day_summary("2025-09-30").unwrap().events[1].event.id;
+    assert!(is_locked_error(rtimelogger::db::set_event_time(
+        tl.connection(),
+        out_id,
+        "18:00"
+    )));
+    assert!(is_locked_error(rtimelogger::db::delete_events_by_date(
+        tl.connection(),
+        "2025-09-30"
+    )));
+
+    // the next month is still open
+    tl.punch_out("2025-10-01", "17:00", None).unwrap();
+    assert!(lock::verify(tl.connection(), "2025-09").unwrap().is_valid());
+
+    // after unlocking, changes are accepted again
+    lock::unlock_period(tl.connection(), tl.clock(), "2025-09").unwrap();
+    tl.edit_pair("2025-09-30", 1, &edit).unwrap();
+    assert!(matches!(
+        lock::unlock_period(tl.connection(), tl.clock(), "2025-09"),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn test_locked_month_refuses_session_setters() {
+    use rtimelogger::db;
+    let mut tl = open();
+    lock::lock_period(tl.connection(), tl.clock(), "2025-09").unwrap();
+    let conn = tl.connection();
+    let day = "2025-09-30";
+    let session_id = db::list_sessions_by_date(conn, day).unwrap()[0].id;
+
+    assert!(is_locked_error(db::add_session(
+        conn,
+        "2025-09-29",
+        "O",
+        "09:00",
+        30,
+        "17:00"
+    )));
+    assert!(is_locked_error(db::delete_session(conn, session_id)));
+    assert!(is_locked_error(db::delete_sessions_by_date(conn, day)));
+    assert!(is_locked_error(db::upsert_position(conn, day, "R")));
+    assert!(is_locked_error(db::upsert_start(conn, day, "08:00")));
+    assert!(is_locked_error(db::upsert_lunch(conn, day, 45)));
+    assert!(is_locked_error(db::upsert_end(conn, day, "18:00")));
+    assert!(is_locked_error(db::force_set_position(conn, day, "R")));
+    assert!(is_locked_error(db::force_set_start(conn, day, "08:00")));
+    assert!(is_locked_error(db::force_set_end(conn, day, "18:00")));
+    assert!(is_locked_error(db::force_set_lunch(conn, day, 45)));
+    let clock = FixedClock::parse("2025-10-03T18:00").unwrap();
+    assert!(is_locked_error(rtimelogger::events::create_missing_event(
+        tl.connection_mut(),
+        day,
+        "12:00",
+        "out",
+        &None,
+        None,
+        &Config::default(),
+        &clock,
+    )));
+
+    // nothing was written
+    assert!(lock::verify(tl.connection(), "2025-09").unwrap().is_valid());
+    let session = &db::list_sessions_by_date(tl.connection(), day).unwrap()[0];
+    assert_eq!((session.start.as_str(), session.lunch), ("09:00", 30));
+}
+
+#[test]
+fn test_override_is_logged_and_breaks_checksum() {
+    let mut tl = open();
+    lock::lock_period(tl.connection(), tl.clock(), "2025-09").unwrap();
+    let edit = DayEntry {
+        end: Some("18:00".into()),
+        ..Default::default()
+    };
+
+    let overrides = |tl: &Timelog| {
+        query_log(
+            tl.connection(),
+            &LogFilter {
+                operations: vec![lock::OVERRIDE_OPERATION.into()],
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+
+    tl.set_force(true);
+    // a forced change that fails is rolled back together with its override row
+    assert!(tl.edit_pair("2025-09-30", 1, &DayEntry::default()).is_err());
+    assert!(overrides(&tl).is_empty());
+    tl.edit_pair("2025-09-30", 1, &edit).unwrap();
+    tl.set_force(false);
+
+    let overrides = overrides(&tl);
+    assert_eq!(overrides.len(), 1);
+    assert_eq!(overrides[0].target, "2025-09");
+    assert!(overrides[0].message.contains("edit on 2025-09-30"));
+
+    let v = lock::verify(tl.connection(), "2025-09").unwrap();
+    assert!(!v.is_valid());
+    assert_eq!(v.expected_events, v.actual_events);
+
+    // undo touches the locked date as well
+    assert!(is_locked_error(tl.undo(1)));
+    // the clearance of the forced edit does not outlive it
+    assert!(is_locked_error(rtimelogger::db::delete_events_by_date(
+        tl.connection(),
+        "2025-09-30"
+    )));
+    tl.set_force(true);
+    tl.undo(1).unwrap();
+    assert!(lock::verify(tl.connection(), "2025-09").unwrap().is_valid());
+}
+
+#[test]
+fn test_cli_period_lock_and_force() {
+    let db_path = setup_test_db("period_lock");
+    init_db_with_data(&db_path);
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "period", "lock", "2025-09"])
+        .assert()
+        .success()
+        .stdout(contains("Period 2025-09 locked"))
+        .stdout(contains("sha256: "));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "add",
+            "2025-09-15",
+            "--edit",
+            "--pair",
+            "1",
+        ])
+        .args(["--out", "18:00"])
+        .assert()
+        .code(2)
+        .stderr(contains("locked period 2025-09"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "--yes", "del", "2025-09-15"])
+        .assert()
+        .code(2);
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "period", "verify", "2025-09"])
+        .assert()
+        .success()
+        .stdout(contains("matches its lock"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "add",
+            "2025-09-15",
+            "--edit",
+            "--pair",
+            "1",
+        ])
+        .args(["--out", "18:00", "--force"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "log", "--operation", "lock_override"])
+        .assert()
+        .success()
+        .stdout(contains("Forced edit on 2025-09-15"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "period", "verify", "2025-09"])
+        .assert()
+        .code(2)
+        .stderr(contains("Checksum mismatch"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "--output", "json", "period", "list"])
+        .assert()
+        .success()
+        .stdout(contains("\"period\":\"2025-09\""));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "period", "unlock", "2025-09"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "period", "list"])
+        .assert()
+        .success()
+        .stdout(contains("No locked periods"));
+}
@@ -0,0 +1,94 @@
+mod common;
+use assert_cmd::Command;
+use common::{setup_test_db, temp_out};
+use predicates::str::contains;
+use rtimelogger::ods::{MIME_TYPE, OdsCell, content_xml};
+use std::fs::File;
+use std::io::Read;
+
+fn rtl(db_path: &str, args: &[&str]) {
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", db_path])
+        .args(args)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_cell_types() {
+    assert_eq!(OdsCell::parse("start_time", ""), OdsCell::Empty);
+    assert!(matches!(
+        OdsCell::parse("date", "2025-09-01"),
+        OdsCell::Date(_)
+    ));
+    assert_eq!(OdsCell::parse("start_time", "09:30"), OdsCell::Time(570));
+    assert_eq!(
+        OdsCell::parse("work_duration", "07h 30m"),
+        OdsCell::Duration(450)
+    );
+    assert_eq!(OdsCell::parse("lunch_break", "30"), OdsCell::Float(30.0));
+    assert_eq!(
+        OdsCell::parse("position", "Office"),
+        OdsCell::Text("Office".into())
+    );
+}
+
+#[test]
+fn test_content_xml_escapes_text() {
+    let xml = content_xml("sessions", &["position"], &[vec!["R&D <lab>".into()]]);
+    assert!(xml.contains("<text:p>R&amp;D &lt;lab&gt;</text:p>"));
+    assert!(xml.contains(r#"table:name="sessions""#));
+}
+
+#[test]
+fn test_cli_export_ods() {
+    let db_path = setup_test_db("ods_export");
+    rtl(&db_path, &["--test", "init"]);
+    rtl(
+        &db_path,
+        &["add", "2025-09-01", "O", "09:00", "30", "17:00"],
+    );
+
+    let out = temp_out("ods_export", "ods");
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "export",
+            "--format",
+            "ods",
+            "--file",
+            &out,
+            "--sessions",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("ODS export completed (1 row(s))"));
+
+    let mut archive = zip::ZipArchive::new(File::open(&out).unwrap()).unwrap();
+    // the mimetype entry comes first and is stored uncompressed
+    {
+        let mut first = archive.by_index(0).unwrap();
+        assert_eq!(first.name(), "mimetype");
+        assert_eq!(first.compression(), zip::CompressionMethod::Stored);
+        let mut mime = String::new();
+        first.read_to_string(&mut mime).unwrap();
+        assert_eq!(mime, MIME_TYPE);
+    }
+    assert!(archive.by_name("META-INF/manifest.xml").is_ok());
+
+    let mut xml = String::new();
+    archive
+        .by_name("content.xml")
+        .unwrap()
+        .read_to_string(&mut xml)
+        .unwrap();
+    assert!(xml.contains(r#"table:name="sessions""#));
+    assert!(xml.contains(r#"table:style-name="ce_header""#));
+    assert!(xml.contains(r#"office:date-value="2025-09-01""#));
+    assert!(xml.contains(r#"office:time-value="PT09H00M00S""#));
+    assert!(xml.contains(r#"office:time-value="PT07H30M00S""#));
+    assert!(xml.contains(r#"office:value-type="float" office:value="30""#));
+}
@@ -0,0 +1,102 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db};
+use rtimelogger::output::{LogRecord, OutputFormat, write_rows};
+
+fn run(db_path: &str, args: &[&str]) -> String {
+    let out = Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", db_path])
+        .args(args)
+        .output()
+        .expect("run rtimelogger");
+    assert!(out.status.success(), "command failed: {:?}", args);
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn test_list_json_lines_are_parseable() {
+    let db_path = setup_test_db("output_list_json");
+    init_db_with_data(&db_path);
+
+    let stdout = run(
+        &db_path,
+        &["--output", "json", "list", "--period", "2025-09"],
+    );
+    let rows: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|l| serde_json::from_str(l).expect("valid JSON line"))
+        .collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["date"], "2025-09-01");
+    assert_eq!(rows[0]["position"], "O");
+    assert_eq!(rows[0]["start"], "09:00");
+    assert_eq!(rows[0]["end"], "17:00");
+    assert_eq!(rows[0]["surplus_minutes"], -30);
+}
+
+#[test]
+fn test_events_and_summary_csv() {
+    let db_path = setup_test_db("output_events_csv");
+    init_db_with_data(&db_path);
+
+    let stdout = run(&db_path, &["--output", "csv", "list", "--events"]);
+    let mut lines = stdout.lines();
+    assert_eq!(
+        lines.next(),
+        Some("id,date,time,kind,position,lunch_break,source,pair,unmatched")
+    );
+    assert_eq!(lines.count(), 4);
+
+    let stdout = run(
+        &db_path,
+        &["--output", "csv", "list", "--events", "--summary"],
+    );
+    let mut lines = stdout.lines();
+    assert_eq!(
+        lines.next(),
+        Some("date,pair,position,start,end,lunch_minutes,duration_minutes,unmatched")
+    );
+    assert_eq!(
+        lines.next(),
+        Some("2025-09-01,1,O,09:00,17:00,30,450,false")
+    );
+}
+
+#[test]
+fn test_log_json_has_no_decorations() {
+    let db_path = setup_test_db("output_log_json");
+    init_db_with_data(&db_path);
+
+    let stdout = run(&db_path, &["--output", "json", "log", "--print"]);
+    assert!(!stdout.is_empty());
+    for line in stdout.lines() {
+        let v: serde_json::Value = serde_json::from_str(line).expect("valid JSON line");
+        assert!(v.get("operation").is_some());
+    }
+}
+
+#[test]
+fn test_write_rows_formats() {
+    let rows = vec![LogRecord {
+        id: 1,
+        date: "2025-10-03".into(),
+        operation: "add".into(),
+        target: String::new(),
+        message: "a, b".into(),
+        user: None,
+        state: Some("done".into()),
+        changes: String::new(),
+    }];
+
+    let mut buf = Vec::new();
+    write_rows(&mut buf, OutputFormat::Csv, &rows).unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        "id,date,operation,target,message,user,state,changes\n1,2025-10-03,add,,\"a, b\",,done,\n"
+    );
+
+    let mut buf = Vec::new();
+    write_rows(&mut buf, OutputFormat::Table, &rows).unwrap();
+    assert!(buf.is_empty());
+}
@@ -0,0 +1,159 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, populate_many_sessions, setup_test_db, temp_out};
+use predicates::str::contains;
+use rtimelogger::config::Config;
+use rtimelogger::export::{ExportFilter, pdf_summary};
+use rtimelogger::pdf_manager::PdfManager;
+use rusqlite::Connection;
+use std::fs;
+
+fn rows(n: usize) -> Vec<Vec<String>> {
+    (0..n)
+        .map(|i| {
+            vec![
+                i.to_string(),
+                format!("2025-10-{:02}", i % 28 + 1),
+                "Zoë → café".to_string(),
+            ]
+        })
+        .collect()
+}
+
+fn count(haystack: &[u8], needle: &str) -> usize {
+    haystack
+        .windows(needle.len())
+        .filter(|w| *w == needle.as_bytes())
+        .count()
+}
+
+#[test]
+fn test_table_continues_on_new_pages() {
+    let mut pdf = PdfManager::new();
+    pdf.set_title("Events 2025-10");
+    pdf.write_table(&["id", "date", "note"], &rows(40));
+    assert_eq!(pdf.page_count(), 1);
+
+    let mut pdf = PdfManager::new();
+    pdf.write_table(&["id", "date", "note"], &rows(200));
+    pdf.write_summary("Summary", &[("Total worked".into(), "10h 00m".into())]);
+    let pages = pdf.page_count();
+    assert!(pages >= 4, "only {} pages", pages);
+
+    let bytes = pdf.finish().unwrap();
+    assert!(bytes.starts_with(b"%PDF-"));
+    assert_eq!(count(&bytes, "/Type /Page\n"), pages);
+    assert_eq!(count(&bytes, &format!("/Count {}", pages)), 1);
+    assert_eq!(count(&bytes, "/Contents"), pages);
+}
+
+#[test]
+fn test_unicode_font_is_embedded() {
+    let mut pdf = PdfManager::new();
+    pdf.write_table(&["name"], &[vec!["Zoë → café".to_string()]]);
+    let bytes = pdf.finish().unwrap();
+
+    assert_eq!(count(&bytes, "Helvetica"), 0);
+    assert_eq!(count(&bytes, "/Subtype /Type0"), 1);
+    assert_eq!(count(&bytes, "/FontFile2"), 1);
+    assert_eq!(count(&bytes, "/ToUnicode"), 1);
+    assert_eq!(count(&bytes, "DejaVuSans"), 3);
+    // the subset is much smaller than the bundled font (~740 KB)
+    assert!(bytes.len() < 60_000, "{} bytes", bytes.len());
+}
+
+#[test]
+fn test_text_width_follows_content() {
+    let pdf = PdfManager::new();
+    let narrow = pdf.text_width("iiii", 10.0);
+    let wide = pdf.text_width("WWWW", 10.0);
+    assert!(narrow > 0.0 && narrow < wide);
+    assert!((pdf.text_width("WWWW", 20.0) - 2.0 * wide).abs() < 0.01);
+}
+
+#[test]
+fn test_pdf_summary_totals() {
+    let db_path = setup_test_db("pdf_summary");
+    init_db_with_data(&db_path);
+    let conn = Connection::open(&db_path).unwrap();
+
+    let lines = pdf_summary(&conn, &Config::default(), &ExportFilter::default()).unwrap();
+    let value = |label: &str| {
+        lines
+            .iter()
+            .find(|(l, _)| l == label)
+            .map(|(_, v)| v.clone())
+            .unwrap()
+    };
+    assert_eq!(value("Total worked"), "15h 00m");
+    assert_eq!(value("Surplus"), "-01h 00m");
+    assert_eq!(value("Days"), "2");
+    assert_eq!(value("  Office"), "2");
+
+    let range = Some(("2025-09-10".to_string(), "2025-09-30".to_string()));
+    let lines = pdf_summary(&conn, &Config::default(), &ExportFilter::range(range)).unwrap();
+    assert!(lines.contains(&("Days".to_string(), "1".to_string())));
+}
+
+#[test]
+fn test_pdf_summary_follows_position_filter() {
+    let db_path = setup_test_db("pdf_summary_pos");
+    init_db_with_data(&db_path);
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "add",
+            "2025-09-16",
+            "R",
+            "08:00",
+            "30",
+            "17:30",
+        ])
+        .assert()
+        .success();
+    let conn = Connection::open(&db_path).unwrap();
+
+    let remote = ExportFilter {
+        pos: Some("R".into()),
+        ..Default::default()
+    };
+    let lines = pdf_summary(&conn, &Config::default(), &remote).unwrap();
+    let value = |label: &str| {
+        lines
+            .iter()
+            .find(|(l, _)| l == label)
+            .map(|(_, v)| v.clone())
+    };
+    assert_eq!(value("Total worked").as_deref(), Some("09h 00m"));
+    assert_eq!(value("Surplus").as_deref(), Some("01h 00m"));
+    assert_eq!(value("Days").as_deref(), Some("1"));
+    assert_eq!(value("  Office"), None);
+}
+
+#[test]
+fn test_cli_export_pdf_is_not_truncated() {
+    let db_path = setup_test_db("export_pdf_pages");
+    populate_many_sessions(&db_path, 150);
+    let out = temp_out("export_pdf_pages", "pdf");
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "export",
+            "--format",
+            "pdf",
+            "--file",
+            &out,
+            "--sessions",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("PDF export completed (4 page(s))"));
+
+    let bytes = fs::read(&out).unwrap();
+    assert_eq!(count(&bytes, "/Count 4"), 1);
+}
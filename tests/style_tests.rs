@@ -0,0 +1,92 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db};
+use rtimelogger::config::ColorTheme;
+use rtimelogger::style::{self, Palette, parse_color_spec};
+
+fn list_stdout(db_path: &str, extra: &[&str], no_color: Option<&str>) -> String {
+    let mut cmd = Command::cargo_bin("rtimelogger").unwrap();
+    cmd.args(["--db", db_path])
+        .args(extra)
+        .args(["list", "--period", "2025-09"]);
+    match no_color {
+        Some(v) => cmd.env("NO_COLOR", v),
+        None => cmd.env_remove("NO_COLOR"),
+    };
+    let out = cmd.output().expect("run rtimelogger");
+    assert!(out.status.success());
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn test_parse_color_spec() {
+    assert_eq!(parse_color_spec("blue").as_deref(), Some("34"));
+    assert_eq!(parse_color_spec("Bright_Red").as_deref(), Some("91"));
+    assert_eq!(parse_color_spec("gray").as_deref(), Some("90"));
+    assert_eq!(parse_color_spec("bold green").as_deref(), Some("1;32"));
+    assert_eq!(
+        parse_color_spec("on magenta bright_white bold").as_deref(),
+        Some("45;97;1")
+    );
+    assert_eq!(parse_color_spec("45;97;1").as_deref(), Some("45;97;1"));
+    assert_eq!(parse_color_spec("none").as_deref(), Some(""));
+    assert_eq!(parse_color_spec("pink"), None);
+    assert_eq!(parse_color_spec("red on"), None);
+}
+
+#[test]
+fn test_palette_from_theme() {
+    let default = Palette::default();
+    assert_eq!(default.position("O"), "34");
+    assert_eq!(default.position("H"), "45;97;1");
+    assert_eq!(default.surplus(10), "32");
+    assert_eq!(default.surplus(-10), "31");
+    assert_eq!(default.surplus(0), "");
+
+    let theme = ColorTheme {
+        office: "bright_green".to_string(),
+        surplus_negative: "not-a-color".to_string(),
+        ..ColorTheme::default()
+    };
+    let p = Palette::from_theme(&theme);
+    assert_eq!(p.position("O"), "92");
+    assert_eq!(p.position("R"), "36");
+    // invalid spec falls back to the default
+    assert_eq!(p.surplus(-1), "31");
+}
+
+#[test]
+fn test_paint_respects_enabled_flag() {
+    style::set_enabled(true);
+    assert_eq!(style::paint("31", "x"), "\x1b[31mx\x1b[0m");
+    assert_eq!(style::paint("", "x"), "x");
+    style::set_enabled(false);
+    assert_eq!(style::paint("31", "x"), "x");
+}
+
+#[test]
+fn test_piped_output_has_no_colors_by_default() {
+    let db_path = setup_test_db("style_auto");
+    init_db_with_data(&db_path);
+
+    let out = list_stdout(&db_path, &[], None);
+    assert!(out.contains("Office"));
+    assert!(!out.contains('\x1b'), "unexpected escapes: {:?}", out);
+}
+
+#[test]
+fn test_color_always_and_no_color() {
+    let db_path = setup_test_db("style_always");
+    init_db_with_data(&db_path);
+
+    let out = list_stdout(&db_path, &["--color", "always"], None);
+    assert!(out.contains("\x1b[34m"));
+
+    // --color always wins over NO_COLOR, auto and never do not
+    let out = list_stdout(&db_path, &["--color", "always"], Some("1"));
+    assert!(out.contains('\x1b'));
+    let out = list_stdout(&db_path, &["--color", "never"], None);
+    assert!(!out.contains('\x1b'));
+    let out = list_stdout(&db_path, &["--color", "auto"], Some("1"));
+    assert!(!out.contains('\x1b'));
+}
@@ -0,0 +1,103 @@
+mod common;
+use common::temp_out;
+use rtimelogger::config::Config;
+use rtimelogger::export::{
+    Columns, PdfReport, Table, Tabular, export_ods, export_pdf, export_xlsx, to_table,
+};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+/// A dataset defined outside the crate, exported through the same writers.
+#[derive(Serialize)]
+struct BalanceRow {
+    date: String,
+    position: String,
+    surplus: i64,
+}
+
+impl Tabular for BalanceRow {
+    const NAME: &'static str = "balance";
+
+    fn headers() -> Vec<&'static str> {
+        vec!["date", "position", "surplus"]
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.date.clone(),
+            self.position.clone(),
+            self.surplus.to_string(),
+        ]
+    }
+}
+
+fn rows() -> Vec<BalanceRow> {
+    vec![
+        BalanceRow {
+            date: "2025-09-01".into(),
+            position: "Office".into(),
+            surplus: -30,
+        },
+        BalanceRow {
+            date: "2025-10-02".into(),
+            position: "Remote".into(),
+            surplus: 45,
+        },
+    ]
+}
+
+fn part(path: &str, name: &str) -> String {
+    let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+    let mut xml = String::new();
+    archive
+        .by_name(name)
+        .unwrap()
+        .read_to_string(&mut xml)
+        .unwrap();
+    xml
+}
+
+#[test]
+fn test_to_table_follows_headers() {
+    let table = to_table(&rows());
+    assert_eq!(table.len(), 2);
+    assert_eq!(table[0].len(), BalanceRow::headers().len());
+    assert_eq!(table[1], ["2025-10-02", "Remote", "45"]);
+}
+
+#[test]
+fn test_custom_dataset_in_every_table_format() {
+    let table = Table::from_rows(&rows());
+
+    let ods = temp_out("tabular_custom", "ods");
+    export_ods(&table, Path::new(&ods)).unwrap();
+    let content = part(&ods, "content.xml");
+    assert!(content.contains(r#"table:name="balance""#));
+    assert!(content.contains(r#"office:value="-30""#));
+
+    let xlsx = temp_out("tabular_custom", "xlsx");
+    export_xlsx(&table, Path::new(&xlsx), &Config::default()).unwrap();
+    let workbook = part(&xlsx, "xl/workbook.xml");
+    assert!(workbook.contains("name=\"2025-09\"") && workbook.contains("name=\"2025-10\""));
+
+    let pdf = temp_out("tabular_custom", "pdf");
+    export_pdf(&table, Path::new(&pdf), &PdfReport::default()).unwrap();
+    assert!(fs::read(&pdf).unwrap().starts_with(b"%PDF-"));
+}
+
+#[test]
+fn test_column_selection() {
+    let columns = Columns::select::<BalanceRow>(&["surplus".into(), "date".into()]).unwrap();
+    assert_eq!(columns.headers(), ["surplus", "date"]);
+    let table = Table::new(&rows(), &columns);
+    assert_eq!(table.rows[0], ["-30", "2025-09-01"]);
+
+    let err = Columns::select::<BalanceRow>(&["kind".into()]).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Unknown column 'kind' for balance")
+    );
+    assert!(Columns::select::<BalanceRow>(&[]).is_err());
+}
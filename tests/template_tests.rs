@@ -0,0 +1,130 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db, temp_out};
+use predicates::str::contains;
+use rtimelogger::template::{Template, environment};
+use std::fs;
+
+fn export(db_path: &str, args: &[&str]) -> String {
+    let out = Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", db_path, "export", "--file", "-"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn test_helpers() {
+    let env = environment();
+    let render = |src: &str| env.render_str(src, ()).unwrap();
+    assert_eq!(render("{{ 450 | mins2hhmm }}"), "07:30");
+    assert_eq!(render("{{ mins2hhmm(-30) }}"), "-00:30");
+    assert_eq!(render("{{ none | mins2hhmm }}"), "");
+    assert_eq!(render("{{ '2025-09-01' | weekday_str }}"), "Mon");
+    assert_eq!(render("{{ weekday_str('2025-09-01', 'l') }}"), "Monday");
+    assert_eq!(render("{{ 'R' | describe_position }}"), "Remote");
+}
+
+#[test]
+fn test_builtin_templates() {
+    let db_path = setup_test_db("template_builtin");
+    init_db_with_data(&db_path);
+
+    let md = export(&db_path, &["--template", "markdown", "--daily"]);
+    assert!(md.starts_with("# rTimelogger – daily (all dates)"));
+    assert!(
+        md.contains("| date | position | worked_minutes | expected_minutes | surplus_minutes |")
+    );
+    assert!(md.contains("| Mon 2025-09-01 | Office | 07:30 | 08:00 | -00:30 |"));
+    assert!(
+        md.contains("**Totals** (2 counted day(s)): worked 15:00, expected 16:00, surplus -01:00")
+    );
+
+    let html = export(
+        &db_path,
+        &["--template", "html", "--events", "--columns", "date,kind"],
+    );
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<th>date</th><th>kind</th></tr>"));
+    assert!(html.contains("<td>Mon 2025-09-01</td><td>in</td>"));
+
+    assert!(Template::load("html").unwrap().name.ends_with(".html"));
+}
+
+#[test]
+fn test_custom_template_file() {
+    let db_path = setup_test_db("template_custom");
+    init_db_with_data(&db_path);
+
+    let dir = std::env::temp_dir().join("rtimelogger_template_custom");
+    fs::create_dir_all(&dir).unwrap();
+    let tmpl = dir.join("weekly.txt");
+    fs::write(
+        &tmpl,
+        "Hours {{ range }}:\n{% for r in rows %}- {{ weekday_str(r.date, 'l') }}: {{ r.worked_minutes | mins2hhmm }}\n{% endfor %}Balance: {{ totals.surplus_minutes | mins2hhmm }}\nGenerated {{ generated }}\n",
+    )
+    .unwrap();
+
+    let out = temp_out("template_custom", "txt");
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "--now-override",
+            "2025-10-01T08:30",
+            "export",
+            "--file",
+            &out,
+            "--template",
+            tmpl.to_str().unwrap(),
+            "--daily",
+            "--range",
+            "2025-09",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Rendered weekly.txt"));
+    assert_eq!(
+        fs::read_to_string(&out).unwrap(),
+        "Hours 2025-09:\n- Monday: 07:30\n- Monday: 07:30\nBalance: -01:00\nGenerated 2025-10-01 08:30\n"
+    );
+
+    // .html templates are escaped
+    let html = dir.join("note.html");
+    fs::write(&html, "<p>{{ '<b>' }}</p>").unwrap();
+    let rendered = export(&db_path, &["--template", html.to_str().unwrap()]);
+    assert_eq!(rendered, "<p>&lt;b&gt;</p>");
+
+    // template errors are export errors, a missing template a validation error
+    let broken = dir.join("broken.txt");
+    fs::write(&broken, "{% for r in rows %}").unwrap();
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "export", "--file", "-", "--template"])
+        .arg(&broken)
+        .assert()
+        .code(7)
+        .stderr(contains("template error"));
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "export",
+            "--file",
+            "-",
+            "--template",
+            "nope",
+        ])
+        .assert()
+        .code(2)
+        .stderr(contains("Template not found"));
+}
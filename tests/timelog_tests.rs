@@ -0,0 +1,107 @@
+use rtimelogger::config::Config;
+use rtimelogger::timelog::DayEntry;
+use rtimelogger::{Error, Timelog};
+use rusqlite::Connection;
+
+fn open() -> Timelog {
+    let conn = Connection::open_in_memory().expect("open in-memory");
+    Timelog::new(conn, Config::default()).expect("timelog")
+}
+
+#[test]
+fn test_punch_in_out_and_day_summary() {
+    let mut tl = open();
+    let ev_in = tl.punch_in("2025-10-03", "9:00", Some("o")).unwrap();
+    assert_eq!(ev_in.time, "09:00");
+    assert_eq!(ev_in.position, "O");
+
+    let ev_out = tl.punch_out("2025-10-03", "17:30", Some(30)).unwrap();
+    assert!(ev_out.is_out());
+    assert_eq!(ev_out.lunch_break, 30);
+    assert_eq!(ev_out.position, "O", "out inherits the position of the in");
+
+    let day = tl.day_summary("2025-10-03").unwrap();
+    assert_eq!(day.pairs.len(), 1);
+    assert_eq!(day.pairs[0].start, "09:00");
+    assert_eq!(day.pairs[0].end, "17:30");
+    assert_eq!(day.worked_minutes, 8 * 60);
+
+    let session = day.session.expect("legacy session");
+    assert_eq!(session.expected_exit.as_deref(), Some("17:30"));
+    assert_eq!(session.surplus_minutes, Some(0));
+}
+
+#[test]
+fn test_add_validates_before_writing() {
+    let mut tl = open();
+    let entry = DayEntry {
+        position: Some("O".into()),
+        start: Some("09:00".into()),
+        lunch: Some(120),
+        ..Default::default()
+    };
+    let err = tl.add("2025-10-03", &entry).unwrap_err();
+    assert_eq!(err.exit_code(), 2);
+    assert!(tl.day_summary("2025-10-03").unwrap().events.is_empty());
+
+    assert!(matches!(
+        tl.punch_in("2025-13-03", "09:00", None),
+        Err(Error::Validation(_))
+    ));
+    assert!(matches!(
+        tl.punch_in("2025-10-03", "09:00", Some("X")),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn test_edit_and_delete_pair() {
+    let mut tl = open();
+    tl.punch_in("2025-10-03", "09:00", None).unwrap();
+    tl.punch_out("2025-10-03", "12:00", None).unwrap();
+    tl.punch_in("2025-10-03", "13:00", None).unwrap();
+    tl.punch_out("2025-10-03", "17:00", None).unwrap();
+
+    let edit = DayEntry {
+        start: Some("08:30".into()),
+        position: Some("R".into()),
+        ..Default::default()
+    };
+    let edited = tl.edit_pair("2025-10-03", 1, &edit).unwrap();
+    assert_eq!(edited.in_event.unwrap().time, "08:30");
+    assert_eq!(edited.position.as_deref(), Some("M"));
+    assert_eq!(edited.changes.len(), 2);
+
+    assert!(tl.edit_pair("2025-10-03", 7, &edit).is_err());
+
+    assert_eq!(tl.delete_pair("2025-10-03", 1).unwrap(), 2);
+    let day = tl.day_summary("2025-10-03").unwrap();
+    assert_eq!(day.pairs.len(), 1);
+    assert_eq!(day.pairs[0].start, "13:00");
+    assert!(tl.delete_pair("2025-10-03", 5).is_err());
+}
+
+#[test]
+fn test_period_report_and_balance() {
+    let mut tl = open();
+    for (date, end) in [("2025-09-01", "17:30"), ("2025-09-02", "18:00")] {
+        let entry = DayEntry {
+            position: Some("O".into()),
+            start: Some("09:00".into()),
+            lunch: Some(30),
+            end: Some(end.into()),
+        };
+        tl.add(date, &entry).unwrap();
+    }
+    tl.punch_in("2025-10-01", "09:00", None).unwrap();
+
+    let report = tl.period_report(Some("2025-09"), None).unwrap();
+    assert_eq!(report.sessions.len(), 2);
+    assert_eq!(report.total_surplus_minutes, 30);
+
+    let balance = tl.balance(Some("2025")).unwrap();
+    assert_eq!(balance.days, 2, "the open day of October is not counted");
+    assert_eq!(balance.expected_minutes, 2 * 8 * 60);
+    assert_eq!(balance.worked_minutes, 8 * 60 + 8 * 60 + 30);
+    assert_eq!(balance.surplus_minutes, 30);
+}
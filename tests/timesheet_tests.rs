@@ -0,0 +1,114 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db, temp_out};
+use predicates::str::contains;
+use rtimelogger::config::{Config, EmployeeInfo};
+use rtimelogger::timesheet::{month_days, months, render};
+use rusqlite::Connection;
+use std::fs;
+
+#[test]
+fn test_months() {
+    assert_eq!(months("2025-09").unwrap(), ["2025-09"]);
+    let year = months("2025").unwrap();
+    assert_eq!(year.len(), 12);
+    assert_eq!(year[0], "2025-01");
+    assert_eq!(year[11], "2025-12");
+    assert!(months("2025-13").is_err());
+    assert!(months("09-2025").is_err());
+}
+
+#[test]
+fn test_month_days_cover_the_whole_month() {
+    let db_path = setup_test_db("timesheet_days");
+    init_db_with_data(&db_path);
+    let conn = Connection::open(&db_path).unwrap();
+
+    let days = month_days(&conn, &Config::default(), "2025-09").unwrap();
+    assert_eq!(days.len(), 30);
+    assert_eq!(days[0].date, "2025-09-01");
+    assert_eq!(days[29].date, "2025-09-30");
+
+    let monday = &days[0];
+    assert_eq!(monday.weekday, "Mon");
+    assert!(!monday.weekend);
+    assert_eq!(monday.position.as_deref(), Some("O"));
+    assert_eq!(monday.start.as_deref(), Some("09:00"));
+    assert_eq!(monday.end.as_deref(), Some("17:00"));
+    assert_eq!(monday.lunch, Some(30));
+    assert_eq!(monday.worked_minutes, Some(450));
+    assert_eq!(monday.surplus_minutes, Some(-30));
+
+    let saturday = &days[5];
+    assert_eq!(saturday.weekday, "Sat");
+    assert!(saturday.weekend);
+    assert_eq!(saturday.position, None);
+    assert_eq!(saturday.worked_minutes, None);
+}
+
+#[test]
+fn test_render_one_page_per_month() {
+    let db_path = setup_test_db("timesheet_render");
+    init_db_with_data(&db_path);
+    let conn = Connection::open(&db_path).unwrap();
+    let config = Config {
+        employee: EmployeeInfo {
+            name: "Zoë Müller".into(),
+            id: "E-042".into(),
+            company: "ACME".into(),
+            manager: "Jean Dupont".into(),
+        },
+        ..Config::default()
+    };
+
+    let pdf = render(&conn, &config, &months("2025-10").unwrap()).unwrap();
+    assert_eq!(pdf.page_count(), 1);
+    let pdf = render(&conn, &config, &months("2025").unwrap()).unwrap();
+    assert_eq!(pdf.page_count(), 12);
+    assert!(pdf.finish().unwrap().starts_with(b"%PDF-"));
+}
+
+#[test]
+fn test_cli_timesheet() {
+    let db_path = setup_test_db("timesheet_cli");
+    init_db_with_data(&db_path);
+    let out = temp_out("timesheet_cli", "pdf");
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "timesheet", "2025-09", "--file", &out])
+        .assert()
+        .success()
+        .stdout(contains("Timesheet written to"))
+        .stdout(contains("(1 page(s))"));
+    assert!(fs::read(&out).unwrap().starts_with(b"%PDF-"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "timesheet",
+            "2025-09",
+            "--file",
+            "sheet.pdf",
+        ])
+        .assert()
+        .code(2)
+        .stderr(contains("must be absolute"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db",
+            &db_path,
+            "timesheet",
+            "2025-9",
+            "--file",
+            &out,
+            "--force",
+        ])
+        .assert()
+        .code(2)
+        .stderr(contains("Invalid period"));
+}
@@ -0,0 +1,244 @@
+mod common;
+use assert_cmd::Command;
+use chrono::{NaiveDate, Utc};
+use common::{setup_test_db, temp_out};
+use rtimelogger::ics::ImportAction;
+use rtimelogger::tracker::{
+    Interval, TrackerFormat, day_pairs, parse_intervals, parse_report_csv, parse_timewarrior,
+};
+use rusqlite::Connection;
+use std::fs;
+
+fn at(date: &str, time: &str) -> chrono::NaiveDateTime {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .unwrap()
+        .and_time(chrono::NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+}
+
+fn interval(start: (&str, &str), end: (&str, &str), tag: &str) -> Interval {
+    Interval {
+        start: at(start.0, start.1),
+        end: at(end.0, end.1),
+        projects: Vec::new(),
+        tags: vec![tag.to_string()],
+    }
+}
+
+#[test]
+fn test_parse_exports() {
+    let json = r#"[
+        {"id":2,"start":"20251003T070000Z","end":"20251003T103000Z","tags":["work","api"]},
+        {"id":1,"start":"20251003T113000Z"}
+    ]"#;
+    let parsed = parse_timewarrior(json, &Utc).unwrap();
+    assert_eq!(
+        parsed.open,
+        ["20251003T113000Z"],
+        "the open interval is skipped"
+    );
+    let intervals = parsed.intervals;
+    assert_eq!(intervals.len(), 1);
+    assert_eq!(intervals[0].start, at("2025-10-03", "07:00"));
+    assert_eq!(intervals[0].end, at("2025-10-03", "10:30"));
+    assert_eq!(intervals[0].tags, ["work", "api"]);
+
+    let toggl = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n\
+        Ada,ada@example.com,ACME,Website,,Fix login,Yes,2025-10-03,09:00:00,2025-10-03,12:30:00,03:30:00,\"frontend, urgent\"\n";
+    let intervals = parse_report_csv(toggl).unwrap();
+    assert_eq!(intervals[0].projects, ["Website"]);
+    assert_eq!(intervals[0].tags, ["frontend", "urgent"]);
+    assert_eq!(intervals[0].end, at("2025-10-03", "12:30"));
+
+    let clockify = "\u{feff}Project,Client,Description,Task,User,Email,Tags,Billable,Start Date,Start Time,End Date,End Time\n\
+        Support,,Calls,,Ada,ada@example.com,,No,10/03/2025,01:15:00 PM,10/03/2025,05:45:00 PM\n";
+    let intervals = parse_intervals(TrackerFormat::Clockify, clockify, &Utc)
+        .unwrap()
+        .intervals;
+    assert_eq!(intervals[0].start, at("2025-10-03", "13:15"));
+    assert_eq!(intervals[0].end, at("2025-10-03", "17:45"));
+    assert!(intervals[0].tags.is_empty());
+
+    let err = parse_report_csv("Project,Start\nX,09:00\n").unwrap_err();
+    assert!(err.to_string().contains("Start date"));
+}
+
+#[test]
+fn test_day_pairs_merge_lunch_and_split_midnight() {
+    let intervals = vec![
+        interval(("2025-10-03", "09:00"), ("2025-10-03", "10:30"), "a"),
+        // touching: same pair
+        interval(("2025-10-03", "10:30"), ("2025-10-03", "12:30"), "b"),
+        // 45 min break in the lunch window: lunch of the same pair
+        interval(("2025-10-03", "13:15"), ("2025-10-03", "16:00"), "a"),
+        // break after the lunch window: new pair
+        interval(("2025-10-03", "17:00"), ("2025-10-03", "18:00"), "c"),
+        // across midnight
+        interval(("2025-10-04", "22:00"), ("2025-10-05", "01:30"), "night"),
+    ];
+    let pairs: Vec<(String, String, String, i32)> = day_pairs(&intervals, 30..=90)
+        .into_iter()
+        .map(|p| (p.date, p.start, p.end, p.lunch))
+        .collect();
+    let expected = [
+        ("2025-10-03", "09:00", "16:00", 45),
+        ("2025-10-03", "17:00", "18:00", 0),
+        ("2025-10-04", "22:00", "23:59", 0),
+        ("2025-10-05", "00:00", "01:30", 0),
+    ];
+    assert_eq!(
+        pairs,
+        expected
+            .iter()
+            .map(|(d, s, e, l)| (d.to_string(), s.to_string(), e.to_string(), *l))
+            .collect::<Vec<_>>()
+    );
+
+    let first = &day_pairs(&intervals, 30..=90)[0];
+    assert_eq!(first.action, ImportAction::Create);
+    assert_eq!(first.meta.tags, ["a", "b"]);
+    assert_eq!(first.meta.to_meta(), r#"{"tags":["a","b"]}"#);
+
+    // a break longer than the maximum lunch splits the day
+    assert_eq!(day_pairs(&intervals[1..3], 30..=30).len(), 2);
+    // and so does one shorter than the minimum
+    assert_eq!(day_pairs(&intervals[1..3], 60..=90).len(), 2);
+    // up to the configured maximum, however long
+    let long_lunch = [
+        interval(("2025-10-06", "09:00"), ("2025-10-06", "12:00"), "a"),
+        interval(("2025-10-06", "14:00"), ("2025-10-06", "17:00"), "a"),
+    ];
+    assert_eq!(day_pairs(&long_lunch, 30..=120)[0].lunch, 120);
+}
+
+#[test]
+fn test_import_toggl_cli() {
+    let db_path = setup_test_db("tracker_import_toggl");
+    let file = temp_out("tracker_import_toggl", "csv");
+    fs::write(
+        &file,
+        "Project,Tags,Start date,Start time,End date,End time\n\
+         Website,frontend,2025-10-06,09:00:00,2025-10-06,12:30:00\n\
+         Website,,2025-10-06,13:30:00,2025-10-06,17:45:00\n\
+         Website,,2025-10-07,09:00:00,2025-10-07,11:00:00\n",
+    )
+    .unwrap();
+    let import = |extra: &[&str]| {
+        Command::cargo_bin("rtimelogger")
+            .unwrap()
+            .args([
+                "--db", &db_path, "import", "--format", "toggl", "--file", &file,
+            ])
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+
+    let out = import(&["--range", "2025-10-{06..06}", "--pos", "r"]);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Imported 1 pair(s)"));
+
+    let conn = Connection::open(&db_path).unwrap();
+    let rows: Vec<(String, String, String, i32, String, String)> = conn
+        .prepare("SELECT time, kind, position, lunch_break, source, meta FROM events ORDER BY date, time")
+        .unwrap()
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?, r.get(5)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let meta = r#"{"projects":["Website"],"tags":["frontend"]}"#;
+    assert_eq!(
+        rows,
+        vec![
+            (
+                "09:00".into(),
+                "in".into(),
+                "R".into(),
+                0,
+                "toggl".into(),
+                meta.into()
+            ),
+            (
+                "17:45".into(),
+                "out".into(),
+                "R".into(),
+                60,
+                "toggl".into(),
+                meta.into()
+            ),
+        ]
+    );
+
+    // importing again only adds the new day
+    let out = import(&["--output", "json"]);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    // JSON lines, one per planned pair
+    let actions: Vec<String> = String::from_utf8(out.stdout)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap()["action"].to_string())
+        .collect();
+    assert_eq!(actions, [r#""exists""#, r#""create""#]);
+}
+
+#[test]
+fn test_timew_data() {
+    let conn = Connection::open_in_memory().unwrap();
+    let mut tl = rtimelogger::Timelog::new(conn, rtimelogger::config::Config::default()).unwrap();
+    tl.add(
+        "2025-10-03",
+        &rtimelogger::timelog::DayEntry {
+            position: Some("R".into()),
+            start: Some("09:00".into()),
+            lunch: Some(30),
+            end: Some("17:30".into()),
+        },
+    )
+    .unwrap();
+    // unmatched: skipped
+    tl.punch_in("2025-10-06", "09:00", None).unwrap();
+    // already in Timewarrior: not written back
+    let pair = &day_pairs(
+        &[interval(
+            ("2025-10-07", "08:00"),
+            ("2025-10-07", "10:00"),
+            "x",
+        )],
+        30..=90,
+    )[0];
+    tl.import_pair(pair, None, "timewarrior").unwrap();
+
+    let events = rtimelogger::db::list_events(tl.connection()).unwrap();
+    let offset = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+    assert_eq!(
+        rtimelogger::tracker::timew_data(&events, &offset),
+        "inc 20251003T070000Z - 20251003T153000Z # rtimelogger pos:R lunch:30\n"
+    );
+}
+
+#[test]
+fn test_export_timew_cli() {
+    let db_path = setup_test_db("tracker_export_timew");
+    common::init_db_with_data(&db_path);
+    let out = Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "export", "--format", "timew", "--file", "-", "--range", "2025-09",
+        ])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let data = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(data.lines().count(), 2);
+    assert!(
+        data.lines()
+            .all(|l| l.starts_with("inc 202509") && l.ends_with("# rtimelogger pos:O lunch:30"))
+    );
+}
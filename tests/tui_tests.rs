@@ -0,0 +1,155 @@
+use chrono::NaiveDate;
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use rtimelogger::clock::FixedClock;
+use rtimelogger::config::Config;
+use rtimelogger::tui::{App, Focus, Mode, render};
+use rtimelogger::{Error, Timelog};
+use rusqlite::Connection;
+
+fn open() -> Timelog {
+    let conn = Connection::open_in_memory().expect("open in-memory");
+    let mut tl = Timelog::new(conn, Config::default()).expect("timelog");
+    tl.set_clock(FixedClock::parse("2025-10-03T18:00").unwrap());
+    tl.punch_in("2025-10-03", "09:00", Some("O")).unwrap();
+    tl.punch_out("2025-10-03", "17:30", Some(30)).unwrap();
+    tl
+}
+
+fn press(app: &mut App, tl: &mut Timelog, keys: &[KeyCode]) {
+    for k in keys {
+        app.handle_key(tl, KeyEvent::new(*k, KeyModifiers::NONE))
+            .unwrap();
+    }
+}
+
+fn type_text(app: &mut App, tl: &mut Timelog, text: &str) {
+    let keys: Vec<KeyCode> = text.chars().map(KeyCode::Char).collect();
+    press(app, tl, &keys);
+}
+
+fn screen(app: &App) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+    terminal.draw(|f| render(f, app)).unwrap();
+    let buffer = terminal.backend().buffer().clone();
+    buffer
+        .content()
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|c| c.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_navigation_and_render() {
+    let mut tl = open();
+    let mut app = App::new(&tl).unwrap();
+    assert_eq!(app.selected_date(), "2025-10-03");
+    assert_eq!(app.day.pairs.len(), 1);
+
+    let out = screen(&app);
+    assert!(out.contains("October 2025"));
+    assert!(out.contains("09:00"));
+    assert!(out.contains("17:30"));
+    assert!(out.contains("Office"));
+
+    press(&mut app, &mut tl, &[KeyCode::Right]);
+    assert_eq!(app.selected_date(), "2025-10-04");
+    assert!(app.day.pairs.is_empty());
+
+    press(&mut app, &mut tl, &[KeyCode::Char('['), KeyCode::Down]);
+    assert_eq!(app.selected_date(), "2025-09-11");
+    assert!(screen(&app).contains("September 2025"));
+
+    press(&mut app, &mut tl, &[KeyCode::Char('t'), KeyCode::Tab]);
+    assert_eq!(app.selected, NaiveDate::from_ymd_opt(2025, 10, 3).unwrap());
+    assert_eq!(app.focus, Focus::Day);
+
+    press(&mut app, &mut tl, &[KeyCode::Char('q')]);
+    assert!(app.should_quit());
+}
+
+#[test]
+fn test_inline_edit_updates_pair_and_surplus() {
+    let mut tl = open();
+    let mut app = App::new(&tl).unwrap();
+    assert_eq!(app.day_surplus(), Some(0));
+
+    press(&mut app, &mut tl, &[KeyCode::Tab, KeyCode::Char('e')]);
+    assert!(matches!(app.mode, Mode::Edit(_)));
+
+    // Out field: replace 17:30 with 18:00
+    press(&mut app, &mut tl, &[KeyCode::Tab]);
+    press(&mut app, &mut tl, &[KeyCode::Backspace; 5]);
+    type_text(&mut app, &mut tl, "18:00");
+    // Position field: lowercase input is uppercased
+    press(
+        &mut app,
+        &mut tl,
+        &[KeyCode::Tab, KeyCode::Tab, KeyCode::Backspace],
+    );
+    type_text(&mut app, &mut tl, "r");
+    press(&mut app, &mut tl, &[KeyCode::Enter]);
+
+    assert_eq!(app.mode, Mode::Browse);
+    assert_eq!(app.day.pairs[0].end, "18:00");
+    assert_eq!(app.day.pairs[0].position, "R");
+    assert_eq!(app.day_surplus(), Some(30));
+    assert_eq!(app.month.total_surplus_minutes, 30);
+    assert!(screen(&app).contains("+0h 30m"));
+
+    let stored = tl.day_summary("2025-10-03").unwrap();
+    assert_eq!(stored.pairs[0].end, "18:00");
+}
+
+#[test]
+fn test_invalid_edit_keeps_form_open() {
+    let mut tl = open();
+    let mut app = App::new(&tl).unwrap();
+    press(&mut app, &mut tl, &[KeyCode::Tab, KeyCode::Enter]);
+    press(&mut app, &mut tl, &[KeyCode::Backspace; 5]);
+    type_text(&mut app, &mut tl, "25:99");
+    press(&mut app, &mut tl, &[KeyCode::Enter]);
+
+    assert!(matches!(app.mode, Mode::Edit(_)));
+    assert!(app.status.as_deref().unwrap_or("").starts_with("❌"));
+    assert_eq!(
+        tl.day_summary("2025-10-03").unwrap().pairs[0].start,
+        "09:00"
+    );
+
+    press(&mut app, &mut tl, &[KeyCode::Esc]);
+    assert_eq!(app.mode, Mode::Browse);
+}
+
+#[test]
+fn test_delete_requires_confirmation() {
+    let mut tl = open();
+    let mut app = App::new(&tl).unwrap();
+
+    press(&mut app, &mut tl, &[KeyCode::Tab, KeyCode::Char('d')]);
+    assert_eq!(app.mode, Mode::ConfirmDelete(1));
+    assert!(screen(&app).contains("Delete pair 1 of 2025-10-03?"));
+    press(&mut app, &mut tl, &[KeyCode::Char('n')]);
+    assert_eq!(app.day.pairs.len(), 1);
+
+    press(&mut app, &mut tl, &[KeyCode::Char('d'), KeyCode::Char('y')]);
+    assert!(app.day.pairs.is_empty());
+    assert!(matches!(
+        tl.delete_pair("2025-10-03", 1),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn test_tui_requires_terminal() {
+    let db = std::env::temp_dir().join("tui_no_tty_rtimelogger.sqlite");
+    std::fs::remove_file(&db).ok();
+    assert_cmd::Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", db.to_str().unwrap(), "tui"])
+        .assert()
+        .code(2)
+        .stderr(predicates::str::contains("interactive terminal"));
+}
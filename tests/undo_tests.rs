@@ -0,0 +1,150 @@
+mod common;
+use assert_cmd::Command;
+use common::{init_db_with_data, setup_test_db};
+use predicates::str::contains;
+use rtimelogger::config::Config;
+use rtimelogger::timelog::DayEntry;
+use rtimelogger::{Error, Timelog};
+use rusqlite::Connection;
+
+fn open() -> Timelog {
+    let conn = Connection::open_in_memory().expect("open in-memory");
+    let mut tl = Timelog::new(conn, Config::default()).expect("timelog");
+    tl.punch_in("2025-10-03", "09:00", Some("O")).unwrap();
+    tl.punch_out("2025-10-03", "12:30", None).unwrap();
+    tl.punch_in("2025-10-03", "13:30", Some("R")).unwrap();
+    tl.punch_out("2025-10-03", "18:00", None).unwrap();
+    tl
+}
+
+#[test]
+fn test_undo_and_redo_delete_pair() {
+    let mut tl = open();
+    let before = tl.day_summary("2025-10-03").unwrap();
+    assert_eq!(before.pairs.len(), 2);
+
+    tl.delete_pair("2025-10-03", 1).unwrap();
+    assert_eq!(tl.day_summary("2025-10-03").unwrap().pairs.len(), 1);
+
+    let undone = tl.undo(1).unwrap();
+    assert_eq!(undone.len(), 1);
+    assert_eq!(undone[0].operation, "del");
+    let restored = tl.day_summary("2025-10-03").unwrap();
+    assert_eq!(restored.pairs.len(), 2);
+    assert_eq!(restored.pairs[0].start, "09:00");
+    assert_eq!(
+        restored.session.map(|s| s.session),
+        before.session.clone().map(|s| s.session)
+    );
+    // events keep their original ids
+    let ids = |d: &rtimelogger::timelog::DaySummary| {
+        d.events.iter().map(|e| e.event.id).collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&tl.day_summary("2025-10-03").unwrap()), ids(&before));
+
+    tl.redo(1).unwrap();
+    assert_eq!(tl.day_summary("2025-10-03").unwrap().pairs.len(), 1);
+}
+
+#[test]
+fn test_undo_multiple_steps_and_edit() {
+    let mut tl = open();
+    let edit = DayEntry {
+        end: Some("19:00".into()),
+        ..Default::default()
+    };
+    tl.edit_pair("2025-10-03", 2, &edit).unwrap();
+    tl.delete_day("2025-10-03").unwrap();
+    assert!(tl.day_summary("2025-10-03").unwrap().events.is_empty());
+
+    let undone = tl.undo(2).unwrap();
+    let ops: Vec<&str> = undone.iter().map(|r| r.operation.as_str()).collect();
+    assert_eq!(ops, ["del", "edit"]);
+    let day = tl.day_summary("2025-10-03").unwrap();
+    assert_eq!(day.pairs[1].end, "18:00");
+
+    // redo applies the oldest undone operation first
+    let redone = tl.redo(1).unwrap();
+    assert_eq!(redone[0].operation, "edit");
+    assert_eq!(tl.day_summary("2025-10-03").unwrap().pairs[1].end, "19:00");
+}
+
+#[test]
+fn test_new_operation_discards_redo_and_nothing_to_undo() {
+    let conn = Connection::open_in_memory().unwrap();
+    let mut tl = Timelog::new(conn, Config::default()).unwrap();
+    assert!(matches!(tl.undo(1), Err(Error::Validation(_))));
+
+    tl.punch_in("2025-10-06", "09:00", None).unwrap();
+    tl.undo(1).unwrap();
+    assert!(tl.day_summary("2025-10-06").unwrap().events.is_empty());
+
+    tl.punch_in("2025-10-06", "10:00", None).unwrap();
+    assert!(matches!(tl.redo(1), Err(Error::Validation(_))));
+}
+
+#[test]
+fn test_undo_refuses_when_events_changed_outside_log() {
+    let mut tl = open();
+    tl.delete_pair("2025-10-03", 2).unwrap();
+    // the remaining pair is modified behind the log's back
+    rtimelogger::db::set_event_time(tl.connection(), 1, "08:00").unwrap();
+    tl.punch_in("2025-10-04", "09:00", None).unwrap();
+
+    tl.undo(1).unwrap(); // 2025-10-04 punch
+    // the deleted pair's events were not touched: undo still works
+    tl.undo(1).unwrap();
+    assert_eq!(tl.day_summary("2025-10-03").unwrap().pairs.len(), 2);
+
+    // editing again, then changing the edited event directly, blocks the undo
+    let edit = DayEntry {
+        end: Some("12:45".into()),
+        ..Default::default()
+    };
+    tl.edit_pair("2025-10-03", 1, &edit).unwrap();
+    let out_id = tl.day_summary("2025-10-03").unwrap().events[1].event.id;
+    rtimelogger::db::set_event_time(tl.connection(), out_id, "12:50").unwrap();
+    let err = tl.undo(1).unwrap_err();
+    assert!(err.to_string().contains("changed afterwards"), "{}", err);
+}
+
+#[test]
+fn test_cli_undo_redo() {
+    let db_path = setup_test_db("undo_cli");
+    init_db_with_data(&db_path);
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "--yes", "del", "2025-09-15"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "undo"])
+        .assert()
+        .success()
+        .stdout(contains("Undone del"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "list", "--period", "2025-09"])
+        .assert()
+        .success()
+        .stdout(contains("2025-09-15"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "redo", "5"])
+        .assert()
+        .success()
+        .stdout(contains("Redone del"))
+        .stderr(contains("Only 1 of 5"));
+
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", &db_path, "redo"])
+        .assert()
+        .code(2)
+        .stderr(contains("Nothing to redo"));
+}
@@ -3,6 +3,7 @@ mod tests {
     use chrono::{NaiveDate, NaiveDateTime};
     use rtimelogger::utils::{
         date2iso, datetime2iso, describe_position, iso2date, iso2datetime, make_separator,
+        parse_date_expr, parse_date_range,
     };
 
     #[test]
@@ -98,4 +99,53 @@ mod tests {
         assert_eq!(label, "X");
         assert_eq!(color, "\x1b[0m"); // default reset
     }
+
+    #[test]
+    fn test_parse_date_expr() {
+        // Wednesday
+        let today = NaiveDate::from_ymd_opt(2025, 10, 15).unwrap();
+        let day = |expr: &str| date2iso(&parse_date_expr(expr, today).unwrap());
+        assert_eq!(day("2025-09-01"), "2025-09-01");
+        assert_eq!(day("today"), "2025-10-15");
+        assert_eq!(day("Yesterday"), "2025-10-14");
+        assert_eq!(day("wed"), "2025-10-15");
+        assert_eq!(day("mon"), "2025-10-13");
+        assert_eq!(day("last friday"), "2025-10-10");
+        assert_eq!(day("last-wed"), "2025-10-08");
+        assert_eq!(day("next mon"), "2025-10-20");
+        assert_eq!(day("-2d"), "2025-10-13");
+        assert_eq!(day("+1w"), "2025-10-22");
+        assert!(parse_date_expr("2025-02-30", today).is_err());
+        assert!(parse_date_expr("someday", today).is_err());
+        assert!(parse_date_expr("-99999999999d", today).is_err());
+        assert!(parse_date_expr("+9999999999999w", today).is_err());
+        assert!(parse_date_expr("--9223372036854775808d", today).is_err());
+    }
+
+    #[test]
+    fn test_parse_date_range() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 15).unwrap();
+        let period = |expr: &str| parse_date_range(expr, today).unwrap().period();
+        assert_eq!(period("2025"), "2025");
+        assert_eq!(period("2025-02"), "2025-02");
+        assert_eq!(period("last-month"), "2025-09");
+        assert_eq!(period("this year"), "2025");
+        assert_eq!(period("this-week"), "2025-10-13..2025-10-19");
+        assert_eq!(period("2025-W41"), "2025-10-06..2025-10-12");
+        assert_eq!(period("2025-10-{06..08}"), "2025-10-06..2025-10-08");
+        assert_eq!(period("2025-09-15..2025-10-03"), "2025-09-15..2025-10-03");
+        assert_eq!(period("-7d..today"), "2025-10-08..2025-10-15");
+        assert_eq!(period("yesterday"), "2025-10-14..2025-10-14");
+        assert_eq!(
+            parse_date_range("2025-09-15..", today).unwrap().bounds(),
+            ("2025-09-15".to_string(), "9999-12-31".to_string())
+        );
+        assert_eq!(
+            parse_date_range("last-month", today).unwrap().month(),
+            Some("2025-09".into())
+        );
+        assert_eq!(parse_date_range("2025-W41", today).unwrap().month(), None);
+        assert!(parse_date_range("2025-10-03..2025-09-15", today).is_err());
+        assert!(parse_date_range("2025-W54", today).is_err());
+    }
 }
@@ -0,0 +1,132 @@
+mod common;
+use assert_cmd::Command;
+use common::{setup_test_db, temp_out};
+use predicates::str::contains;
+use std::fs::File;
+use std::io::Read;
+
+fn rtl(db_path: &str, args: &[&str]) {
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args(["--db", db_path])
+        .args(args)
+        .assert()
+        .success();
+}
+
+/// Read one XML part of an .xlsx file.
+fn part(path: &str, name: &str) -> String {
+    let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+    let mut xml = String::new();
+    archive
+        .by_name(name)
+        .unwrap()
+        .read_to_string(&mut xml)
+        .unwrap();
+    xml
+}
+
+fn export(name: &str, dataset: &str, months: usize) -> String {
+    let db_path = setup_test_db(name);
+    rtl(&db_path, &["--test", "init"]);
+    rtl(
+        &db_path,
+        &["add", "2025-09-01", "O", "09:00", "30", "17:00"],
+    );
+    rtl(
+        &db_path,
+        &["add", "2025-09-15", "R", "08:30", "45", "18:00"],
+    );
+    rtl(&db_path, &["add", "2025-10-01", "H"]);
+
+    let out = temp_out(name, "xlsx");
+    Command::cargo_bin("rtimelogger")
+        .unwrap()
+        .args([
+            "--db", &db_path, "export", "--format", "xlsx", "--file", &out, dataset,
+        ])
+        .assert()
+        .success()
+        .stdout(contains(format!("{} month sheet(s) and a summary", months)));
+    out
+}
+
+#[test]
+fn test_xlsx_one_sheet_per_month_and_summary() {
+    let out = export("xlsx_sheets", "--sessions", 2);
+
+    let workbook = part(&out, "xl/workbook.xml");
+    let summary = workbook.find("name=\"Summary\"").unwrap();
+    let september = workbook.find("name=\"2025-09\"").unwrap();
+    let october = workbook.find("name=\"2025-10\"").unwrap();
+    assert!(summary < september && september < october);
+
+    // summary rows link the totals of each month sheet, plus a grand total
+    let sheet = part(&out, "xl/worksheets/sheet1.xml");
+    assert!(sheet.contains("<f>'2025-09'!G4</f><v>0.6770833333333334</v>"));
+    assert!(sheet.contains("<f>'2025-10'!G3</f>"));
+    assert!(sheet.contains("<f>SUM(B2:B3)</f><v>3</v>"));
+}
+
+#[test]
+fn test_xlsx_durations_formulas_and_filters() {
+    let out = export("xlsx_durations", "--sessions", 2);
+    let sheet = part(&out, "xl/worksheets/sheet2.xml");
+
+    // 7h 30m and 8h 45m as fractions of a day, not text
+    assert!(sheet.contains("<v>0.3125</v>"));
+    assert!(sheet.contains("<v>0.3645833333333333</v>"));
+    // surplus formula against the 8h default, and SUM totals row
+    assert!(sheet.contains("<f>IF(ISNUMBER(G2),ROUND(G2*1440,0)-480,\"\")</f><v>-30</v>"));
+    assert!(sheet.contains("<f>SUM(G2:G3)</f>"));
+    assert!(sheet.contains("<f>SUM(H2:H3)</f><v>15</v>"));
+    assert!(sheet.contains("<f>SUM(E2:E3)</f><v>75</v>"));
+    // autofilter over header + data, frozen header row
+    assert!(sheet.contains("<autoFilter ref=\"A1:H3\"/>"));
+    assert!(sheet.contains("state=\"frozen\""));
+
+    let styles = part(&out, "xl/styles.xml");
+    assert!(styles.contains("formatCode=\"[h]:mm\""));
+
+    let strings = part(&out, "xl/sharedStrings.xml");
+    assert!(strings.contains("surplus_minutes"));
+    assert!(!strings.contains("07h 30m"));
+}
+
+#[test]
+fn test_xlsx_events_have_no_surplus_column() {
+    // the holiday has no events: September only
+    let out = export("xlsx_events", "--events", 1);
+    let strings = part(&out, "xl/sharedStrings.xml");
+    assert!(!strings.contains("surplus_minutes"));
+
+    let sheet = part(&out, "xl/worksheets/sheet2.xml");
+    assert!(sheet.contains("<autoFilter ref="));
+    // lunch_break is summed
+    assert!(sheet.contains("<f>SUM("));
+}
+
+#[test]
+fn test_xlsx_surplus_follows_list_rules() {
+    let db_path = setup_test_db("xlsx_partial_day");
+    rtl(&db_path, &["--test", "init"]);
+    rtl(
+        &db_path,
+        &["add", "2025-09-01", "O", "09:00", "30", "17:00"],
+    );
+    // morning only: outside the lunch window, so `list` shows no surplus for it
+    rtl(&db_path, &["add", "2025-09-02", "O", "08:00", "0", "12:00"]);
+    // no lunch recorded: `list` still counts the 30 minutes minimum of an office day
+    rtl(&db_path, &["add", "2025-09-03", "O", "09:00", "0", "17:30"]);
+
+    let out = temp_out("xlsx_partial_day", "xlsx");
+    rtl(
+        &db_path,
+        &["export", "--format", "xlsx", "--file", &out, "--sessions"],
+    );
+    let sheet = part(&out, "xl/worksheets/sheet2.xml");
+    assert!(sheet.contains("<f>IF(ISNUMBER(G2),ROUND(G2*1440,0)-480,\"\")</f><v>-30</v>"));
+    assert!(!sheet.contains("ISNUMBER(G3)"));
+    assert!(sheet.contains("<f>IF(ISNUMBER(G4),ROUND(G4*1440,0)-480-30,\"\")</f><v>0</v>"));
+    assert!(sheet.contains("<f>SUM(H2:H4)</f><v>-30</v>"));
+}